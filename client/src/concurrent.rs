@@ -4,26 +4,24 @@ mod worker;
 
 #[derive(Debug)]
 pub struct Client {
-    write: Arc<Mutex<transport::Transport>>,
+    write: Mutex<transport::WriteHalf<Box<dyn transport::Transport>>>,
     pending: Arc<Mutex<HashMap<String, PendingInternal>>>,
     worker: task::JoinHandle<()>,
 }
 
 impl Client {
     pub async fn new(remote: impl Into<face::Uri>) -> io::Result<Self> {
-        let remote = remote.into().to_addr().await?;
-        let local = remote.any();
         let pending = Arc::new(Mutex::new(HashMap::new()));
-        let transport = transport::Transport::new(local, remote)
-            .await
-            .map(Mutex::new)
-            .map(Arc::new)?;
+        let transport = transport::Registry::default()
+            .connect(remote.into(), None)
+            .await?;
+        let (read, write) = transport.split();
 
-        let inner = worker::ClientInternal::new(transport.clone(), pending.clone());
+        let inner = worker::ClientInternal::new(read, pending.clone());
         let worker = tokio::spawn(inner.run());
 
         Ok(Self {
-            write: transport,
+            write: Mutex::new(write),
             pending,
             worker,
         })
@@ -113,3 +111,44 @@ where
 //         }
 //     }
 // }
+
+#[cfg(test)]
+mod tests {
+    use bytes::BytesMut;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use tokio::net::TcpListener;
+    use tokio::time;
+
+    use super::*;
+
+    #[tokio::test]
+    async fn express_while_waiting_for_data() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let uri = format!("tcp4://{}", listener.local_addr().unwrap());
+        let client = Client::new(uri).await.unwrap();
+        let (mut remote, _) = listener.accept().await.unwrap();
+
+        // The worker is already waiting for Data, sending goes ahead regardless
+        let pending = time::timeout(
+            time::Duration::from_secs(1),
+            client.express_interest::<tlv::Name>("/a"),
+        )
+        .await
+        .unwrap()
+        .unwrap();
+
+        let mut buf = BytesMut::new();
+        remote.read_buf(&mut buf).await.unwrap();
+        let interest = <tlv::Interest as tlv::TlvCodec>::decode(&mut buf).unwrap();
+        assert_eq!(interest.name(), "/a");
+
+        let mut data = tlv::Data::new("/a");
+        let content = tlv::TlvCodec::bytes(&interest.name);
+        data.content = Some(tlv::Content::new(&content));
+        remote
+            .write_all(&tlv::TlvCodec::bytes(&data))
+            .await
+            .unwrap();
+        assert_eq!(pending.data().await.unwrap(), interest.name);
+    }
+}
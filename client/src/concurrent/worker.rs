@@ -1,24 +1,24 @@
 use super::*;
 
 pub(super) struct ClientInternal {
-    read: Arc<Mutex<transport::Transport>>,
+    read: transport::ReadHalf<Box<dyn transport::Transport>>,
     pending: Arc<Mutex<HashMap<String, PendingInternal>>>,
 }
 
 impl ClientInternal {
     pub(super) fn new(
-        read: Arc<Mutex<transport::Transport>>,
+        read: transport::ReadHalf<Box<dyn transport::Transport>>,
         pending: Arc<Mutex<HashMap<String, PendingInternal>>>,
     ) -> Self {
         Self { read, pending }
     }
 
-    pub(super) async fn run(self) {
+    /// Deliver the incoming Data until the transport is closed
+    pub(super) async fn run(mut self) {
         tracing::trace!("Entering ClientInternal loop");
-        time::sleep(time::Duration::from_secs(1)).await;
         loop {
-            match self.next_item().await {
-                Ok(generic) => {
+            match self.read.recv_item().await {
+                Ok(Some(generic)) => {
                     tracing::trace!(?generic, "Received next_item");
                     let data = match tlv::Data::decode_from_generic(generic) {
                         Ok(data) => data,
@@ -48,24 +48,12 @@ impl ClientInternal {
                         tracing::warn!(name, "Drop unsolicited data packet");
                     }
                 }
+                Ok(None) => {
+                    tracing::debug!("Transport closed");
+                    break;
+                }
                 Err(err) => tracing::error!(%err, "transport.recv_item()"),
             }
         }
     }
-
-    async fn next_item(&self) -> io::Result<tlv::Generic> {
-        loop {
-            let Some(item) = self.read.lock().await.recv_item().await.transpose() else {
-                tracing::trace!("Empty recv_item()");
-                continue;
-            };
-            break item;
-        }
-    }
-
-    async fn _next_data_item(&self) -> io::Result<tlv::Data> {
-        let generic = self.next_item().await?;
-        tlv::Data::decode_from_generic(generic)
-            .map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))
-    }
 }
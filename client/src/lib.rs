@@ -6,12 +6,13 @@ use hashbrown::HashMap;
 use tokio::sync::oneshot;
 use tokio::sync::Mutex;
 use tokio::task;
-use tokio::time;
 
 use ndn_face as face;
 use ndn_router as router;
 use ndn_tlv as tlv;
 use ndn_transport as transport;
 
+use transport::TransportExt;
+
 pub mod concurrent;
 pub mod simple;
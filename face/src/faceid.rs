@@ -3,6 +3,9 @@ use super::*;
 tlv::non_negative_number!(FaceId => tlv::Type::FaceId; prefix => "faceid");
 
 impl FaceId {
+    /// Reserved FaceId used as IncomingFaceId of the Data served from the Content Store
+    pub const CONTENT_STORE: Self = Self(tlv::NonNegativeNumber(254));
+
     pub fn null() -> Self {
         Self::from(0)
     }
@@ -30,10 +30,12 @@ pub use status::FaceStatus;
 pub use uri::Addr;
 pub use uri::Internal;
 pub use uri::LocalUri;
+pub use uri::Quic;
 pub use uri::Tcp;
 pub use uri::Udp;
 pub use uri::Unix;
 pub use uri::Uri;
+pub use uri::Ws;
 pub use uri::URI_DELIMITER;
 
 mod congestion;
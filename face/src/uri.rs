@@ -2,9 +2,11 @@ use super::*;
 
 pub use addr::Addr;
 pub use addr::Internal;
+pub use addr::Quic;
 pub use addr::Tcp;
 pub use addr::Udp;
 pub use addr::Unix;
+pub use addr::Ws;
 
 mod addr;
 
@@ -1,21 +1,27 @@
 use super::*;
 
 pub use internal::Internal;
+pub use quic::Quic;
 pub use tcp::Tcp;
 pub use udp::Udp;
 pub use unix::Unix;
+pub use ws::Ws;
 
 mod internal;
+mod quic;
 mod tcp;
 mod udp;
 mod unix;
+mod ws;
 
 #[derive(Debug)]
 pub enum Addr {
     Internal(Internal),
+    Quic(Quic),
     Tcp(Tcp),
     Udp(Udp),
     Unix(Unix),
+    Ws(Ws),
 }
 
 impl Addr {
@@ -23,12 +29,16 @@ impl Addr {
         let (prefix, addr) = split_face_uri(uri)?;
         if prefix.starts_with(Internal::PREFIX) {
             Internal::from_uri(prefix, addr).await.map(Self::Internal)
+        } else if prefix.starts_with(Quic::PREFIX) {
+            Quic::from_uri(prefix, addr).await.map(Self::Quic)
         } else if prefix.starts_with(Tcp::PREFIX) {
             Tcp::from_uri(prefix, addr).await.map(Self::Tcp)
         } else if prefix.starts_with(Udp::PREFIX) {
             Udp::from_uri(prefix, addr).await.map(Self::Udp)
         } else if prefix.starts_with(Unix::PREFIX) {
             Unix::from_uri(prefix, addr).await.map(Self::Unix)
+        } else if prefix.starts_with(Ws::PREFIX) {
+            Ws::from_uri(prefix, addr).await.map(Self::Ws)
         } else {
             Err(io::Error::other(format!("unknown Uri prefix: {prefix}")))
         }
@@ -37,9 +47,11 @@ impl Addr {
     pub fn any(&self) -> Self {
         match self {
             Self::Internal(_) => Self::Internal(Internal::any()),
+            Self::Quic(_) => Self::Quic(Quic::any()),
             Self::Tcp(_) => Self::Tcp(Tcp::any()),
             Self::Udp(_) => Self::Udp(Udp::any()),
             Self::Unix(unix) => Self::Unix(unix.clone()),
+            Self::Ws(_) => Self::Ws(Ws::any()),
         }
     }
 }
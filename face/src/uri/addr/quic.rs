@@ -0,0 +1,60 @@
+use super::*;
+
+/// QUIC endpoint, `quic://host[:port]`. The host is also the name the certificate
+/// of the remote end is verified against.
+#[derive(Debug)]
+pub struct Quic {
+    pub addr: net::SocketAddr,
+    pub server_name: String,
+}
+
+impl Quic {
+    pub const PREFIX: &'static str = "quic";
+    /// NDN port, same as for TCP and UDP
+    pub const DEFAULT_PORT: u16 = 6363;
+
+    pub async fn from_uri(quic: &str, addr: &str) -> io::Result<Self> {
+        if quic != Self::PREFIX {
+            return Err(io::Error::other(format!("unknown quic scheme: {quic}")));
+        }
+        let (host, addr) = match addr.rsplit_once(':') {
+            Some((host, port)) if !port.ends_with(']') => {
+                (host, NeedIp::Any.lookup_addr(addr).await?)
+            }
+            _ => {
+                let authority = format!("{addr}:{}", Self::DEFAULT_PORT);
+                (addr, NeedIp::Any.lookup_addr(&authority).await?)
+            }
+        };
+        let server_name = host.trim_start_matches('[').trim_end_matches(']');
+        Ok(Self::new(addr, server_name))
+    }
+
+    pub(super) fn any() -> Self {
+        Self::new(net::SocketAddr::from((net::Ipv6Addr::UNSPECIFIED, 0)), "")
+    }
+
+    fn new(addr: net::SocketAddr, server_name: impl ToString) -> Self {
+        let server_name = server_name.to_string();
+        Self { addr, server_name }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn parse() {
+        let quic = Quic::from_uri("quic", "127.0.0.1:7000").await.unwrap();
+        assert_eq!(quic.addr, "127.0.0.1:7000".parse().unwrap());
+        assert_eq!(quic.server_name, "127.0.0.1");
+
+        let quic = Quic::from_uri("quic", "[::1]").await.unwrap();
+        assert_eq!(quic.addr.port(), Quic::DEFAULT_PORT);
+        assert_eq!(quic.server_name, "::1");
+
+        let quic = Quic::from_uri("quic", "localhost:7000").await.unwrap();
+        assert_eq!(quic.server_name, "localhost");
+    }
+}
@@ -0,0 +1,62 @@
+use super::*;
+
+/// WebSocket endpoint, `ws://host[:port][/path]`
+#[derive(Debug)]
+pub struct Ws {
+    pub addr: net::SocketAddr,
+    pub path: String,
+}
+
+impl Ws {
+    pub const PREFIX: &'static str = "ws";
+    /// Port of the WebSocket channel of NFD
+    pub const DEFAULT_PORT: u16 = 9696;
+
+    pub async fn from_uri(ws: &str, addr: &str) -> io::Result<Self> {
+        if ws != Self::PREFIX {
+            return Err(io::Error::other(format!("unknown ws scheme: {ws}")));
+        }
+        let (authority, path) = addr.find('/').map_or((addr, "/"), |at| addr.split_at(at));
+        let addr = if has_port(authority) {
+            NeedIp::Any.lookup_addr(authority).await?
+        } else {
+            let authority = format!("{authority}:{}", Self::DEFAULT_PORT);
+            NeedIp::Any.lookup_addr(&authority).await?
+        };
+        Ok(Self::new(addr, path))
+    }
+
+    pub(super) fn any() -> Self {
+        Self::new(net::SocketAddr::from((net::Ipv6Addr::UNSPECIFIED, 0)), "/")
+    }
+
+    fn new(addr: net::SocketAddr, path: impl ToString) -> Self {
+        let path = path.to_string();
+        Self { addr, path }
+    }
+}
+
+/// Bracketed IPv6 hosts have colons of their own
+fn has_port(authority: &str) -> bool {
+    authority
+        .rsplit_once(':')
+        .is_some_and(|(host, _)| !authority.starts_with('[') || host.ends_with(']'))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn parse() {
+        let ws = Ws::from_uri("ws", "127.0.0.1:9000/ndn").await.unwrap();
+        assert_eq!(ws.addr, "127.0.0.1:9000".parse().unwrap());
+        assert_eq!(ws.path, "/ndn");
+
+        let ws = Ws::from_uri("ws", "[::1]").await.unwrap();
+        assert_eq!(ws.addr.port(), Ws::DEFAULT_PORT);
+        assert_eq!(ws.path, "/");
+
+        assert!(Ws::from_uri("wss", "127.0.0.1:9000").await.is_err());
+    }
+}
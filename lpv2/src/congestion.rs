@@ -0,0 +1,164 @@
+//! Congestion detection and marking
+//!
+//! Follows the CoDel-like algorithm of NFD's GenericLinkService: once the send queue stays above
+//! the threshold for one base marking interval, the outgoing packet is marked, and the following
+//! marks are spaced by the base interval divided by the square root of the number of marks
+//! in the current congestion incident. Marking state resets as soon as the queue drops below
+//! the threshold.
+
+use super::*;
+
+// CongestionMark indicates the congestion level of the link (0 means no congestion)
+tlv::non_negative_number!(CongestionMark => tlv::Type::CongestionMark);
+
+impl CongestionMark {
+    pub fn is_congested(self) -> bool {
+        self.to_u64() > 0
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct CongestionMarkingOptions {
+    /// time the send queue has to stay above the threshold before the first mark
+    pub base_marking_interval: Duration,
+    /// send queue length (in bytes) considered congested
+    pub threshold: usize,
+}
+
+impl Default for CongestionMarkingOptions {
+    fn default() -> Self {
+        Self {
+            base_marking_interval: Duration::from_millis(100),
+            threshold: 65536,
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct CongestionMarking {
+    options: CongestionMarkingOptions,
+    next_mark_time: Option<Instant>,
+    n_marked_since_in_marking_state: u32,
+    n_congestion_marked: u64,
+}
+
+impl CongestionMarking {
+    pub fn new(options: CongestionMarkingOptions) -> Self {
+        Self {
+            options,
+            next_mark_time: None,
+            n_marked_since_in_marking_state: 0,
+            n_congestion_marked: 0,
+        }
+    }
+
+    pub fn options(&self) -> &CongestionMarkingOptions {
+        &self.options
+    }
+
+    /// Number of packets marked since the link was established
+    pub fn n_congestion_marked(&self) -> u64 {
+        self.n_congestion_marked
+    }
+
+    /// Check the current send queue length and decide whether the outgoing packet is to be marked
+    pub fn check(&mut self, send_queue_length: usize, now: Instant) -> Option<CongestionMark> {
+        if send_queue_length <= self.options.threshold {
+            if self.next_mark_time.take().is_some() {
+                tracing::debug!(
+                    send_queue_length,
+                    "Send queue dropped below congestion threshold"
+                );
+                self.n_marked_since_in_marking_state = 0;
+            }
+            return None;
+        }
+
+        let next_mark_time = self
+            .next_mark_time
+            .get_or_insert(now + self.options.base_marking_interval);
+
+        if now < *next_mark_time {
+            return None;
+        }
+
+        self.n_congestion_marked += 1;
+        self.n_marked_since_in_marking_state += 1;
+        let divisor = f64::from(self.n_marked_since_in_marking_state + 1).sqrt();
+        *next_mark_time += self.options.base_marking_interval.div_f64(divisor);
+        tracing::debug!(send_queue_length, "Marking packet as congested");
+
+        Some(CongestionMark::from(1))
+    }
+}
+
+impl Default for CongestionMarking {
+    fn default() -> Self {
+        Self::new(CongestionMarkingOptions::default())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const INTERVAL: Duration = Duration::from_millis(100);
+    const THRESHOLD: usize = 1000;
+
+    fn marking() -> CongestionMarking {
+        CongestionMarking::new(CongestionMarkingOptions {
+            base_marking_interval: INTERVAL,
+            threshold: THRESHOLD,
+        })
+    }
+
+    #[test]
+    fn below_threshold() {
+        let now = Instant::now();
+        let mut marking = marking();
+        for n in 0..10 {
+            assert!(marking.check(THRESHOLD, now + INTERVAL * n).is_none());
+        }
+        assert_eq!(marking.n_congestion_marked(), 0);
+    }
+
+    #[test]
+    fn marking_interval_shrinks() {
+        let start = Instant::now();
+        let mut marking = marking();
+
+        // Queue has to stay above the threshold for one interval
+        assert!(marking.check(THRESHOLD + 1, start).is_none());
+        assert!(marking.check(THRESHOLD + 1, start + INTERVAL / 2).is_none());
+        let first = start + INTERVAL;
+        assert!(marking.check(THRESHOLD + 1, first).unwrap().is_congested());
+
+        // Next mark after INTERVAL / sqrt(2)
+        let second = first + INTERVAL.div_f64(2_f64.sqrt());
+        assert!(marking.check(THRESHOLD + 1, first + INTERVAL / 2).is_none());
+        assert!(marking.check(THRESHOLD + 1, second).is_some());
+
+        // And then after INTERVAL / sqrt(3)
+        let third = second + INTERVAL.div_f64(3_f64.sqrt());
+        assert!(marking
+            .check(THRESHOLD + 1, third - Duration::from_millis(1))
+            .is_none());
+        assert!(marking.check(THRESHOLD + 1, third).is_some());
+        assert_eq!(marking.n_congestion_marked(), 3);
+    }
+
+    #[test]
+    fn reset_after_congestion() {
+        let start = Instant::now();
+        let mut marking = marking();
+
+        assert!(marking.check(THRESHOLD + 1, start).is_none());
+        assert!(marking.check(THRESHOLD + 1, start + INTERVAL).is_some());
+        assert!(marking.check(0, start + INTERVAL * 2).is_none());
+
+        // New congestion incident starts over
+        let restart = start + INTERVAL * 3;
+        assert!(marking.check(THRESHOLD + 1, restart).is_none());
+        assert!(marking.check(THRESHOLD + 1, restart + INTERVAL).is_some());
+    }
+}
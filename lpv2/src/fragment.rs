@@ -1,7 +1,27 @@
 use super::*;
 
+// FragIndex is the zero-based index of the fragment within the network layer packet,
+// FragCount the number of fragments the packet has been split into.
+tlv::non_negative_number!(FragIndex => tlv::Type::FragIndex);
+tlv::non_negative_number!(FragCount => tlv::Type::FragCount);
+
 #[derive(Clone, Debug, PartialEq, tlv::Tlv)]
 #[tlv(r#type = tlv::Type::Fragment, error = tlv::DecodeError, crates(tlv_core = tlv::core))]
 pub struct Fragment {
     fragment: Bytes,
 }
+
+impl Fragment {
+    pub fn new(fragment: impl Into<Bytes>) -> Self {
+        let fragment = fragment.into();
+        Self { fragment }
+    }
+
+    pub fn as_bytes(&self) -> &Bytes {
+        &self.fragment
+    }
+
+    pub fn into_bytes(self) -> Bytes {
+        self.fragment
+    }
+}
@@ -0,0 +1,355 @@
+//! NDNLPv2 fragmentation and reassembly.
+//!
+//! A network layer packet too large for the link MTU is split into LpPackets sharing
+//! consecutive Sequence numbers, each carrying its FragIndex and the FragCount. The header
+//! fields travel on the first fragment only.
+
+use std::collections::{BTreeMap, HashMap};
+
+use super::*;
+
+// Sequence, FragIndex and FragCount (TLV-TYPE, TLV-LENGTH and up to 8 octets each),
+// Fragment TLV-TYPE and TLV-LENGTH, and LpPacket TLV-TYPE and TLV-LENGTH
+const FRAGMENT_OVERHEAD: usize = 3 * 10 + 4 + 4;
+
+/// Splits the outgoing packets which do not fit into the MTU
+#[derive(Debug)]
+pub struct Fragmenter {
+    next_sequence: u64,
+}
+
+impl Fragmenter {
+    pub fn new() -> Self {
+        // Same as for TxSequence, fragments of a previous incarnation of this link
+        // are unlikely to be reassembled with the new ones
+        let next_sequence = rand::random::<u32>().into();
+        Self { next_sequence }
+    }
+
+    /// `packet` as it is if it fits into `mtu` octets, otherwise its fragments
+    pub fn fragment(&mut self, packet: LpPacket, mtu: usize) -> io::Result<Vec<LpPacket>> {
+        use tlv::TlvCodec;
+
+        if packet.total_size() <= mtu {
+            return Ok(vec![packet]);
+        }
+        let Some(payload) = packet.fragment().cloned() else {
+            return Err(too_small(mtu));
+        };
+
+        let header = LpPacket {
+            fragment: None,
+            ..packet
+        };
+        let first = mtu
+            .checked_sub(header.total_size() + FRAGMENT_OVERHEAD)
+            .filter(|size| *size > 0)
+            .ok_or_else(|| too_small(mtu))?;
+        let rest = mtu
+            .checked_sub(FRAGMENT_OVERHEAD)
+            .filter(|size| *size > 0)
+            .ok_or_else(|| too_small(mtu))?;
+
+        let mut chunks = vec![payload.slice(..first.min(payload.len()))];
+        let mut offset = first;
+        while offset < payload.len() {
+            let end = (offset + rest).min(payload.len());
+            chunks.push(payload.slice(offset..end));
+            offset = end;
+        }
+
+        let frag_count = chunks.len() as u64;
+        let base = self.next_sequence;
+        self.next_sequence = self.next_sequence.wrapping_add(frag_count);
+
+        let fragments = chunks
+            .into_iter()
+            .zip(0..)
+            .map(|(chunk, index)| {
+                let fields = if index == 0 {
+                    header.clone()
+                } else {
+                    LpPacket::new()
+                };
+                LpPacket {
+                    sequence: Some(base.wrapping_add(index).into()),
+                    frag_index: Some(index.into()),
+                    frag_count: Some(frag_count.into()),
+                    fragment: Some(Fragment::new(chunk)),
+                    ..fields
+                }
+            })
+            .collect();
+        Ok(fragments)
+    }
+}
+
+impl Default for Fragmenter {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Puts the incoming fragments back together
+#[derive(Debug, Default)]
+pub struct Reassembler {
+    /// Incomplete packets by the Sequence of their first fragment
+    partial: HashMap<u64, Partial>,
+    /// First fragment Sequence of the incomplete packets, oldest first
+    order: BTreeMap<u64, u64>,
+    next_seq: u64,
+    n_timed_out: u64,
+}
+
+#[derive(Debug)]
+struct Partial {
+    fragments: Vec<Option<LpPacket>>,
+    deadline: Instant,
+    seq: u64,
+}
+
+impl Reassembler {
+    /// Fragments of a packet not complete by then are dropped
+    pub const REASSEMBLY_TIMEOUT: Duration = Duration::from_millis(500);
+    /// Packets split into more fragments are rejected
+    pub const MAX_FRAGMENTS: u64 = 400;
+    /// Incomplete packets held at once, the oldest one is dropped to make room
+    pub const MAX_PARTIAL_PACKETS: usize = 64;
+
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Packets dropped so far for missing fragments, timed out or pushed out by newer ones
+    pub fn n_timed_out(&self) -> u64 {
+        self.n_timed_out
+    }
+
+    /// Unfragmented packets are passed through, fragments are held until the packet is
+    /// complete. The reassembled packet carries the header fields of its first fragment.
+    pub fn receive(&mut self, packet: LpPacket, now: Instant) -> io::Result<Option<LpPacket>> {
+        self.expire(now);
+
+        let frag_count = packet.frag_count.map_or(1, |count| count.to_u64());
+        let frag_index = packet.frag_index.map_or(0, |index| index.to_u64());
+        if frag_index >= frag_count || frag_count > Self::MAX_FRAGMENTS {
+            return Err(invalid_fragment(format!(
+                "Fragment {frag_index} of {frag_count}"
+            )));
+        }
+        if frag_count == 1 {
+            return Ok(Some(packet));
+        }
+
+        let sequence = packet
+            .sequence
+            .as_ref()
+            .ok_or_else(|| invalid_fragment("Fragment without Sequence".to_string()))?;
+        let base = sequence.to_u64().wrapping_sub(frag_index);
+        let count = frag_count as usize;
+        if !self.partial.contains_key(&base) && self.partial.len() >= Self::MAX_PARTIAL_PACKETS {
+            self.evict_oldest();
+        }
+        let partial = self.partial.entry(base).or_insert_with(|| {
+            let seq = self.next_seq;
+            self.next_seq += 1;
+            self.order.insert(seq, base);
+            Partial {
+                fragments: vec![None; count],
+                deadline: now + Self::REASSEMBLY_TIMEOUT,
+                seq,
+            }
+        });
+        if partial.fragments.len() != count {
+            self.remove(base);
+            return Err(invalid_fragment(format!(
+                "FragCount {frag_count} differs from the earlier fragments"
+            )));
+        }
+        partial.fragments[frag_index as usize] = Some(packet);
+
+        if partial.fragments.iter().any(Option::is_none) {
+            return Ok(None);
+        }
+        let fragments = self
+            .remove(base)
+            .map(|partial| partial.fragments.into_iter().flatten().collect())
+            .unwrap_or_default();
+        Ok(Self::reassemble(fragments))
+    }
+
+    fn reassemble(fragments: Vec<LpPacket>) -> Option<LpPacket> {
+        let mut payload = BytesMut::new();
+        for fragment in &fragments {
+            payload.extend_from_slice(fragment.fragment()?);
+        }
+        let first = fragments.into_iter().next()?;
+        Some(LpPacket {
+            sequence: None,
+            frag_index: None,
+            frag_count: None,
+            fragment: Some(Fragment::new(payload.freeze())),
+            ..first
+        })
+    }
+
+    fn remove(&mut self, base: u64) -> Option<Partial> {
+        let partial = self.partial.remove(&base)?;
+        self.order.remove(&partial.seq);
+        Some(partial)
+    }
+
+    fn evict_oldest(&mut self) {
+        if let Some((_, base)) = self.order.pop_first() {
+            self.partial.remove(&base);
+            tracing::debug!(base, "Dropping oldest incomplete fragmented packet");
+            self.n_timed_out += 1;
+        }
+    }
+
+    /// Deadlines follow the arrival order, so the expired packets are the oldest ones
+    fn expire(&mut self, now: Instant) {
+        let mut expired = 0;
+        while let Some(entry) = self.order.first_entry() {
+            let base = *entry.get();
+            if self
+                .partial
+                .get(&base)
+                .is_some_and(|partial| partial.deadline > now)
+            {
+                break;
+            }
+            entry.remove();
+            self.partial.remove(&base);
+            expired += 1;
+        }
+        if expired > 0 {
+            tracing::debug!(expired, "Dropping incomplete fragmented packets");
+            self.n_timed_out += expired;
+        }
+    }
+}
+
+fn too_small(mtu: usize) -> io::Error {
+    io::Error::new(
+        io::ErrorKind::InvalidInput,
+        format!("MTU {mtu} is too small to carry the packet"),
+    )
+}
+
+fn invalid_fragment(reason: String) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, reason)
+}
+
+#[cfg(test)]
+mod tests {
+    use tlv::TlvCodec;
+
+    use super::*;
+
+    fn data(size: usize) -> tlv::Data {
+        let mut data = tlv::Data::new("/large");
+        data.content = Some(tlv::Content::new(vec![7; size]));
+        data
+    }
+
+    #[test]
+    fn fragment_and_reassemble() {
+        let now = Instant::now();
+        let packet = LpPacket::from_tlv(data(4000)).with_pit_token(PitToken::from(7));
+        let mut fragmenter = Fragmenter::new();
+        let fragments = fragmenter.fragment(packet.clone(), 1400).unwrap();
+        assert_eq!(fragments.len(), 3);
+        assert!(fragments
+            .iter()
+            .all(|fragment| fragment.total_size() <= 1400));
+        assert!(fragments[0].pit_token.is_some());
+        assert!(fragments[1].pit_token.is_none());
+
+        // Out of order arrival
+        let mut reassembler = Reassembler::new();
+        let mut fragments = fragments.into_iter().rev();
+        for fragment in fragments.by_ref().take(2) {
+            assert!(reassembler.receive(fragment, now).unwrap().is_none());
+        }
+        let reassembled = reassembler
+            .receive(fragments.next().unwrap(), now)
+            .unwrap()
+            .unwrap();
+        assert_eq!(reassembled, packet);
+
+        // Small packets pass through untouched
+        let small = LpPacket::from_tlv(data(10));
+        let fragments = fragmenter.fragment(small.clone(), 1400).unwrap();
+        assert_eq!(fragments, vec![small.clone()]);
+        assert_eq!(
+            reassembler.receive(small.clone(), now).unwrap(),
+            Some(small)
+        );
+    }
+
+    #[test]
+    fn incomplete_packets_time_out() {
+        let now = Instant::now();
+        let mut fragments = Fragmenter::new()
+            .fragment(LpPacket::from_tlv(data(3000)), 1400)
+            .unwrap();
+        let last = fragments.pop().unwrap();
+
+        let mut reassembler = Reassembler::new();
+        for fragment in fragments {
+            assert!(reassembler.receive(fragment, now).unwrap().is_none());
+        }
+        let later = now + Reassembler::REASSEMBLY_TIMEOUT;
+        assert!(reassembler.receive(last, later).unwrap().is_none());
+        assert_eq!(reassembler.n_timed_out(), 1);
+    }
+
+    #[test]
+    fn flood_of_incomplete_packets() {
+        let now = Instant::now();
+        let mut fragmenter = Fragmenter::new();
+        let mut reassembler = Reassembler::new();
+        let mut fragments = fragmenter
+            .fragment(LpPacket::from_tlv(data(3000)), 1400)
+            .unwrap();
+        let last = fragments.pop().unwrap();
+        for fragment in fragments {
+            assert!(reassembler.receive(fragment, now).unwrap().is_none());
+        }
+
+        // First fragments only, never completed
+        let n_flood = Reassembler::MAX_PARTIAL_PACKETS as u64 + 10;
+        for _ in 0..n_flood {
+            let first = fragmenter
+                .fragment(LpPacket::from_tlv(data(3000)), 1400)
+                .unwrap()
+                .swap_remove(0);
+            assert!(reassembler.receive(first, now).unwrap().is_none());
+        }
+        assert_eq!(reassembler.partial.len(), Reassembler::MAX_PARTIAL_PACKETS);
+        assert_eq!(reassembler.order.len(), Reassembler::MAX_PARTIAL_PACKETS);
+        assert_eq!(reassembler.n_timed_out(), 11);
+
+        // The oldest packet was pushed out
+        assert!(reassembler.receive(last, now).unwrap().is_none());
+    }
+
+    #[test]
+    fn invalid_fragments() {
+        let now = Instant::now();
+        let mut reassembler = Reassembler::new();
+        let fragment = LpPacket {
+            sequence: Some(1.into()),
+            frag_index: Some(2.into()),
+            frag_count: Some(2.into()),
+            ..LpPacket::from_tlv(data(10))
+        };
+        assert!(reassembler.receive(fragment, now).is_err());
+
+        let error = Fragmenter::new()
+            .fragment(LpPacket::from_tlv(data(100)), 40)
+            .unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::InvalidInput);
+    }
+}
@@ -1,17 +1,47 @@
+use std::collections::BTreeMap;
+use std::collections::VecDeque;
+use std::fmt;
 use std::io;
+use std::time::{Duration, Instant};
 
 use bytes::{Buf, BufMut, Bytes, BytesMut};
 
 use ndn_tlv as tlv;
 
+use tlv::Tlv;
+
+pub use congestion::CongestionMark;
+pub use congestion::CongestionMarking;
+pub use congestion::CongestionMarkingOptions;
+pub use fragment::FragCount;
+pub use fragment::FragIndex;
 pub use fragment::Fragment;
+pub use fragmentation::Fragmenter;
+pub use fragmentation::Reassembler;
+pub use local::CachePolicy;
+pub use local::CachePolicyType;
+pub use local::IncomingFaceId;
+pub use local::NextHopFaceId;
+pub use nack::Nack;
+pub use nack::NackReason;
+pub use packet::LpPacket;
+pub use reliability::Event;
+pub use reliability::Reliability;
+pub use reliability::ReliabilityOptions;
+pub use rtt::RttEstimator;
+pub use rtt::RttOptions;
+pub use sequence::Ack;
 pub use sequence::Sequence;
+pub use sequence::TxSequence;
+pub use token::PitToken;
 
+mod congestion;
 mod fragment;
+mod fragmentation;
+mod local;
+mod nack;
+mod packet;
+mod reliability;
+mod rtt;
 mod sequence;
-
-#[derive(Clone, Debug, PartialEq)]
-pub struct LpPacket {
-    lp_header_field: Vec<Sequence>,
-    fragment: Option<Fragment>,
-}
+mod token;
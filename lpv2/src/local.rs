@@ -0,0 +1,53 @@
+//! Local fields exchanged between the forwarder and local applications,
+//! see https://redmine.named-data.net/projects/nfd/wiki/NDNLPv2#Local-Fields
+//!
+//! These are only meaningful on local faces with LocalFieldsEnabled flag set,
+//! anywhere else they are to be dropped.
+
+use super::*;
+
+// IncomingFaceId contains the FaceId of the face on which the network layer packet has been received
+tlv::non_negative_number!(IncomingFaceId => tlv::Type::IncomingFaceId);
+
+// NextHopFaceId asks the forwarder to send the Interest out the given face, bypassing the FIB
+tlv::non_negative_number!(NextHopFaceId => tlv::Type::NextHopFaceId);
+
+// CachePolicyType tells the forwarder how the Data is to be cached
+tlv::non_negative_number!(CachePolicyType => tlv::Type::CachePolicyType; display_as_str);
+
+#[allow(non_upper_case_globals)]
+impl CachePolicyType {
+    pub const NoCache: Self = Self(tlv::NonNegativeNumber(1));
+
+    pub fn as_str(&self) -> &'static str {
+        match *self {
+            Self::NoCache => "no-cache",
+            _ => "unknown",
+        }
+    }
+}
+
+// CachePolicy ::= CACHE-POLICY-TYPE TLV-LENGTH
+//                   CachePolicyType
+#[derive(Clone, Copy, Debug, PartialEq, Eq, tlv::Tlv)]
+#[tlv(r#type = tlv::Type::CachePolicy, error = tlv::DecodeError, crates(tlv_core = tlv::core))]
+pub struct CachePolicy {
+    pub cache_policy_type: CachePolicyType,
+}
+
+impl CachePolicy {
+    pub fn no_cache() -> Self {
+        let cache_policy_type = CachePolicyType::NoCache;
+        Self { cache_policy_type }
+    }
+
+    pub fn is_no_cache(&self) -> bool {
+        self.cache_policy_type == CachePolicyType::NoCache
+    }
+}
+
+impl fmt::Display for CachePolicy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.cache_policy_type.fmt(f)
+    }
+}
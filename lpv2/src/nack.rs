@@ -0,0 +1,61 @@
+use super::*;
+
+// NackReason tells why the Interest has been rejected
+tlv::non_negative_number!(NackReason => tlv::Type::NackReason; display_as_str);
+
+#[allow(non_upper_case_globals)]
+impl NackReason {
+    pub const None: Self = Self(tlv::NonNegativeNumber(0));
+    pub const Congestion: Self = Self(tlv::NonNegativeNumber(50));
+    pub const Duplicate: Self = Self(tlv::NonNegativeNumber(100));
+    pub const NoRoute: Self = Self(tlv::NonNegativeNumber(150));
+
+    pub fn as_str(&self) -> &'static str {
+        match *self {
+            Self::None => "none",
+            Self::Congestion => "congestion",
+            Self::Duplicate => "duplicate",
+            Self::NoRoute => "no-route",
+            _ => "unknown",
+        }
+    }
+
+    /// Whether this reason is less severe than `other`, a missing reason being the most severe
+    pub fn is_less_severe(&self, other: &Self) -> bool {
+        if *self == Self::None {
+            false
+        } else if *other == Self::None {
+            true
+        } else {
+            self < other
+        }
+    }
+}
+
+// Nack ::= NACK-TYPE TLV-LENGTH
+//            [NackReason]
+//
+// LpPacket carrying Nack header field has the rejected Interest as its fragment.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, tlv::Tlv)]
+#[tlv(r#type = tlv::Type::Nack, error = tlv::DecodeError, crates(tlv_core = tlv::core))]
+pub struct Nack {
+    pub reason: Option<NackReason>,
+}
+
+impl Nack {
+    pub fn new(reason: NackReason) -> Self {
+        let reason = Some(reason);
+        Self { reason }
+    }
+
+    /// Missing NackReason is to be treated as None
+    pub fn reason(&self) -> NackReason {
+        self.reason.unwrap_or(NackReason::None)
+    }
+}
+
+impl fmt::Display for Nack {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "nack={:#}", self.reason())
+    }
+}
@@ -0,0 +1,446 @@
+use super::*;
+
+// LpPacket ::= LP-PACKET-TYPE TLV-LENGTH
+//                *LpHeaderField
+//                [Fragment]
+//
+// Header fields are encoded in the order of increasing TLV-TYPE, the Fragment always goes last.
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct LpPacket {
+    pub sequence: Option<Sequence>,
+    pub frag_index: Option<FragIndex>,
+    pub frag_count: Option<FragCount>,
+    pub pit_token: Option<PitToken>,
+    pub nack: Option<Nack>,
+    pub incoming_face_id: Option<IncomingFaceId>,
+    pub next_hop_face_id: Option<NextHopFaceId>,
+    pub cache_policy: Option<CachePolicy>,
+    pub congestion_mark: Option<CongestionMark>,
+    pub acks: Vec<Ack>,
+    pub tx_sequence: Option<TxSequence>,
+    pub prefix_announcement: Option<Box<tlv::PrefixAnnouncement>>,
+    pub fragment: Option<Fragment>,
+}
+
+impl LpPacket {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Wrap a network layer packet (Interest, Data, etc.) into a bare LpPacket
+    pub fn from_tlv(item: impl Tlv) -> Self {
+        use tlv::TlvCodec;
+        let fragment = Some(Fragment::new(item.bytes()));
+        Self {
+            fragment,
+            ..Self::default()
+        }
+    }
+
+    pub fn with_tx_sequence(self, tx_sequence: impl Into<TxSequence>) -> Self {
+        let tx_sequence = Some(tx_sequence.into());
+        Self {
+            tx_sequence,
+            ..self
+        }
+    }
+
+    pub fn with_congestion_mark(self, congestion_mark: impl Into<Option<CongestionMark>>) -> Self {
+        let congestion_mark = congestion_mark.into();
+        Self {
+            congestion_mark,
+            ..self
+        }
+    }
+
+    pub fn with_pit_token(self, pit_token: impl Into<Option<PitToken>>) -> Self {
+        let pit_token = pit_token.into();
+        Self { pit_token, ..self }
+    }
+
+    pub fn with_nack(self, nack: impl Into<Option<Nack>>) -> Self {
+        let nack = nack.into();
+        Self { nack, ..self }
+    }
+
+    pub fn with_incoming_face_id(self, face_id: impl Into<IncomingFaceId>) -> Self {
+        let incoming_face_id = Some(face_id.into());
+        Self {
+            incoming_face_id,
+            ..self
+        }
+    }
+
+    pub fn with_next_hop_face_id(self, face_id: impl Into<NextHopFaceId>) -> Self {
+        let next_hop_face_id = Some(face_id.into());
+        Self {
+            next_hop_face_id,
+            ..self
+        }
+    }
+
+    pub fn with_cache_policy(self, cache_policy: CachePolicy) -> Self {
+        let cache_policy = Some(cache_policy);
+        Self {
+            cache_policy,
+            ..self
+        }
+    }
+
+    pub fn with_prefix_announcement(
+        self,
+        prefix_announcement: impl Into<Option<tlv::PrefixAnnouncement>>,
+    ) -> Self {
+        let prefix_announcement = prefix_announcement.into().map(Box::new);
+        Self {
+            prefix_announcement,
+            ..self
+        }
+    }
+
+    pub fn with_acks(self, acks: impl IntoIterator<Item = Ack>) -> Self {
+        let acks = acks.into_iter().collect();
+        Self { acks, ..self }
+    }
+
+    pub fn decode_from_generic(generic: tlv::Generic) -> Result<Self, tlv::DecodeError> {
+        let tlv::Generic {
+            r#type,
+            length,
+            mut value,
+        } = generic.check_type(tlv::Type::LpPacket)?;
+        let length = length.to_usize();
+        Self::decode_value(r#type, length, &mut value)
+    }
+
+    /// LpPacket without header fields is sent as its bare network layer packet
+    pub fn has_header_fields(&self) -> bool {
+        self.sequence.is_some()
+            || self.is_fragmented()
+            || self.pit_token.is_some()
+            || self.nack.is_some()
+            || self.has_local_fields()
+            || self.congestion_mark.is_some()
+            || !self.acks.is_empty()
+            || self.tx_sequence.is_some()
+            || self.prefix_announcement.is_some()
+    }
+
+    /// FragIndex or FragCount is present, the fragment is only part of a network layer packet
+    pub fn is_fragmented(&self) -> bool {
+        self.frag_index.is_some() || self.frag_count.is_some()
+    }
+
+    /// IncomingFaceId, NextHopFaceId or CachePolicy is present
+    pub fn has_local_fields(&self) -> bool {
+        self.incoming_face_id.is_some()
+            || self.next_hop_face_id.is_some()
+            || self.cache_policy.is_some()
+    }
+
+    /// The same LpPacket with the local fields removed, as required on non-local faces
+    pub fn without_local_fields(self) -> Self {
+        Self {
+            incoming_face_id: None,
+            next_hop_face_id: None,
+            cache_policy: None,
+            ..self
+        }
+    }
+
+    /// LpPacket without fragment is an IDLE packet, used to carry header fields only (e.g. Acks)
+    pub fn is_idle(&self) -> bool {
+        self.fragment.is_none()
+    }
+
+    pub fn fragment(&self) -> Option<&Bytes> {
+        self.fragment.as_ref().map(Fragment::as_bytes)
+    }
+
+    /// Decode the network layer packet carried in the fragment
+    pub fn network_packet(&self) -> Option<tlv::Generic> {
+        let mut fragment = self.fragment()?.clone();
+        tlv::Generic::from_bytes(&mut fragment)
+    }
+
+    /// The same LpPacket stripped from all the header fields
+    pub fn without_header_fields(&self) -> Self {
+        let fragment = self.fragment.clone();
+        Self {
+            fragment,
+            ..Self::default()
+        }
+    }
+
+    fn header_field_length(&self) -> usize {
+        use tlv::TlvCodec;
+        [
+            self.sequence.total_size(),
+            self.frag_index.total_size(),
+            self.frag_count.total_size(),
+            self.pit_token.total_size(),
+            self.nack.total_size(),
+            self.incoming_face_id.total_size(),
+            self.next_hop_face_id.total_size(),
+            self.cache_policy.total_size(),
+            self.congestion_mark.total_size(),
+            self.acks.total_size(),
+            self.tx_sequence.total_size(),
+            self.prefix_announcement
+                .as_deref()
+                .map_or(0, tlv::TlvCodec::total_size),
+        ]
+        .into_iter()
+        .sum()
+    }
+
+    // Unknown header fields may be ignored if their TLV-TYPE is in [800, 959] range
+    // and the two least significant bits are 00.
+    fn is_ignorable(r#type: tlv::Type) -> bool {
+        let r#type = r#type.to_u64();
+        (800..=959).contains(&r#type) && r#type & 0b11 == 0
+    }
+}
+
+impl Tlv for LpPacket {
+    type Error = tlv::DecodeError;
+    const TYPE: tlv::Type = tlv::Type::LpPacket;
+
+    fn length(&self) -> usize {
+        use tlv::TlvCodec;
+        self.header_field_length() + self.fragment.total_size()
+    }
+
+    fn encode_value(&self, dst: &mut BytesMut) {
+        use tlv::TlvCodec;
+        self.sequence.encode(dst);
+        self.frag_index.encode(dst);
+        self.frag_count.encode(dst);
+        self.pit_token.encode(dst);
+        self.nack.encode(dst);
+        self.incoming_face_id.encode(dst);
+        self.next_hop_face_id.encode(dst);
+        self.cache_policy.encode(dst);
+        self.congestion_mark.encode(dst);
+        self.acks.encode(dst);
+        self.tx_sequence.encode(dst);
+        if let Some(prefix_announcement) = &self.prefix_announcement {
+            prefix_announcement.encode(dst);
+        }
+        self.fragment.encode(dst);
+    }
+
+    fn decode_value(
+        r#type: tlv::Type,
+        length: usize,
+        src: &mut BytesMut,
+    ) -> Result<Self, Self::Error> {
+        let _ = (r#type, length);
+        let mut packet = Self::default();
+
+        while !src.is_empty() {
+            let tlv::Generic {
+                r#type,
+                length,
+                mut value,
+            } = tlv::Generic::from_bytes_mut(src)
+                .ok_or_else(|| tlv::DecodeError::invalid("Truncated LpPacket header field"))?;
+            let length = length.to_usize();
+            let value = &mut value;
+
+            if packet.fragment.is_some() {
+                Err(tlv::DecodeError::invalid("Fragment must be the last field"))?;
+            }
+
+            match r#type {
+                tlv::Type::Sequence => {
+                    packet.sequence = Some(Tlv::decode_value(r#type, length, value)?);
+                }
+                tlv::Type::FragIndex => {
+                    packet.frag_index = Some(Tlv::decode_value(r#type, length, value)?);
+                }
+                tlv::Type::FragCount => {
+                    packet.frag_count = Some(Tlv::decode_value(r#type, length, value)?);
+                }
+                tlv::Type::PitToken => {
+                    packet.pit_token = Some(Tlv::decode_value(r#type, length, value)?);
+                }
+                tlv::Type::Nack => {
+                    packet.nack = Some(Tlv::decode_value(r#type, length, value)?);
+                }
+                tlv::Type::IncomingFaceId => {
+                    packet.incoming_face_id = Some(Tlv::decode_value(r#type, length, value)?);
+                }
+                tlv::Type::NextHopFaceId => {
+                    packet.next_hop_face_id = Some(Tlv::decode_value(r#type, length, value)?);
+                }
+                tlv::Type::CachePolicy => {
+                    packet.cache_policy = Some(Tlv::decode_value(r#type, length, value)?);
+                }
+                tlv::Type::CongestionMark => {
+                    packet.congestion_mark = Some(Tlv::decode_value(r#type, length, value)?);
+                }
+                tlv::Type::Ack => packet.acks.push(Tlv::decode_value(r#type, length, value)?),
+                tlv::Type::TxSequence => {
+                    packet.tx_sequence = Some(Tlv::decode_value(r#type, length, value)?);
+                }
+                tlv::Type::PrefixAnnouncement => {
+                    let prefix_announcement = Tlv::decode_value(r#type, length, value)?;
+                    packet.prefix_announcement = Some(Box::new(prefix_announcement));
+                }
+                tlv::Type::Fragment => {
+                    packet.fragment = Some(Tlv::decode_value(r#type, length, value)?);
+                }
+                other if Self::is_ignorable(other) => {
+                    tracing::trace!(r#type = %other, "Ignoring unknown LpPacket header field");
+                }
+                other => Err(tlv::DecodeError::invalid(format!(
+                    "Unrecognized non-ignorable LpPacket header field {other}"
+                )))?,
+            }
+        }
+
+        Ok(packet)
+    }
+}
+
+impl fmt::Display for LpPacket {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "LpPacket[")?;
+        if let (Some(frag_index), Some(frag_count)) = (self.frag_index, self.frag_count) {
+            write!(f, " {frag_index}/{frag_count}")?;
+        }
+        if let Some(pit_token) = &self.pit_token {
+            write!(f, " {pit_token}")?;
+        }
+        if let Some(nack) = self.nack {
+            write!(f, " {nack}")?;
+        }
+        if let Some(incoming_face_id) = self.incoming_face_id {
+            write!(f, " {incoming_face_id}")?;
+        }
+        if let Some(next_hop_face_id) = self.next_hop_face_id {
+            write!(f, " {next_hop_face_id}")?;
+        }
+        if let Some(cache_policy) = self.cache_policy {
+            write!(f, " {cache_policy}")?;
+        }
+        if let Some(congestion_mark) = self.congestion_mark {
+            write!(f, " {congestion_mark}")?;
+        }
+        if let Some(tx_sequence) = self.tx_sequence {
+            write!(f, " {tx_sequence}")?;
+        }
+        for ack in &self.acks {
+            write!(f, " {ack}")?;
+        }
+        if let Some(prefix_announcement) = &self.prefix_announcement {
+            write!(f, " {prefix_announcement}")?;
+        }
+        if let Some(fragment) = self.fragment() {
+            write!(f, " fragment={}", fragment.len())?;
+        }
+        write!(f, " ]")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use tlv::TlvCodec;
+
+    use super::*;
+
+    #[test]
+    fn bare_roundtrip() {
+        let interest = tlv::Interest::new("/localhost/nfd/status/general");
+        let packet = LpPacket::from_tlv(interest.clone());
+        assert!(!packet.has_header_fields());
+
+        let mut bytes = BytesMut::from(packet.bytes().as_ref());
+        let decoded = LpPacket::decode(&mut bytes).unwrap();
+        assert!(bytes.is_empty());
+        assert_eq!(decoded, packet);
+
+        let generic = decoded.network_packet().unwrap();
+        assert_eq!(generic.r#type, tlv::Type::Interest);
+    }
+
+    #[test]
+    fn header_fields_roundtrip() {
+        let packet = LpPacket::from_tlv(tlv::Interest::new("/a/b"))
+            .with_tx_sequence(0x1_0000_0000)
+            .with_pit_token(PitToken::from(0xdead_beef))
+            .with_congestion_mark(CongestionMark::from(1))
+            .with_acks([Ack::from(1), Ack::from(300)]);
+
+        let mut bytes = BytesMut::from(packet.bytes().as_ref());
+        let decoded = LpPacket::decode(&mut bytes).unwrap();
+        assert_eq!(decoded, packet);
+        assert_eq!(decoded.tx_sequence.unwrap().to_u64(), 0x1_0000_0000);
+        assert_eq!(decoded.acks.len(), 2);
+        let token = decoded.pit_token.as_ref().unwrap();
+        assert_eq!(u64::try_from(token).unwrap(), 0xdead_beef);
+    }
+
+    #[test]
+    fn nack_roundtrip() {
+        let packet = LpPacket::from_tlv(tlv::Interest::new("/a/b"))
+            .with_nack(Nack::new(NackReason::Congestion));
+
+        let mut bytes = BytesMut::from(packet.bytes().as_ref());
+        let decoded = LpPacket::decode(&mut bytes).unwrap();
+        assert_eq!(decoded.nack.unwrap().reason(), NackReason::Congestion);
+
+        // Nack without NackReason
+        let mut bytes = BytesMut::from(&[100, 4, 253, 3, 32, 0][..]);
+        let decoded = LpPacket::decode(&mut bytes).unwrap();
+        assert_eq!(decoded.nack.unwrap().reason(), NackReason::None);
+    }
+
+    #[test]
+    fn local_fields_roundtrip() {
+        let packet = LpPacket::from_tlv(tlv::Interest::new("/a/b"))
+            .with_incoming_face_id(257)
+            .with_next_hop_face_id(258)
+            .with_cache_policy(CachePolicy::no_cache());
+        assert!(packet.has_local_fields());
+
+        let mut bytes = BytesMut::from(packet.bytes().as_ref());
+        let decoded = LpPacket::decode(&mut bytes).unwrap();
+        assert_eq!(decoded, packet);
+        assert!(decoded.cache_policy.unwrap().is_no_cache());
+
+        let stripped = decoded.without_local_fields();
+        assert!(!stripped.has_local_fields());
+        assert!(!stripped.has_header_fields());
+    }
+
+    #[test]
+    fn ignorable_header_field() {
+        // LpPacket with unknown header field 956 (ignorable) and an empty Fragment
+        let mut bytes = BytesMut::from(&[100, 6, 253, 3, 188, 0, 80, 0][..]);
+        let decoded = LpPacket::decode(&mut bytes).unwrap();
+        assert!(decoded.fragment.is_some());
+
+        // The same with 957, which must not be ignored
+        let mut bytes = BytesMut::from(&[100, 6, 253, 3, 189, 0, 80, 0][..]);
+        assert!(LpPacket::decode(&mut bytes).is_err());
+    }
+
+    #[test]
+    fn prefix_announcement_roundtrip() {
+        let announcement =
+            tlv::PrefixAnnouncement::new("/ucla".parse().unwrap(), Duration::from_secs(3600));
+        let packet = LpPacket::from_tlv(tlv::Data::new("/ucla/videos"))
+            .with_prefix_announcement(announcement.clone());
+        assert!(packet.has_header_fields());
+
+        let mut bytes = BytesMut::from(packet.bytes().as_ref());
+        let decoded = LpPacket::decode(&mut bytes).unwrap();
+        assert_eq!(decoded, packet);
+        let decoded = decoded.prefix_announcement.unwrap();
+        assert_eq!(decoded.prefix().to_string(), "/ucla");
+        assert_eq!(decoded.expiration_period(), Duration::from_secs(3600));
+    }
+}
@@ -0,0 +1,413 @@
+//! NDNLPv2 link-layer reliability protocol
+//!
+//! Every outgoing frame carrying a Fragment gets a TxSequence and is kept until the peer
+//! acknowledges it. Acks are piggybacked onto outgoing frames, or sent in IDLE packets if there is
+//! no traffic. Unacknowledged frames are retransmitted with a new TxSequence after RTO expires
+//! or when enough frames sent after them were acknowledged. Once the retransmissions are
+//! exhausted the frame is reported as lost.
+//!
+//! This state machine does no I/O and never reads the clock on its own, the link service feeds
+//! it with the current time and transmits whatever it asks to.
+
+use super::*;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ReliabilityOptions {
+    /// maximum number of retransmissions of a single frame
+    pub max_retx: usize,
+    /// how long to hold pending Acks waiting for an outgoing frame to piggyback on
+    pub idle_ack_timer_period: Duration,
+    /// number of Acks for later TxSequences to consider an unacknowledged frame lost
+    pub seq_num_loss_threshold: usize,
+    /// RTO estimator parameters
+    pub rtt: RttOptions,
+}
+
+impl Default for ReliabilityOptions {
+    fn default() -> Self {
+        Self {
+            max_retx: 3,
+            idle_ack_timer_period: Duration::from_millis(5),
+            seq_num_loss_threshold: 3,
+            rtt: RttOptions::default(),
+        }
+    }
+}
+
+/// Work for the link service produced by the reliability protocol
+#[derive(Clone, Debug, PartialEq)]
+pub enum Event {
+    /// Frame to be transmitted: either a retransmission or an IDLE packet carrying Acks
+    Send(LpPacket),
+    /// Frame that has not been acknowledged after all the retransmissions
+    Lost(LpPacket),
+}
+
+#[derive(Debug)]
+struct UnackedFrame {
+    packet: LpPacket,
+    sent: Instant,
+    deadline: Instant,
+    retx_count: usize,
+    n_greater_seq_acks: usize,
+}
+
+#[derive(Debug)]
+pub struct Reliability {
+    options: ReliabilityOptions,
+    mtu: Option<usize>,
+    rtt: RttEstimator,
+    next_tx_sequence: u64,
+    unacked: BTreeMap<u64, UnackedFrame>,
+    pending_acks: VecDeque<Ack>,
+    idle_ack_deadline: Option<Instant>,
+    n_acknowledged: u64,
+    n_retransmitted: u64,
+    n_retx_exhausted: u64,
+}
+
+impl Reliability {
+    /// Octets the TxSequence adds to each frame
+    pub const FRAME_OVERHEAD: usize = 3 + 1 + 8;
+
+    pub fn new(options: ReliabilityOptions) -> Self {
+        let rtt = RttEstimator::new(options.rtt);
+        // Start from a random TxSequence, so that Acks destined to a previous incarnation
+        // of this link are unlikely to match anything
+        let next_tx_sequence = rand::random::<u32>().into();
+
+        Self {
+            options,
+            mtu: None,
+            rtt,
+            next_tx_sequence,
+            unacked: BTreeMap::new(),
+            pending_acks: VecDeque::new(),
+            idle_ack_deadline: None,
+            n_acknowledged: 0,
+            n_retransmitted: 0,
+            n_retx_exhausted: 0,
+        }
+    }
+
+    /// Limit the number of piggybacked Acks so that the frame fits into `mtu` octets
+    pub fn with_mtu(self, mtu: impl Into<Option<usize>>) -> Self {
+        let mtu = mtu.into();
+        Self { mtu, ..self }
+    }
+
+    pub fn options(&self) -> &ReliabilityOptions {
+        &self.options
+    }
+
+    pub fn rtt(&self) -> &RttEstimator {
+        &self.rtt
+    }
+
+    /// Number of frames acknowledged by the peer
+    pub fn n_acknowledged(&self) -> u64 {
+        self.n_acknowledged
+    }
+
+    /// Number of frames retransmitted
+    pub fn n_retransmitted(&self) -> u64 {
+        self.n_retransmitted
+    }
+
+    /// Number of frames given up on after exhausting all the retransmissions
+    pub fn n_retx_exhausted(&self) -> u64 {
+        self.n_retx_exhausted
+    }
+
+    /// Number of frames sent, but not acknowledged yet
+    pub fn n_unacked(&self) -> usize {
+        self.unacked.len()
+    }
+
+    /// Prepare outgoing frame: assign TxSequence (if it carries a Fragment) and piggyback pending Acks
+    pub fn handle_outgoing(&mut self, packet: LpPacket, now: Instant) -> LpPacket {
+        let packet = if packet.is_idle() {
+            packet
+        } else {
+            self.track(packet, 0, now)
+        };
+        self.piggyback(packet, now)
+    }
+
+    /// Process Acks and TxSequence of the incoming frame
+    pub fn process_incoming(&mut self, packet: &LpPacket, now: Instant) -> Vec<Event> {
+        let mut events = vec![];
+
+        for ack in &packet.acks {
+            self.on_ack(*ack, now, &mut events);
+        }
+
+        if let Some(tx_sequence) = packet.tx_sequence {
+            self.pending_acks.push_back(tx_sequence.into());
+            self.idle_ack_deadline
+                .get_or_insert(now + self.options.idle_ack_timer_period);
+        }
+
+        events
+    }
+
+    /// Handle expired retransmission timers and idle Ack timer
+    pub fn poll(&mut self, now: Instant) -> Vec<Event> {
+        let mut events = vec![];
+
+        let expired = self
+            .unacked
+            .iter()
+            .filter(|(_, frame)| frame.deadline <= now)
+            .map(|(tx_sequence, _)| *tx_sequence)
+            .collect::<Vec<_>>();
+
+        if !expired.is_empty() {
+            self.rtt.backoff_rto();
+        }
+
+        for tx_sequence in expired {
+            self.on_lost_frame(tx_sequence, now, &mut events);
+        }
+
+        if self
+            .idle_ack_deadline
+            .is_some_and(|deadline| deadline <= now)
+        {
+            let idle = self.piggyback(LpPacket::new(), now);
+            if !idle.acks.is_empty() {
+                events.push(Event::Send(idle));
+            }
+        }
+
+        events
+    }
+
+    /// The earliest moment `poll()` has something to do
+    pub fn next_deadline(&self) -> Option<Instant> {
+        self.unacked
+            .values()
+            .map(|frame| frame.deadline)
+            .chain(self.idle_ack_deadline)
+            .min()
+    }
+
+    fn track(&mut self, packet: LpPacket, retx_count: usize, now: Instant) -> LpPacket {
+        let tx_sequence = self.next_tx_sequence;
+        self.next_tx_sequence = self.next_tx_sequence.wrapping_add(1);
+
+        let packet = LpPacket {
+            acks: vec![],
+            tx_sequence: None,
+            ..packet
+        };
+        let frame = UnackedFrame {
+            packet: packet.clone(),
+            sent: now,
+            deadline: now + self.rtt.estimated_rto(),
+            retx_count,
+            n_greater_seq_acks: 0,
+        };
+        self.unacked.insert(tx_sequence, frame);
+
+        packet.with_tx_sequence(tx_sequence)
+    }
+
+    fn piggyback(&mut self, mut packet: LpPacket, now: Instant) -> LpPacket {
+        use tlv::TlvCodec;
+
+        let mut size = packet.total_size();
+        while let Some(ack) = self.pending_acks.front() {
+            // Encoding the extra Ack may also grow the LpPacket TLV-LENGTH, hence the slack
+            let ack_size = ack.total_size() + 8;
+            if self.mtu.is_some_and(|mtu| size + ack_size > mtu) {
+                break;
+            }
+            size += ack.total_size();
+            packet.acks.extend(self.pending_acks.pop_front());
+        }
+
+        self.idle_ack_deadline = if self.pending_acks.is_empty() {
+            None
+        } else {
+            Some(now + self.options.idle_ack_timer_period)
+        };
+
+        packet
+    }
+
+    fn on_ack(&mut self, ack: Ack, now: Instant, events: &mut Vec<Event>) {
+        let tx_sequence = ack.to_u64();
+        let Some(frame) = self.unacked.remove(&tx_sequence) else {
+            tracing::trace!(%ack, "Ignoring Ack for unknown or already acknowledged frame");
+            return;
+        };
+
+        self.n_acknowledged += 1;
+        // Karn's algorithm: RTT of retransmitted frames is ambiguous
+        if frame.retx_count == 0 {
+            self.rtt
+                .add_measurement(now.saturating_duration_since(frame.sent));
+        }
+
+        let threshold = self.options.seq_num_loss_threshold;
+        let lost = self
+            .unacked
+            .range_mut(..tx_sequence)
+            .filter_map(|(tx_sequence, frame)| {
+                frame.n_greater_seq_acks += 1;
+                (frame.n_greater_seq_acks >= threshold).then_some(*tx_sequence)
+            })
+            .collect::<Vec<_>>();
+
+        for tx_sequence in lost {
+            self.on_lost_frame(tx_sequence, now, events);
+        }
+    }
+
+    fn on_lost_frame(&mut self, tx_sequence: u64, now: Instant, events: &mut Vec<Event>) {
+        let Some(frame) = self.unacked.remove(&tx_sequence) else {
+            return;
+        };
+
+        if frame.retx_count < self.options.max_retx {
+            self.n_retransmitted += 1;
+            tracing::trace!(tx_sequence, retx = frame.retx_count + 1, "Retransmitting");
+            let packet = self.track(frame.packet, frame.retx_count + 1, now);
+            let packet = self.piggyback(packet, now);
+            events.push(Event::Send(packet));
+        } else {
+            self.n_retx_exhausted += 1;
+            tracing::debug!(tx_sequence, "Retransmissions exhausted, frame is lost");
+            events.push(Event::Lost(frame.packet));
+        }
+    }
+}
+
+impl Default for Reliability {
+    fn default() -> Self {
+        Self::new(ReliabilityOptions::default())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn frame(name: &str) -> LpPacket {
+        LpPacket::from_tlv(tlv::Interest::new(name))
+    }
+
+    fn sent(events: Vec<Event>) -> Vec<LpPacket> {
+        events
+            .into_iter()
+            .filter_map(|event| match event {
+                Event::Send(packet) => Some(packet),
+                Event::Lost(_) => None,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn ack_and_piggyback() {
+        let now = Instant::now();
+        let mut a = Reliability::default();
+        let mut b = Reliability::default();
+
+        let packet = a.handle_outgoing(frame("/a"), now);
+        let tx_sequence = packet.tx_sequence.unwrap();
+        assert_eq!(a.n_unacked(), 1);
+
+        assert!(b.process_incoming(&packet, now).is_empty());
+        let reply = b.handle_outgoing(frame("/b"), now);
+        assert_eq!(reply.acks, vec![Ack::from(tx_sequence)]);
+
+        a.process_incoming(&reply, now + Duration::from_millis(20));
+        assert_eq!(a.n_unacked(), 0);
+        assert_eq!(a.n_acknowledged(), 1);
+        assert_eq!(a.rtt().last_rtt(), Some(Duration::from_millis(20)));
+    }
+
+    #[test]
+    fn idle_ack() {
+        let now = Instant::now();
+        let mut a = Reliability::default();
+        let mut b = Reliability::default();
+
+        let packet = a.handle_outgoing(frame("/a"), now);
+        b.process_incoming(&packet, now);
+        assert!(b.poll(now).is_empty());
+
+        let deadline = b.next_deadline().unwrap();
+        let idle = sent(b.poll(deadline));
+        assert_eq!(idle.len(), 1);
+        assert!(idle[0].is_idle());
+        assert_eq!(idle[0].acks.len(), 1);
+        assert!(b.next_deadline().is_none());
+    }
+
+    #[test]
+    fn retransmit_until_exhausted() {
+        let mut now = Instant::now();
+        let options = ReliabilityOptions {
+            max_retx: 2,
+            ..ReliabilityOptions::default()
+        };
+        let mut a = Reliability::new(options);
+        let original = a.handle_outgoing(frame("/a"), now);
+
+        let mut tx_sequences = vec![original.tx_sequence];
+        for _ in 0..2 {
+            now = a.next_deadline().unwrap();
+            let retx = sent(a.poll(now));
+            assert_eq!(retx.len(), 1);
+            assert_eq!(retx[0].fragment(), original.fragment());
+            tx_sequences.push(retx[0].tx_sequence);
+        }
+        tx_sequences.dedup();
+        assert_eq!(tx_sequences.len(), 3);
+
+        now = a.next_deadline().unwrap();
+        let events = a.poll(now);
+        assert_eq!(events, vec![Event::Lost(original.without_header_fields())]);
+        assert_eq!(a.n_retransmitted(), 2);
+        assert_eq!(a.n_retx_exhausted(), 1);
+        assert!(a.next_deadline().is_none());
+    }
+
+    #[test]
+    fn loss_detected_by_later_acks() {
+        let now = Instant::now();
+        let mut a = Reliability::default();
+        let frames = ["/1", "/2", "/3", "/4"]
+            .map(|name| a.handle_outgoing(frame(name), now))
+            .to_vec();
+
+        let acks = frames[1..]
+            .iter()
+            .filter_map(|packet| packet.tx_sequence)
+            .map(Ack::from);
+        let feedback = LpPacket::new().with_acks(acks);
+
+        let retx = sent(a.process_incoming(&feedback, now));
+        assert_eq!(retx.len(), 1);
+        assert_eq!(retx[0].fragment(), frames[0].fragment());
+        assert_ne!(retx[0].tx_sequence, frames[0].tx_sequence);
+        assert_eq!(a.n_unacked(), 1);
+    }
+
+    #[test]
+    fn piggyback_respects_mtu() {
+        let now = Instant::now();
+        let mut b = Reliability::default().with_mtu(64);
+        for n in 0..32 {
+            let packet = LpPacket::from_tlv(tlv::Interest::new("/x")).with_tx_sequence(n);
+            b.process_incoming(&packet, now);
+        }
+
+        let reply = b.handle_outgoing(frame("/y"), now);
+        assert!(!reply.acks.is_empty());
+        assert!(reply.size() <= 64);
+        assert!(b.next_deadline().is_some());
+    }
+}
@@ -0,0 +1,103 @@
+use super::*;
+
+/// Parameters of the RTT estimator, see RFC 6298
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct RttOptions {
+    /// weight of the exponential moving average for the smoothed RTT
+    pub alpha: f64,
+    /// weight of the exponential moving average for the RTT variation
+    pub beta: f64,
+    /// RTO before the first RTT measurement
+    pub initial_rto: Duration,
+    pub min_rto: Duration,
+    pub max_rto: Duration,
+    /// RTT variation multiplier used when computing RTO
+    pub k: u32,
+    /// RTO multiplier used in backoff operation
+    pub rto_backoff_multiplier: u32,
+}
+
+impl Default for RttOptions {
+    fn default() -> Self {
+        Self {
+            alpha: 0.125,
+            beta: 0.25,
+            initial_rto: Duration::from_secs(1),
+            min_rto: Duration::from_millis(200),
+            max_rto: Duration::from_secs(60),
+            k: 4,
+            rto_backoff_multiplier: 2,
+        }
+    }
+}
+
+/// Retransmission timeout (RTO) estimator following RFC 6298
+#[derive(Clone, Debug)]
+pub struct RttEstimator {
+    options: RttOptions,
+    srtt: Option<Duration>,
+    rttvar: Duration,
+    rto: Duration,
+    last_rtt: Option<Duration>,
+}
+
+impl RttEstimator {
+    pub fn new(options: RttOptions) -> Self {
+        Self {
+            options,
+            srtt: None,
+            rttvar: Duration::ZERO,
+            rto: options.initial_rto,
+            last_rtt: None,
+        }
+    }
+
+    /// Record a new RTT measurement
+    pub fn add_measurement(&mut self, rtt: Duration) {
+        let RttOptions { alpha, beta, .. } = self.options;
+        let (srtt, rttvar) = if let Some(srtt) = self.srtt {
+            let delta = srtt.abs_diff(rtt);
+            let rttvar = self.rttvar.mul_f64(1.0 - beta) + delta.mul_f64(beta);
+            let srtt = srtt.mul_f64(1.0 - alpha) + rtt.mul_f64(alpha);
+            (srtt, rttvar)
+        } else {
+            (rtt, rtt / 2)
+        };
+
+        self.srtt = Some(srtt);
+        self.rttvar = rttvar;
+        self.last_rtt = Some(rtt);
+        self.rto = self.clamp(srtt + rttvar * self.options.k);
+    }
+
+    /// Back off RTO after a timeout
+    pub fn backoff_rto(&mut self) {
+        self.rto = self.clamp(self.rto * self.options.rto_backoff_multiplier);
+    }
+
+    pub fn estimated_rto(&self) -> Duration {
+        self.rto
+    }
+
+    pub fn smoothed_rtt(&self) -> Option<Duration> {
+        self.srtt
+    }
+
+    pub fn rtt_variation(&self) -> Duration {
+        self.rttvar
+    }
+
+    pub fn last_rtt(&self) -> Option<Duration> {
+        self.last_rtt
+    }
+
+    fn clamp(&self, rto: Duration) -> Duration {
+        rto.clamp(self.options.min_rto, self.options.max_rto)
+    }
+}
+
+impl Default for RttEstimator {
+    fn default() -> Self {
+        Self::new(RttOptions::default())
+    }
+}
@@ -6,6 +6,19 @@ pub struct Sequence {
     sequence: [u8; 8],
 }
 
+impl Sequence {
+    pub fn to_u64(&self) -> u64 {
+        u64::from_be_bytes(self.sequence)
+    }
+}
+
+impl From<u64> for Sequence {
+    fn from(sequence: u64) -> Self {
+        let sequence = sequence.to_be_bytes();
+        Self { sequence }
+    }
+}
+
 impl tlv::Tlv for Sequence {
     type Error = tlv::DecodeError;
     const TYPE: tlv::Type = tlv::Type::Sequence;
@@ -37,3 +50,16 @@ impl tlv::Tlv for Sequence {
         }
     }
 }
+
+// TxSequence contains a sequence number assigned to each transmission of a link-layer frame
+// by the reliability protocol; a retransmitted frame gets a new TxSequence.
+tlv::non_negative_number!(TxSequence => tlv::Type::TxSequence);
+
+// Ack acknowledges the receipt of a frame carrying the same TxSequence.
+tlv::non_negative_number!(Ack => tlv::Type::Ack);
+
+impl From<TxSequence> for Ack {
+    fn from(tx_sequence: TxSequence) -> Self {
+        Self::from(tx_sequence.to_u64())
+    }
+}
@@ -0,0 +1,45 @@
+use super::*;
+
+/// PitToken is an opaque hop-by-hop token assigned by the upstream-facing node to an outgoing
+/// Interest, to be echoed back unchanged on the corresponding Data or Nack.
+#[derive(Clone, Debug, PartialEq, Eq, Hash, tlv::Tlv)]
+#[tlv(r#type = tlv::Type::PitToken, error = tlv::DecodeError, crates(tlv_core = tlv::core))]
+pub struct PitToken {
+    token: Bytes,
+}
+
+impl PitToken {
+    pub fn new(token: impl Into<Bytes>) -> Self {
+        let token = token.into();
+        Self { token }
+    }
+
+    pub fn as_bytes(&self) -> &Bytes {
+        &self.token
+    }
+}
+
+impl From<u64> for PitToken {
+    fn from(value: u64) -> Self {
+        Self::new(value.to_be_bytes().to_vec())
+    }
+}
+
+impl TryFrom<&PitToken> for u64 {
+    type Error = tlv::DecodeError;
+
+    fn try_from(token: &PitToken) -> Result<Self, Self::Error> {
+        let bytes = <[u8; 8]>::try_from(token.token.as_ref())
+            .map_err(|_| tlv::DecodeError::invalid("PitToken is not 8 octets long"))?;
+        Ok(Self::from_be_bytes(bytes))
+    }
+}
+
+impl fmt::Display for PitToken {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "pit_token=")?;
+        self.token
+            .iter()
+            .try_for_each(|byte| write!(f, "{byte:02x}"))
+    }
+}
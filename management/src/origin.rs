@@ -1,3 +1,27 @@
 use super::*;
 
-tlv::non_negative_number!(Origin => tlv::Type::Origin);
+tlv::non_negative_number!(Origin => tlv::Type::Origin; display_as_str);
+
+#[allow(non_upper_case_globals)]
+impl Origin {
+    pub const App: Self = Self(tlv::NonNegativeNumber(0));
+    pub const Autoreg: Self = Self(tlv::NonNegativeNumber(64));
+    pub const Client: Self = Self(tlv::NonNegativeNumber(65));
+    pub const Autoconf: Self = Self(tlv::NonNegativeNumber(66));
+    pub const Nlsr: Self = Self(tlv::NonNegativeNumber(128));
+    pub const PrefixAnn: Self = Self(tlv::NonNegativeNumber(129));
+    pub const Static: Self = Self(tlv::NonNegativeNumber(255));
+
+    pub fn as_str(&self) -> &'static str {
+        match *self {
+            Self::App => "app",
+            Self::Autoreg => "autoreg",
+            Self::Client => "client",
+            Self::Autoconf => "autoconf",
+            Self::Nlsr => "nlsr",
+            Self::PrefixAnn => "prefixann",
+            Self::Static => "static",
+            _ => "unknown",
+        }
+    }
+}
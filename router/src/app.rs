@@ -0,0 +1,289 @@
+//! In-process applications attached to the router through internal faces
+
+use std::future::Future;
+use std::time::Duration;
+
+use bytes::BytesMut;
+use tlv::TlvCodec;
+use tokio::sync::oneshot;
+use tokio::sync::Mutex;
+
+use super::*;
+
+/// Application answering the Interests under a registered prefix
+pub trait Producer: Send + Sync + 'static {
+    /// Data satisfying `interest`, if there is any
+    fn on_interest(&self, interest: Interest) -> impl Future<Output = Option<Data>> + Send;
+}
+
+impl<F, Fut> Producer for F
+where
+    F: Fn(Interest) -> Fut + Send + Sync + 'static,
+    Fut: Future<Output = Option<Data>> + Send,
+{
+    fn on_interest(&self, interest: Interest) -> impl Future<Output = Option<Data>> + Send {
+        self(interest)
+    }
+}
+
+type Pending = Arc<Mutex<HashMap<lpv2::PitToken, oneshot::Sender<io::Result<Data>>>>>;
+
+/// Application end of the face shared by all the in-process consumers,
+/// responses are told apart by the PitToken attached to each Interest
+#[derive(Debug)]
+pub(crate) struct Consumer {
+    app: Arc<transport::Internal>,
+    pending: Pending,
+    next_token: AtomicU64,
+}
+
+impl Router {
+    /// Serve the Interests under `prefix` with `producer` over a new in-process face
+    pub async fn register_producer(
+        self: &Arc<Self>,
+        prefix: tlv::Name,
+        producer: impl Producer,
+    ) -> io::Result<face::FaceId> {
+        let (face, app) = self.attach_app_face().await?;
+        self.forwarding_information_base
+            .add_nexthop(prefix, face, 0)
+            .await;
+
+        let producer = Arc::new(producer);
+        let app = Arc::new(app);
+        tokio::spawn(async move {
+            loop {
+                let packet = match recv_packet(&app).await {
+                    Ok(packet) => packet,
+                    Err(error) => {
+                        tracing::debug!(%face, %error, "Producer face closed");
+                        break;
+                    }
+                };
+                let Some(interest) = packet
+                    .network_packet()
+                    .filter(|_| packet.nack.is_none())
+                    .and_then(|generic| Interest::decode_from_generic(generic).ok())
+                else {
+                    continue;
+                };
+
+                let producer = Arc::clone(&producer);
+                let app = Arc::clone(&app);
+                tokio::spawn(async move {
+                    if let Some(data) = producer.on_interest(interest).await {
+                        let packet =
+                            lpv2::LpPacket::from_tlv(data).with_pit_token(packet.pit_token);
+                        if let Err(error) = send_packet(&app, packet).await {
+                            tracing::debug!(%face, %error, "Failed to answer Interest");
+                        }
+                    }
+                });
+            }
+        });
+
+        Ok(face)
+    }
+
+    /// Send `interest` from the in-process consumer face and wait for the Data
+    /// for as long as the InterestLifetime
+    pub async fn express_interest(self: &Arc<Self>, interest: Interest) -> io::Result<Data> {
+        self.consumer
+            .get_or_try_init(|| Consumer::attach(self))
+            .await?
+            .express(interest)
+            .await
+    }
+
+    /// New in-process face served by its own task, returns the application end of it
+    async fn attach_app_face(self: &Arc<Self>) -> io::Result<(face::FaceId, transport::Internal)> {
+        let (forwarder, app) = transport::Internal::pair();
+        let uri = face::Uri::from(face::Internal::PREFIX);
+        let face = Face::with_transport(uri, face::FacePersistency::OnDemand, forwarder)?;
+        let face = self.attach_face(face).await;
+        Ok((face, app))
+    }
+}
+
+impl Consumer {
+    async fn attach(router: &Arc<Router>) -> io::Result<Self> {
+        let (_, app) = router.attach_app_face().await?;
+        let app = Arc::new(app);
+        let pending = Pending::default();
+        tokio::spawn(Self::deliver(Arc::clone(&app), Arc::clone(&pending)));
+        Ok(Self {
+            app,
+            pending,
+            next_token: AtomicU64::new(0),
+        })
+    }
+
+    /// Hand the Data and Nacks arriving on the face over to the waiting consumers
+    async fn deliver(app: Arc<transport::Internal>, pending: Pending) {
+        loop {
+            let packet = match recv_packet(&app).await {
+                Ok(packet) => packet,
+                Err(error) => {
+                    tracing::debug!(%error, "Consumer face closed");
+                    break;
+                }
+            };
+            let (Some(token), Some(generic)) = (&packet.pit_token, packet.network_packet()) else {
+                continue;
+            };
+
+            let response = match (generic.r#type, packet.nack) {
+                (tlv::Type::Data, _) => Data::decode_from_generic(generic).map_err(io::Error::from),
+                (tlv::Type::Interest, Some(nack)) => Err(io::Error::other(format!("{nack}"))),
+                _ => continue,
+            };
+            if let Some(tx) = pending.lock().await.remove(token) {
+                let _ = tx.send(response);
+            }
+        }
+    }
+
+    async fn express(&self, interest: Interest) -> io::Result<Data> {
+        let lifetime = interest.interest_lifetime.map_or(
+            PendingInterestTable::DEFAULT_INTEREST_LIFETIME,
+            |lifetime| Duration::from_millis(lifetime.to_u64()),
+        );
+        let token = lpv2::PitToken::from(self.next_token.fetch_add(1, Ordering::Relaxed));
+        let (tx, rx) = oneshot::channel();
+        self.pending.lock().await.insert(token.clone(), tx);
+
+        let packet = lpv2::LpPacket::from_tlv(interest).with_pit_token(Some(token.clone()));
+        send_packet(&self.app, packet).await?;
+
+        let response = time::timeout(lifetime, rx).await;
+        self.pending.lock().await.remove(&token);
+        match response {
+            Ok(Ok(response)) => response,
+            Ok(Err(_)) => Err(io::Error::other("Consumer face has been closed")),
+            Err(_) => Err(io::Error::new(
+                io::ErrorKind::TimedOut,
+                "Interest timed out",
+            )),
+        }
+    }
+}
+
+async fn recv_packet(app: &transport::Internal) -> io::Result<lpv2::LpPacket> {
+    let bytes = app.recv().await?;
+    let generic = tlv::Generic::decode(&mut BytesMut::from(bytes.as_ref()))?;
+    if generic.r#type == tlv::Type::LpPacket {
+        lpv2::LpPacket::decode_from_generic(generic).map_err(io::Error::from)
+    } else {
+        Ok(lpv2::LpPacket::from_tlv(generic))
+    }
+}
+
+async fn send_packet(app: &transport::Internal, packet: lpv2::LpPacket) -> io::Result<()> {
+    app.send(packet.bytes()).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn produce_and_consume() {
+        let router = Arc::new(Router::new());
+        let prefix = "/hello".parse().unwrap();
+        router
+            .register_producer(prefix, |interest: Interest| async move {
+                Some(Data::new(interest.name.to_string()))
+            })
+            .await
+            .unwrap();
+
+        let interest = Interest::new("/hello/world");
+        let data = router.express_interest(interest).await.unwrap();
+        assert_eq!(data.name.to_string(), "/hello/world");
+
+        // Concurrent consumers get their own Data
+        let names = ["/hello/1", "/hello/2", "/hello/3"];
+        let expressed = names.map(|name| {
+            let router = Arc::clone(&router);
+            tokio::spawn(async move { router.express_interest(Interest::new(name)).await })
+        });
+        for (name, expressed) in names.into_iter().zip(expressed) {
+            let data = expressed.await.unwrap().unwrap();
+            assert_eq!(data.name.to_string(), name);
+        }
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn no_producer() {
+        let router = Arc::new(Router::new());
+        let interest = Interest::new("/nobody").with_interest_lifetime(100);
+        let error = router.express_interest(interest).await.unwrap_err();
+        assert_eq!(error.to_string(), "nack=no-route");
+        assert!(router.shards()[0].pending_interest_table().is_empty().await);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn upstream_gone() {
+        let router = Arc::new(Router::new());
+        let (face, _upstream) = router.attach_app_face().await.unwrap();
+        let prefix = "/gone".parse().unwrap();
+        router
+            .forwarding_information_base
+            .add_nexthop(prefix, face, 0)
+            .await;
+        router.destroy_face(face).await;
+
+        // The route outlives its face, the Interest is not left hanging
+        let interest = Interest::new("/gone/1").with_interest_lifetime(100);
+        let error = router.express_interest(interest).await.unwrap_err();
+        assert_eq!(error.to_string(), "nack=no-route");
+        assert!(router.shards()[0].pending_interest_table().is_empty().await);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn duplicate_nonce() {
+        let router = Arc::new(Router::new());
+        router
+            .register_producer("/".parse().unwrap(), |_| async { None })
+            .await
+            .unwrap();
+        let (_, first) = router.attach_app_face().await.unwrap();
+        let (_, second) = router.attach_app_face().await.unwrap();
+
+        // Same Interest coming back over another face is looping
+        let interest = Interest::new("/loop");
+        for app in [&first, &second] {
+            let packet = lpv2::LpPacket::from_tlv(interest.clone());
+            send_packet(app, packet).await.unwrap();
+        }
+        let packet = recv_packet(&second).await.unwrap();
+        assert_eq!(packet.nack.unwrap().reason(), lpv2::NackReason::Duplicate);
+        assert_eq!(router.shards()[0].pending_interest_table().len().await, 1);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn nack_from_upstream() {
+        let router = Arc::new(Router::new());
+        let (face, upstream) = router.attach_app_face().await.unwrap();
+        let prefix = "/congested".parse().unwrap();
+        router
+            .forwarding_information_base
+            .add_nexthop(prefix, face, 0)
+            .await;
+
+        let expressed = tokio::spawn({
+            let router = Arc::clone(&router);
+            async move { router.express_interest(Interest::new("/congested/1")).await }
+        });
+        let packet = recv_packet(&upstream).await.unwrap();
+        let nack = lpv2::Nack::new(lpv2::NackReason::Congestion);
+        send_packet(&upstream, packet.with_nack(nack))
+            .await
+            .unwrap();
+
+        // The only upstream gave up, so does the router
+        let error = expressed.await.unwrap().unwrap_err();
+        assert_eq!(error.to_string(), "nack=congestion");
+        assert!(router.shards()[0].pending_interest_table().is_empty().await);
+    }
+}
@@ -0,0 +1,92 @@
+//! Listening channels, the faces of the remote endpoints connecting to the router
+
+use super::*;
+
+impl Router {
+    /// Accept the connections arriving on `channel` until it fails,
+    /// each connection becoming an on-demand face
+    pub async fn serve_channel(self: &Arc<Self>, channel: transport::Channel) -> io::Result<()> {
+        let local = channel.local_uri()?;
+        tracing::info!(%local, "Listening");
+        loop {
+            let handshake = channel.accept().await?;
+            let router = Arc::clone(self);
+            tokio::spawn(async move {
+                match handshake.await.and_then(Face::accepted) {
+                    Ok(face) => {
+                        let face = router.attach_face(face).await;
+                        tracing::debug!(%face, "Accepted");
+                    }
+                    Err(error) => tracing::debug!(%error, "Failed to accept connection"),
+                }
+            });
+        }
+    }
+}
+
+impl Face {
+    /// Face over a transport accepted by a channel, gone once the remote end disconnects
+    fn accepted(transport: Box<dyn transport::Transport>) -> io::Result<Self> {
+        let uri = transport.remote_uri()?;
+        Self::with_transport(uri, face::FacePersistency::OnDemand, transport)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use futures::SinkExt;
+
+    use super::*;
+
+    #[tokio::test]
+    async fn websocket_clients() {
+        let router = Arc::new(Router::new());
+        let prefix = "/hello".parse().unwrap();
+        router
+            .register_producer(prefix, |interest: Interest| async move {
+                Some(Data::new(interest.name.to_string()))
+            })
+            .await
+            .unwrap();
+
+        let local = face::LocalUri::from("ws://127.0.0.1:0");
+        let channel = transport::Channel::new(local.to_addr().await.unwrap())
+            .await
+            .unwrap();
+        let remote = face::Uri::from(&*channel.local_uri().unwrap());
+        tokio::spawn({
+            let router = Arc::clone(&router);
+            async move { router.serve_channel(channel).await }
+        });
+
+        let mut client = transport::Registry::default()
+            .connect(remote, None)
+            .await
+            .unwrap();
+        client.send_item(Interest::new("/hello/ws")).await.unwrap();
+        let item = client.recv_item().await.unwrap().unwrap();
+        let data = Data::decode_from_generic(item).unwrap();
+        assert_eq!(data.name.to_string(), "/hello/ws");
+
+        let handles = router.faces.get_handles().await;
+        let accepted = handles
+            .iter()
+            .find(|face| face.uri().starts_with(face::Ws::PREFIX))
+            .unwrap();
+        assert_eq!(accepted.persistency(), face::FacePersistency::OnDemand);
+        let accepted = accepted.face_id();
+
+        // The face goes away along with the client
+        client.close().await.unwrap();
+        let removed = async {
+            while router.faces.get_face(accepted).await.is_ok() {
+                time::sleep(Duration::from_millis(10)).await;
+            }
+        };
+        time::timeout(Duration::from_secs(5), removed)
+            .await
+            .unwrap();
+    }
+}
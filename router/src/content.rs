@@ -1,13 +1,155 @@
+use std::collections::BTreeMap;
+
+use tlv::TlvCodec;
+
 use super::*;
 
+#[derive(Debug)]
+struct Entry {
+    data: Data,
+    /// position in the insertion order
+    seq: u64,
+}
+
+#[derive(Debug, Default)]
+struct Cs {
+    /// in canonical name order, the Data under a prefix follow it
+    entries: BTreeMap<tlv::Name, Entry>,
+    /// names by insertion sequence number, oldest first
+    order: BTreeMap<u64, tlv::Name>,
+    next_seq: u64,
+    n_bytes: usize,
+}
+
 #[derive(Debug, Default)]
 pub struct ContentStore {
-    store: RwLock<HashMap<Interest, Data>>,
+    store: RwLock<Cs>,
+    max_bytes: Option<usize>,
+    n_evictions: AtomicU64,
 }
 
 impl ContentStore {
+    /// Limit the total size of the stored Data, the oldest Data is evicted first
+    pub fn with_max_bytes(self, max_bytes: Option<usize>) -> Self {
+        Self { max_bytes, ..self }
+    }
+
+    /// Data named exactly as `interest` or, with CanBePrefix, the first one under its name
+    /// in canonical order
     pub async fn lookup(&self, interest: &Interest) -> Option<RwLockReadGuard<'_, Data>> {
         let store = self.store.read().await;
-        RwLockReadGuard::try_map(store, |store| store.get(interest)).ok()
+        RwLockReadGuard::try_map(store, |store| {
+            if interest.is_can_be_prefix() {
+                store
+                    .entries
+                    .range(&interest.name..)
+                    .take_while(|(name, _)| interest.name.is_prefix_of(name))
+                    .map(|(_, entry)| &entry.data)
+                    .find(|data| interest.matches_data(data))
+            } else {
+                store.entries.get(&interest.name).map(|entry| &entry.data)
+            }
+        })
+        .ok()
+    }
+
+    pub async fn insert(&self, data: Data) {
+        let size = data.total_size();
+        if self.max_bytes.is_some_and(|max| size > max) {
+            tracing::debug!(name = %data.name, size, "Data does not fit in the Content Store");
+            return;
+        }
+
+        let mut store = self.store.write().await;
+        let name = data.name.clone();
+        let seq = store.next_seq;
+        store.next_seq += 1;
+        if let Some(old) = store.entries.insert(name.clone(), Entry { data, seq }) {
+            store.n_bytes -= old.data.total_size();
+            store.order.remove(&old.seq);
+        }
+        store.order.insert(seq, name);
+        store.n_bytes += size;
+
+        while self.max_bytes.is_some_and(|max| store.n_bytes > max) {
+            let Some((_, oldest)) = store.order.pop_first() else {
+                break;
+            };
+            if let Some(evicted) = store.entries.remove(&oldest) {
+                store.n_bytes -= evicted.data.total_size();
+                self.n_evictions.fetch_add(1, Ordering::Relaxed);
+            }
+        }
+    }
+
+    pub async fn len(&self) -> usize {
+        self.store.read().await.entries.len()
+    }
+
+    pub async fn is_empty(&self) -> bool {
+        self.store.read().await.entries.is_empty()
+    }
+
+    /// Total size of the stored Data in octets
+    pub async fn n_bytes(&self) -> usize {
+        self.store.read().await.n_bytes
+    }
+
+    /// Number of Data evicted to stay within the capacity
+    pub fn n_evictions(&self) -> u64 {
+        self.n_evictions.load(Ordering::Relaxed)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn max_bytes() {
+        let size = Data::new("/a/1").total_size();
+        let cs = ContentStore::default().with_max_bytes(Some(size * 2));
+
+        for name in ["/a/1", "/a/2", "/a/3"] {
+            cs.insert(Data::new(name)).await;
+        }
+        assert_eq!(cs.len().await, 2);
+        assert_eq!(cs.n_bytes().await, size * 2);
+        assert_eq!(cs.n_evictions(), 1);
+
+        // The oldest one is gone
+        assert!(cs.lookup(&Interest::new("/a/1")).await.is_none());
+        assert!(cs.lookup(&Interest::new("/a/3")).await.is_some());
+
+        // Inserting again makes it the newest
+        cs.insert(Data::new("/a/2")).await;
+        cs.insert(Data::new("/a/4")).await;
+        assert!(cs.lookup(&Interest::new("/a/2")).await.is_some());
+        assert!(cs.lookup(&Interest::new("/a/3")).await.is_none());
+        assert_eq!(cs.n_bytes().await, size * 2);
+    }
+
+    #[tokio::test]
+    async fn can_be_prefix() {
+        let cs = ContentStore::default();
+        for name in ["/b", "/a/c", "/a/b/2", "/a/b/1", "/ab"] {
+            cs.insert(Data::new(name)).await;
+        }
+
+        // The first Data under the prefix in canonical order, whatever the insertion order
+        let expected = [
+            ("/a", Some("/a/b/1")),
+            ("/a/b", Some("/a/b/1")),
+            ("/ab", Some("/ab")),
+            ("/c", None),
+        ];
+        for (prefix, name) in expected {
+            let interest = Interest::new(prefix).can_be_prefix();
+            let found = cs.lookup(&interest).await.map(|data| data.name.to_string());
+            assert_eq!(found.as_deref(), name);
+        }
+
+        // Exact match only without CanBePrefix
+        assert!(cs.lookup(&Interest::new("/a")).await.is_none());
     }
 }
@@ -4,16 +4,22 @@ use slotmap::SlotMap;
 
 use super::*;
 
+pub use handle::FaceEvent;
+pub use handle::FaceHandle;
+
 mod create;
 mod destroy;
+mod handle;
 
 #[derive(Debug)]
 pub struct Face {
     face_id: face::FaceId,
     uri: face::Uri,
     local_uri: face::LocalUri,
-    mtu: face::Mtu,
+    mtu: Option<face::Mtu>,
     persistency: face::FacePersistency,
+    face_scope: face::FaceScope,
+    link_type: face::LinkType,
     base_congestion_marking_interval: Option<face::BaseCongestionMarkingInterval>,
     default_congestion_threshold: Option<face::DefaultCongestionThreshold>,
     flags: face::Flags,
@@ -26,7 +32,14 @@ pub struct Face {
     n_out_nacks: u64,
     n_in_bytes: u64,
     n_out_bytes: u64,
-    transport: transport::Transport,
+    reliability_options: lpv2::ReliabilityOptions,
+    reliability: Option<lpv2::Reliability>,
+    fragmenter: lpv2::Fragmenter,
+    reassembler: lpv2::Reassembler,
+    congestion_marking: Option<lpv2::CongestionMarking>,
+    dropped_interests: Vec<Interest>,
+    transport: Box<dyn transport::Transport>,
+    transport_closed: bool,
 }
 
 slotmap::new_key_type! { struct FaceKey; }
@@ -38,25 +51,73 @@ impl From<face::FaceId> for FaceKey {
     }
 }
 
-#[derive(Debug, Default)]
+/// Face table, each face runs as its own task and the table holds the handles only
+#[derive(Debug)]
 pub struct FaceManegement {
-    faces: RwLock<SlotMap<FaceKey, Face>>,
+    faces: RwLock<SlotMap<FaceKey, Arc<FaceHandle>>>,
+    reliability_options: lpv2::ReliabilityOptions,
+    interest_rate_limit: Option<InterestRateLimit>,
+    queue_capacity: usize,
+    transports: transport::Registry,
 }
 
 impl FaceManegement {
+    /// Packets each face buffers in either direction
+    pub const DEFAULT_QUEUE_CAPACITY: usize = 64;
+
     pub fn new() -> Self {
         Self::default()
     }
 
-    #[tracing::instrument]
+    /// Send and receive queue capacity for the faces created from now on
+    pub fn with_queue_capacity(self, queue_capacity: usize) -> Self {
+        Self {
+            queue_capacity,
+            ..self
+        }
+    }
+
+    /// LpReliability retry count and RTO parameters for the faces created from now on
+    pub fn with_reliability_options(self, reliability_options: lpv2::ReliabilityOptions) -> Self {
+        Self {
+            reliability_options,
+            ..self
+        }
+    }
+
+    /// Transports by URI scheme for the faces created from now on
+    pub fn with_transport_registry(self, transports: transport::Registry) -> Self {
+        Self { transports, ..self }
+    }
+
+    /// Incoming Interest rate limit for the faces created from now on
+    pub fn with_interest_rate_limit(self, interest_rate_limit: Option<InterestRateLimit>) -> Self {
+        Self {
+            interest_rate_limit,
+            ..self
+        }
+    }
+
     pub async fn create(&self, params: mgmt::ControlParameters) -> mgmt::ControlResponse {
+        self.create_face(params).await.0
+    }
+
+    /// Same as [`Self::create`], along with the handle of the face created
+    #[tracing::instrument]
+    pub(crate) async fn create_face(
+        &self,
+        params: mgmt::ControlParameters,
+    ) -> (mgmt::ControlResponse, Option<Arc<FaceHandle>>) {
         match params.try_into() {
-            Ok(create) => self.create_impl(create).await.map_or_else(
-                mgmt::ControlResponse::socket_error,
-                mgmt::ControlResponse::from,
+            Ok(create) => match self.create_impl(create).await {
+                Ok((response, handle)) => (response.into(), Some(handle)),
+                Err(error) => (mgmt::ControlResponse::socket_error(error), None),
+            },
+
+            Err(reason) => (
+                mgmt::ControlResponse::incorrect_control_parameters(reason),
+                None,
             ),
-
-            Err(reason) => mgmt::ControlResponse::incorrect_control_parameters(reason),
         }
     }
 
@@ -71,19 +132,30 @@ impl FaceManegement {
     }
 
     pub async fn send_item(&self, face: face::FaceId, item: impl tlv::Tlv) -> io::Result<()> {
-        self.get_face_mut(face).await?.send_item(item).await
+        let packet = lpv2::LpPacket::from_tlv(item);
+        self.send_packet(face, packet).await
     }
 
+    pub async fn send_packet(&self, face: face::FaceId, packet: lpv2::LpPacket) -> io::Result<()> {
+        self.get_face(face).await?.send_packet(packet).await
+    }
+
+    /// Receive next network layer packet, skipping the link layer events
     pub async fn recv_item(&self, face: face::FaceId) -> io::Result<tlv::Generic> {
+        let face = self.get_face(face).await?;
         loop {
-            if let Some(item) = self.get_face_mut(face).await?.recv_item().await.transpose() {
-                break item;
-            } else {
-                continue;
+            if let FaceEvent::Packet(packet) = face.recv_event().await? {
+                if let Some(item) = packet.network_packet() {
+                    break Ok(item);
+                }
             }
         }
     }
 
+    pub async fn recv_event(&self, face: face::FaceId) -> io::Result<FaceEvent> {
+        self.get_face(face).await?.recv_event().await
+    }
+
     pub async fn get_faces(&self) -> Vec<face::FaceId> {
         self.faces
             .read()
@@ -93,42 +165,55 @@ impl FaceManegement {
             .collect()
     }
 
-    #[tracing::instrument]
-    pub async fn get_face(&self, face: face::FaceId) -> io::Result<RwLockReadGuard<'_, Face>> {
-        let key = face.into();
-        let faces = self.faces.read().await;
-        RwLockReadGuard::try_map(faces, |faces| faces.get(key))
-            .map_err(|_| io::Error::other("FaceId not found"))
+    pub async fn get_handles(&self) -> Vec<Arc<FaceHandle>> {
+        self.faces.read().await.values().cloned().collect()
     }
 
+    /// The table lock is only held long enough to clone the handle
     #[tracing::instrument]
-    pub async fn get_face_mut(
-        &self,
-        face: face::FaceId,
-    ) -> io::Result<RwLockMappedWriteGuard<'_, Face>> {
+    pub async fn get_face(&self, face: face::FaceId) -> io::Result<Arc<FaceHandle>> {
         let key = face.into();
-        let faces = self.faces.write().await;
-        RwLockWriteGuard::try_map(faces, |faces| faces.get_mut(key))
-            .map_err(|_| io::Error::other("FaceId not found"))
+        self.faces
+            .read()
+            .await
+            .get(key)
+            .cloned()
+            .ok_or_else(|| io::Error::other("FaceId not found"))
     }
 
-    async fn insert(&self, face: Face) -> face::FaceId {
+    /// Start the `face` task
+    pub(crate) async fn insert(&self, face: Face) -> face::FaceId {
         let key = self.faces.write().await.insert_with_key(|key| {
             let id = key.data().as_ffi();
             let face_id = face::FaceId::from(id);
-            face.update_face_id(face_id)
+            let face = face.update_face_id(face_id);
+            let handle = FaceHandle::spawn(face, self.queue_capacity, self.interest_rate_limit);
+            Arc::new(handle)
         });
         let id = key.data().as_ffi();
         face::FaceId::from(id)
     }
 
-    async fn remove(&self, face: face::FaceId) -> Option<Face> {
+    async fn remove(&self, face: face::FaceId) -> Option<Arc<FaceHandle>> {
         let key = face.into();
         self.faces.write().await.remove(key)
     }
 }
 
+impl Default for FaceManegement {
+    fn default() -> Self {
+        Self {
+            faces: RwLock::default(),
+            reliability_options: lpv2::ReliabilityOptions::default(),
+            interest_rate_limit: None,
+            queue_capacity: Self::DEFAULT_QUEUE_CAPACITY,
+            transports: transport::Registry::default(),
+        }
+    }
+}
+
 impl Face {
+    /// Face over one of the built-in transports
     #[tracing::instrument]
     pub async fn new(
         uri: face::Uri,
@@ -136,17 +221,37 @@ impl Face {
         persistency: face::FacePersistency,
         mtu: Option<face::Mtu>,
     ) -> io::Result<Self> {
-        let face_id = face::FaceId::null(); // To be updated with actual FaceId later
-        let remote = uri.to_addr().await?;
-        let local = if let Some(uri) = local_uri {
-            uri.to_addr().await?
-        } else {
-            remote.any()
-        };
+        let transports = transport::Registry::default();
+        Self::connect(&transports, uri, local_uri, Some(persistency), mtu).await
+    }
+
+    /// Face over a transport opened by the `transports` connector of the `uri` scheme.
+    /// Unless given, the persistency is the one suggested by the transport.
+    #[tracing::instrument]
+    pub async fn connect(
+        transports: &transport::Registry,
+        uri: face::Uri,
+        local_uri: Option<face::LocalUri>,
+        persistency: Option<face::FacePersistency>,
+        mtu: Option<face::Mtu>,
+    ) -> io::Result<Self> {
+        let transport = transports.connect(uri.clone(), local_uri).await?;
+        let persistency = persistency.unwrap_or_else(|| transport.persistency());
+        let face = Self::with_transport(uri, persistency, transport)?;
+        Ok(face.update_mtu(mtu))
+    }
 
-        let transport = transport::Transport::new(local, remote).await?;
+    /// Face over an already established `transport`
+    pub fn with_transport(
+        uri: face::Uri,
+        persistency: face::FacePersistency,
+        transport: impl transport::Transport + 'static,
+    ) -> io::Result<Self> {
+        let face_id = face::FaceId::null(); // To be updated with actual FaceId later
         let local_uri = transport.local_uri()?;
         let mtu = transport.mtu();
+        let face_scope = transport.face_scope();
+        let link_type = transport.link_type();
         let flags = face::Flags::empty();
         let mask = face::Mask::empty();
 
@@ -156,6 +261,8 @@ impl Face {
             local_uri,
             mtu,
             persistency,
+            face_scope,
+            link_type,
             base_congestion_marking_interval: None,
             default_congestion_threshold: None,
             flags,
@@ -168,7 +275,14 @@ impl Face {
             n_out_nacks: 0,
             n_in_bytes: 0,
             n_out_bytes: 0,
-            transport,
+            reliability_options: lpv2::ReliabilityOptions::default(),
+            reliability: None,
+            fragmenter: lpv2::Fragmenter::new(),
+            reassembler: lpv2::Reassembler::new(),
+            congestion_marking: None,
+            dropped_interests: Vec::new(),
+            transport: Box::new(transport),
+            transport_closed: false,
         })
     }
 
@@ -178,22 +292,98 @@ impl Face {
         base_congestion_marking_interval: Option<face::BaseCongestionMarkingInterval>,
         default_congestion_threshold: Option<face::DefaultCongestionThreshold>,
     ) -> Self {
-        Self {
+        let mut face = Self {
             base_congestion_marking_interval,
             default_congestion_threshold,
             ..self
+        };
+        if face.congestion_marking.is_some() {
+            face.congestion_marking = Some(face.new_congestion_marking());
+        }
+        face
+    }
+
+    /// Cap the MTU told by the transport, packets larger than that are fragmented
+    pub fn update_mtu(self, mtu: Option<face::Mtu>) -> Self {
+        let mtu = match (self.mtu, mtu) {
+            (Some(transport), Some(configured)) => Some(transport.min(configured)),
+            (transport, configured) => transport.or(configured),
+        };
+        Self { mtu, ..self }
+    }
+
+    /// Override the scope told by the transport, e.g. for links emulated in-process
+    pub fn update_face_scope(self, face_scope: face::FaceScope) -> Self {
+        Self { face_scope, ..self }
+    }
+
+    /// Retransmission parameters used once LpReliability is enabled on this face
+    pub fn update_reliability_options(self, reliability_options: lpv2::ReliabilityOptions) -> Self {
+        Self {
+            reliability_options,
+            ..self
         }
     }
 
     #[tracing::instrument]
     pub async fn update_flags(
-        &self,
+        &mut self,
         flags_and_mask: Option<(face::Flags, face::Mask)>,
     ) -> io::Result<()> {
-        tracing::warn!("Not implemented yet");
+        if let Some((flags, mask)) = flags_and_mask {
+            let local_fields = face::Flags::LocalFieldsEnabled;
+            let enables_local_fields = *flags & *mask & *local_fields != 0;
+            if enables_local_fields && self.face_scope != face::FaceScope::Local {
+                return Err(io::Error::other(
+                    "Local fields can only be enabled on faces with local scope",
+                ));
+            }
+            self.flags = ((*self.flags & !*mask) | (*flags & *mask)).into();
+            self.mask = (*self.mask | *mask).into();
+        }
+
+        if !self.flags().lp_reliability_enabled() {
+            self.reliability = None;
+        } else if self.reliability.is_none() {
+            let mtu = self.mtu.map(face::Mtu::to_usize);
+            let reliability = lpv2::Reliability::new(self.reliability_options).with_mtu(mtu);
+            self.reliability = Some(reliability);
+        }
+
+        if !self.flags().congestion_marking_enabled() {
+            self.congestion_marking = None;
+        } else if self.congestion_marking.is_none() {
+            self.congestion_marking = Some(self.new_congestion_marking());
+        }
+
         Ok(())
     }
 
+    fn new_congestion_marking(&self) -> lpv2::CongestionMarking {
+        let defaults = lpv2::CongestionMarkingOptions::default();
+        let base_marking_interval = self
+            .base_congestion_marking_interval
+            .map_or(defaults.base_marking_interval, |interval| {
+                interval.to_std_duration()
+            });
+        let threshold = self
+            .default_congestion_threshold
+            .and_then(|threshold| usize::try_from(threshold.to_u64()).ok())
+            .unwrap_or(defaults.threshold);
+        lpv2::CongestionMarking::new(lpv2::CongestionMarkingOptions {
+            base_marking_interval,
+            threshold,
+        })
+    }
+
+    pub fn base_congestion_marking_interval(&self) -> Option<face::BaseCongestionMarkingInterval> {
+        self.base_congestion_marking_interval
+    }
+
+    pub fn default_congestion_threshold(&self) -> Option<face::DefaultCongestionThreshold> {
+        self.default_congestion_threshold
+    }
+
     pub fn face_id(&self) -> face::FaceId {
         self.face_id
     }
@@ -214,10 +404,24 @@ impl Face {
         self.persistency
     }
 
-    pub fn mtu(&self) -> face::Mtu {
+    /// `None` if unlimited
+    pub fn mtu(&self) -> Option<face::Mtu> {
         self.mtu
     }
 
+    pub fn face_scope(&self) -> face::FaceScope {
+        self.face_scope
+    }
+
+    pub fn link_type(&self) -> face::LinkType {
+        self.link_type
+    }
+
+    /// IncomingFaceId, NextHopFaceId and CachePolicy are exchanged on this face
+    pub fn local_fields_enabled(&self) -> bool {
+        self.face_scope == face::FaceScope::Local && self.flags().local_fields_enabled()
+    }
+
     pub fn flags(&self) -> face::Flags {
         (*self.flags & *face::Flags::all_fields() & *self.mask).into()
     }
@@ -225,15 +429,196 @@ impl Face {
     #[tracing::instrument(skip_all)]
     pub async fn send_item(&mut self, item: impl tlv::Tlv) -> io::Result<()> {
         tracing::trace!(r#type = %item.r#type(), "Outgoing item");
-        self.transport.send_item(item).await
+        let packet = lpv2::LpPacket::from_tlv(item);
+        self.send_packet(packet).await
     }
 
-    #[tracing::instrument(skip(self))]
+    /// Send network layer packet along with the link layer header fields.
+    /// Local fields are dropped unless enabled on this face.
+    #[tracing::instrument(skip_all)]
+    pub async fn send_packet(&mut self, packet: lpv2::LpPacket) -> io::Result<()> {
+        let packet = if self.local_fields_enabled() {
+            packet
+        } else {
+            packet.without_local_fields()
+        };
+        let packet = packet.with_congestion_mark(self.check_congestion());
+        for packet in self.fragment(packet)? {
+            let packet = if let Some(reliability) = self.reliability.as_mut() {
+                reliability.handle_outgoing(packet, now())
+            } else {
+                packet
+            };
+
+            if packet.has_header_fields() {
+                self.transport.send_item(packet).await?;
+            } else if let Some(item) = packet.network_packet() {
+                self.transport.send_item(item).await?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Split the packet unless it fits into the MTU, leaving room for the TxSequence
+    fn fragment(&mut self, packet: lpv2::LpPacket) -> io::Result<Vec<lpv2::LpPacket>> {
+        let Some(mtu) = self.mtu else {
+            return Ok(vec![packet]);
+        };
+        let overhead = if self.reliability.is_some() {
+            lpv2::Reliability::FRAME_OVERHEAD
+        } else {
+            0
+        };
+        let mtu = mtu.to_usize().saturating_sub(overhead);
+        self.fragmenter.fragment(packet, mtu)
+    }
+
+    /// Packets marked as congested on this face so far
+    pub fn n_congestion_marked(&self) -> u64 {
+        self.congestion_marking
+            .as_ref()
+            .map_or(0, lpv2::CongestionMarking::n_congestion_marked)
+    }
+
+    fn check_congestion(&mut self) -> Option<lpv2::CongestionMark> {
+        let marking = self.congestion_marking.as_mut()?;
+        let send_queue_length = self.transport.send_queue_length()?;
+        marking.check(send_queue_length, now())
+    }
+
+    /// Receive next network layer packet, dropping the link layer header fields
     pub async fn recv_item(&mut self) -> io::Result<Option<tlv::Generic>> {
-        self.transport.recv_item().await.inspect(|item| {
-            item.as_ref()
-                .inspect(|item| tracing::trace!(r#type = %item.r#type(), "Incoming item"));
-        })
+        let packet = self.recv_packet().await?;
+        Ok(packet.as_ref().and_then(lpv2::LpPacket::network_packet))
+    }
+
+    /// Receive next network layer packet along with its link layer header fields.
+    /// Bare network layer packets are returned wrapped into an LpPacket without header fields.
+    #[tracing::instrument(skip(self))]
+    pub async fn recv_packet(&mut self) -> io::Result<Option<lpv2::LpPacket>> {
+        let item = if let Some(deadline) = self.reliability_deadline() {
+            tokio::select! {
+                item = self.transport.recv_item() => Some(item),
+                () = time::sleep_until(deadline) => None,
+            }
+        } else {
+            Some(self.transport.recv_item().await)
+        };
+        self.handle_received(item).await
+    }
+
+    /// Time the link layer reliability has something to do next, if any
+    fn reliability_deadline(&self) -> Option<time::Instant> {
+        self.reliability
+            .as_ref()
+            .and_then(lpv2::Reliability::next_deadline)
+            .map(time::Instant::from_std)
+    }
+
+    /// Handle `item` received from the transport, `None` when the reliability deadline
+    /// has come first. A transport error other than a malformed packet closes the transport.
+    async fn handle_received(
+        &mut self,
+        item: Option<io::Result<Option<tlv::Generic>>>,
+    ) -> io::Result<Option<lpv2::LpPacket>> {
+        let item = match item {
+            Some(Ok(item)) => item,
+            Some(Err(error)) => {
+                if error.kind() != io::ErrorKind::InvalidData {
+                    self.transport_closed = true;
+                }
+                return Err(error);
+            }
+            None => {
+                self.poll_reliability().await?;
+                return Ok(None);
+            }
+        };
+
+        let packet = match item {
+            Some(item) if item.r#type == tlv::Type::LpPacket => self.recv_lp_packet(item).await?,
+            Some(item) => {
+                tracing::trace!(r#type = %item.r#type(), "Incoming item");
+                Some(lpv2::LpPacket::from_tlv(item))
+            }
+            None => {
+                self.transport_closed = true;
+                None
+            }
+        };
+
+        Ok(packet)
+    }
+
+    /// Re-establish the transport once it has closed. The link layer state carries over,
+    /// except for the fragments of the packets cut short.
+    pub async fn reconnect(&mut self) -> io::Result<()> {
+        self.transport.reconnect().await?;
+        self.reconnected()
+    }
+
+    /// Link layer bookkeeping once the transport has been re-established
+    fn reconnected(&mut self) -> io::Result<()> {
+        self.local_uri = self.transport.local_uri()?;
+        self.reassembler = lpv2::Reassembler::new();
+        self.transport_closed = false;
+        Ok(())
+    }
+
+    /// Interests given up on by the link layer since last call
+    pub fn take_dropped_interests(&mut self) -> Vec<Interest> {
+        std::mem::take(&mut self.dropped_interests)
+    }
+
+    async fn recv_lp_packet(&mut self, item: tlv::Generic) -> io::Result<Option<lpv2::LpPacket>> {
+        let packet = lpv2::LpPacket::decode_from_generic(item).map_err(io::Error::from)?;
+        tracing::trace!(%packet, "Incoming LpPacket");
+
+        // IncomingFaceId is set by the forwarder only, the rest is honored on local faces only
+        let packet = if self.local_fields_enabled() {
+            lpv2::LpPacket {
+                incoming_face_id: None,
+                ..packet
+            }
+        } else {
+            packet.without_local_fields()
+        };
+
+        if let Some(reliability) = self.reliability.as_mut() {
+            let events = reliability.process_incoming(&packet, now());
+            self.handle_reliability_events(events).await?;
+        }
+
+        if packet.is_idle() {
+            Ok(None)
+        } else {
+            self.reassembler.receive(packet, now())
+        }
+    }
+
+    async fn poll_reliability(&mut self) -> io::Result<()> {
+        if let Some(reliability) = self.reliability.as_mut() {
+            let events = reliability.poll(now());
+            self.handle_reliability_events(events).await?;
+        }
+        Ok(())
+    }
+
+    async fn handle_reliability_events(&mut self, events: Vec<lpv2::Event>) -> io::Result<()> {
+        for event in events {
+            match event {
+                lpv2::Event::Send(packet) => self.transport.send_item(packet).await?,
+                lpv2::Event::Lost(packet) => {
+                    let interest = packet
+                        .network_packet()
+                        .and_then(|generic| Interest::decode_from_generic(generic).ok());
+                    if let Some(interest) = interest {
+                        self.dropped_interests.push(interest);
+                    }
+                }
+            }
+        }
+        Ok(())
     }
 
     pub fn to_face_status(&self) -> face::FaceStatus {
@@ -241,10 +626,10 @@ impl Face {
         let uri = self.uri.clone();
         let local_uri = self.local_uri.clone();
         let expiration_period = None;
-        let face_scope = face::FaceScope::NonLocal;
+        let face_scope = self.face_scope;
         let face_persistency = self.persistency;
-        let mtu = Some(self.mtu);
-        let link_type = face::LinkType::PointToPoint;
+        let mtu = self.mtu;
+        let link_type = self.link_type;
 
         face::FaceStatus {
             face_id,
@@ -269,3 +654,317 @@ impl Face {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use bytes::BytesMut;
+    use tlv::TlvCodec;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use tokio::net::TcpListener;
+
+    use super::*;
+
+    #[tokio::test]
+    async fn faces_run_concurrently() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let uri = format!("tcp4://{}", listener.local_addr().unwrap());
+        let faces = Arc::new(FaceManegement::new());
+
+        let mut remotes = Vec::new();
+        for _ in 0..2 {
+            let params = mgmt::ControlParameters::create_face(uri.as_str());
+            assert!(faces.create(params).await.status_code.is_ok());
+            remotes.push(listener.accept().await.unwrap().0);
+        }
+        // Faces are listed in creation order, matching the accept order
+        let (idle, busy) = (faces.get_faces().await[0], faces.get_faces().await[1]);
+        let mut remote = remotes.pop().unwrap();
+
+        // Waiting for input on an idle face no longer blocks the others
+        let waiting = {
+            let faces = Arc::clone(&faces);
+            tokio::spawn(async move { faces.recv_item(idle).await })
+        };
+        tokio::task::yield_now().await;
+
+        let interest = Interest::new("/a");
+        let bytes = interest.bytes();
+        let exchange = async {
+            faces.send_item(busy, interest.clone()).await.unwrap();
+            let mut buf = vec![0; bytes.len()];
+            remote.read_exact(&mut buf).await.unwrap();
+            assert_eq!(buf, bytes);
+
+            remote.write_all(&bytes).await.unwrap();
+            let item = faces.recv_item(busy).await.unwrap();
+            Interest::decode(&mut BytesMut::from(item.bytes().as_ref())).unwrap()
+        };
+        let received = time::timeout(Duration::from_secs(5), exchange)
+            .await
+            .unwrap();
+        assert_eq!(received.name, interest.name);
+        assert!(!waiting.is_finished());
+
+        let status = faces.get_face(busy).await.unwrap().face_status().await;
+        assert_eq!(status.unwrap().face_id, busy);
+    }
+
+    #[tokio::test]
+    async fn created_face_echoes_congestion_settings() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let uri = format!("tcp4://{}", listener.local_addr().unwrap());
+        let faces = FaceManegement::new();
+        let interval = face::BaseCongestionMarkingInterval::from(50_000_000);
+        let threshold = face::DefaultCongestionThreshold::from(32_768);
+        let params = mgmt::ControlParameters {
+            base_congestion_marking_interval: Some(interval),
+            default_congestion_threshold: Some(threshold),
+            ..mgmt::ControlParameters::create_face(uri.as_str())
+        };
+        let response = faces.create(params).await;
+        assert!(response.status_code.is_ok());
+        let echoed = |bytes: bytes::Bytes| response.body.iter().any(|item| item.bytes() == bytes);
+        assert!(echoed(interval.bytes()));
+        assert!(echoed(threshold.bytes()));
+
+        let handle = faces.get_face(faces.get_faces().await[0]).await.unwrap();
+        assert_eq!(handle.base_congestion_marking_interval(), Some(interval));
+        assert_eq!(handle.default_congestion_threshold(), Some(threshold));
+    }
+
+    #[tokio::test]
+    async fn created_faces_are_served() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let uri = format!("tcp4://{}", listener.local_addr().unwrap());
+        let queue_capacity = 4;
+        let router = Arc::new(Router {
+            faces: FaceManegement::new().with_queue_capacity(queue_capacity),
+            ..Router::default()
+        });
+        let params = mgmt::ControlParameters::create_face(uri.as_str());
+        assert!(router.handle_create_face(params).await.status_code.is_ok());
+        let mut remote = transport::Tcp::from_stream(listener.accept().await.unwrap().0);
+
+        // Without a route every Interest comes back as a Nack, none is left in the queue
+        let n_interests = queue_capacity * 4;
+        for n in 0..n_interests {
+            let interest = Interest::new(format!("/a/{n}"));
+            remote.send_item(interest).await.unwrap();
+        }
+        let nacks = async {
+            for _ in 0..n_interests {
+                let item = remote.recv_item().await.unwrap().unwrap();
+                assert_eq!(item.r#type, tlv::Type::LpPacket);
+            }
+        };
+        time::timeout(Duration::from_secs(5), nacks).await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn mtu_fragments_packets() {
+        let ports = [0; 2].map(|_| {
+            let socket = std::net::UdpSocket::bind("127.0.0.1:0").unwrap();
+            socket.local_addr().unwrap().port()
+        });
+        let uri = |port: u16| format!("udp4://127.0.0.1:{port}");
+        let persistency = face::FacePersistency::Persistent;
+        let mtu = Some(face::Mtu::from(1000));
+        let local = Some(uri(ports[0]).into());
+        let mut a = Face::new(uri(ports[1]).into(), local, persistency, mtu)
+            .await
+            .unwrap();
+        let local = Some(uri(ports[1]).into());
+        let mut b = Face::new(uri(ports[0]).into(), local, persistency, None)
+            .await
+            .unwrap();
+
+        // The configured MTU caps the one of the transport
+        assert_eq!(a.to_face_status().mtu, mtu);
+        assert_eq!(b.mtu(), Some(face::Mtu::MAX_NDN_PACKET_SIZE));
+
+        let mut data = Data::new("/large");
+        data.content = Some(tlv::Content::new(vec![1; 4000]));
+        a.send_item(data.clone()).await.unwrap();
+        let item = loop {
+            if let Some(item) = b.recv_item().await.unwrap() {
+                break item;
+            }
+        };
+        let received = Data::decode_from_generic(item).unwrap();
+        assert_eq!(received.name(), data.name());
+        assert_eq!(received.content, data.content);
+    }
+
+    /// Forwards to all the nexthops, the one the Interest has come from included
+    #[derive(Debug)]
+    struct Flood;
+
+    impl Strategy for Flood {
+        fn name(&self) -> &'static str {
+            "/localhost/nfd/strategy/multicast"
+        }
+
+        fn after_receive_interest(
+            &self,
+            _interest: &Interest,
+            _downstream: face::FaceId,
+            fib_entry: &FibEntry,
+            _measurements: &mut MeasurementsTable,
+            _now: Instant,
+        ) -> Vec<face::FaceId> {
+            fib_entry
+                .nexthops
+                .iter()
+                .map(|nexthop| nexthop.face)
+                .collect()
+        }
+    }
+
+    #[tokio::test]
+    async fn multicast_faces() {
+        let group = "udp4://224.0.23.170:56364";
+        let local = Some("udp4://127.0.0.1:0".into());
+        let persistency = face::FacePersistency::Permanent;
+        let face = Face::new(group.into(), local, persistency, None)
+            .await
+            .unwrap();
+        assert_eq!(face.link_type(), face::LinkType::MultiAccess);
+
+        let prefix = "/a".parse().unwrap();
+        let strategy_choice = StrategyChoice::default().with_strategy(prefix, Flood);
+        let router = Arc::new(Router::new().with_strategy_choice(strategy_choice));
+        let face = router.attach_face(face).await;
+        let prefix = "/a".parse().unwrap();
+        router
+            .forwarding_information_base
+            .add_nexthop(prefix, face, 0)
+            .await;
+        let prefix = "/hello".parse().unwrap();
+        router
+            .register_producer(prefix, |interest: Interest| async move {
+                Some(Data::new(interest.name.to_string()))
+            })
+            .await
+            .unwrap();
+
+        let group = "224.0.23.170:56364".parse().unwrap();
+        let mut member =
+            transport::UdpMulticast::new(std::net::Ipv4Addr::LOCALHOST, group).unwrap();
+        // The route of "/a" leads back to the group, the Interest does not go there again
+        member.send_item(Interest::new("/a")).await.unwrap();
+        member.send_item(Interest::new("/hello/1")).await.unwrap();
+        let item = time::timeout(Duration::from_secs(5), member.recv_item())
+            .await
+            .unwrap()
+            .unwrap()
+            .unwrap();
+        let data = Data::decode_from_generic(item).unwrap();
+        assert_eq!(data.name.to_string(), "/hello/1");
+    }
+
+    /// Keep sending an Interest until `remote` gets it, those sent while the face is still
+    /// reconnecting are dropped
+    async fn send_until_received(
+        faces: &FaceManegement,
+        face: face::FaceId,
+        remote: &mut tokio::net::TcpStream,
+    ) {
+        let interest = Interest::new("/a");
+        let bytes = interest.bytes();
+        let mut buf = vec![0; bytes.len()];
+        let delivered = async {
+            loop {
+                faces.send_item(face, interest.clone()).await.unwrap();
+                let read = remote.read_exact(&mut buf);
+                if time::timeout(Duration::from_millis(100), read)
+                    .await
+                    .is_ok()
+                {
+                    break;
+                }
+            }
+        };
+        time::timeout(Duration::from_secs(5), delivered)
+            .await
+            .unwrap();
+        assert_eq!(buf, bytes);
+    }
+
+    #[tokio::test]
+    async fn permanent_faces_reconnect() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let uri = format!("tcp4://{}", listener.local_addr().unwrap());
+        let faces = FaceManegement::new();
+        let params = mgmt::ControlParameters {
+            face_persistency: Some(face::FacePersistency::Permanent),
+            ..mgmt::ControlParameters::create_face(uri.as_str())
+        };
+        assert!(faces.create(params).await.status_code.is_ok());
+        let face = faces.get_faces().await[0];
+
+        // The remote end goes away, the face comes back on its own
+        drop(listener.accept().await.unwrap());
+        let (mut remote, _) = time::timeout(Duration::from_secs(5), listener.accept())
+            .await
+            .unwrap()
+            .unwrap();
+        send_until_received(&faces, face, &mut remote).await;
+        assert!(faces.get_face(face).await.is_ok());
+    }
+
+    #[tokio::test]
+    async fn permanent_faces_redial_after_errors() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let faces = FaceManegement::new().with_queue_capacity(4);
+        let params = mgmt::ControlParameters {
+            face_persistency: Some(face::FacePersistency::Permanent),
+            ..mgmt::ControlParameters::create_face(format!("tcp4://{addr}").as_str())
+        };
+        assert!(faces.create(params).await.status_code.is_ok());
+        let face = faces.get_faces().await[0];
+
+        // The connection is reset while nobody listens for the next one
+        let (remote, _) = listener.accept().await.unwrap();
+        drop(listener);
+        remote.set_linger(Some(Duration::ZERO)).unwrap();
+        drop(remote);
+        // Leave it to the face to find out on its own
+        time::sleep(Duration::from_millis(100)).await;
+
+        // Reconnecting does not hold up the forwarder
+        let handle = faces.get_face(face).await.unwrap();
+        let serving = async {
+            for _ in 0..16 {
+                handle
+                    .send_packet(lpv2::LpPacket::from_tlv(Interest::new("/a")))
+                    .await
+                    .unwrap();
+            }
+            handle.face_status().await.unwrap()
+        };
+        let status = time::timeout(Duration::from_millis(500), serving)
+            .await
+            .unwrap();
+        assert_eq!(status.face_id, face);
+
+        let listener = TcpListener::bind(addr).await.unwrap();
+        let (mut remote, _) = time::timeout(Duration::from_secs(5), listener.accept())
+            .await
+            .unwrap()
+            .unwrap();
+        send_until_received(&faces, face, &mut remote).await;
+
+        // The reset has not reached the forwarder as an error, the next event is a packet
+        remote
+            .write_all(&Interest::new("/b").bytes())
+            .await
+            .unwrap();
+        let event = time::timeout(Duration::from_secs(5), handle.recv_event())
+            .await
+            .unwrap();
+        assert!(matches!(event, Ok(FaceEvent::Packet(_))));
+    }
+}
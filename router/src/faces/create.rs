@@ -39,23 +39,32 @@ pub(super) struct CreateResponse {
 
 impl FaceManegement {
     #[tracing::instrument]
-    pub(super) async fn create_impl(&self, create: CreateRequest) -> io::Result<CreateResponse> {
+    pub(super) async fn create_impl(
+        &self,
+        create: CreateRequest,
+    ) -> io::Result<(CreateResponse, Arc<FaceHandle>)> {
         tracing::debug!(?create);
         let persistency = create.face_persistency.unwrap_or_default();
-        let face = Face::new(create.uri, create.local_uri, persistency, create.mtu)
-            .await?
-            .update_congestion(
-                create.base_congestion_marking_interval,
-                create.default_congestion_threshold,
-            );
+        let mut face = Face::connect(
+            &self.transports,
+            create.uri,
+            create.local_uri,
+            Some(persistency),
+            create.mtu,
+        )
+        .await?
+        .update_congestion(
+            create.base_congestion_marking_interval,
+            create.default_congestion_threshold,
+        )
+        .update_reliability_options(self.reliability_options);
         tracing::info!(?face, "CREATED");
 
         face.update_flags(create.flags_and_mask).await?;
 
         let id = self.insert(face).await;
-        self.get_face(id)
-            .await
-            .map(|face| CreateResponse::from_face(&face))
+        let face = self.get_face(id).await?;
+        Ok((CreateResponse::from_face(&face), face))
     }
 }
 
@@ -109,21 +118,23 @@ impl TryFrom<mgmt::ControlParameters> for CreateRequest {
 }
 
 impl CreateResponse {
-    fn from_face(face: &Face) -> Self {
+    fn from_face(face: &FaceHandle) -> Self {
         let face_id = face.face_id();
         let uri = face.uri().clone();
         let local_uri = face.local_uri().clone();
         let face_persistency = face.persistency();
-        let mtu = Some(face.mtu());
-        let flags = face::Flags::from(0); // FIXME
+        let base_congestion_marking_interval = face.base_congestion_marking_interval();
+        let default_congestion_threshold = face.default_congestion_threshold();
+        let mtu = face.mtu();
+        let flags = face.flags();
 
         Self {
             face_id,
             uri,
             local_uri,
             face_persistency,
-            base_congestion_marking_interval: None,
-            default_congestion_threshold: None,
+            base_congestion_marking_interval,
+            default_congestion_threshold,
             mtu,
             flags,
         }
@@ -8,7 +8,7 @@ impl FaceManegement {
     ) -> io::Result<DestroyResponse> {
         tracing::debug!(?request);
         if let Some(face) = self.remove(request.face_id).await {
-            // Teardown face
+            face.close();
             tracing::info!(?face, "DESTROYED");
         } else {
             tracing::info!("Face not found; nothing to do");
@@ -0,0 +1,333 @@
+use std::time::Duration;
+
+use tokio::sync::mpsc;
+use tokio::sync::oneshot;
+use tokio::sync::Mutex;
+use tokio::task::JoinHandle;
+
+use super::*;
+
+/// Delay between the attempts to re-establish the transport of a permanent face,
+/// doubling after each failed attempt up to the maximum
+const INITIAL_REDIAL_DELAY: Duration = Duration::from_secs(1);
+const MAX_REDIAL_DELAY: Duration = Duration::from_secs(60);
+
+#[derive(Debug)]
+enum Command {
+    Send(Box<lpv2::LpPacket>),
+    Status(oneshot::Sender<face::FaceStatus>),
+}
+
+/// What a face delivers to the forwarder
+#[derive(Debug)]
+pub enum FaceEvent {
+    /// Network layer packet along with its link layer header fields
+    Packet(lpv2::LpPacket),
+    /// Interest the link layer has given up delivering
+    InterestDropped(Interest),
+}
+
+/// Cheap handle to a face running as its own task.
+/// The task owns the transport, the handle talks to it over bounded queues.
+#[derive(Debug)]
+pub struct FaceHandle {
+    face_id: face::FaceId,
+    uri: face::Uri,
+    local_uri: face::LocalUri,
+    persistency: face::FacePersistency,
+    mtu: Option<face::Mtu>,
+    face_scope: face::FaceScope,
+    link_type: face::LinkType,
+    flags: face::Flags,
+    base_congestion_marking_interval: Option<face::BaseCongestionMarkingInterval>,
+    default_congestion_threshold: Option<face::DefaultCongestionThreshold>,
+    commands: mpsc::Sender<Command>,
+    events: Mutex<mpsc::Receiver<io::Result<FaceEvent>>>,
+    interest_rate: Mutex<Option<TokenBucket>>,
+    task: JoinHandle<()>,
+}
+
+impl FaceHandle {
+    pub(super) fn spawn(
+        face: Face,
+        queue_capacity: usize,
+        interest_rate_limit: Option<InterestRateLimit>,
+    ) -> Self {
+        let (commands, command_rx) = mpsc::channel(queue_capacity);
+        let (event_tx, events) = mpsc::channel(queue_capacity);
+        let interest_rate = interest_rate_limit.map(|limit| TokenBucket::new(limit, now()));
+
+        let face_id = face.face_id();
+        let uri = face.uri().clone();
+        let local_uri = face.local_uri().clone();
+        let persistency = face.persistency();
+        let mtu = face.mtu();
+        let face_scope = face.face_scope();
+        let link_type = face.link_type();
+        let flags = face.flags();
+        let base_congestion_marking_interval = face.base_congestion_marking_interval();
+        let default_congestion_threshold = face.default_congestion_threshold();
+        let task = tokio::spawn(face.run(command_rx, event_tx));
+
+        Self {
+            face_id,
+            uri,
+            local_uri,
+            persistency,
+            mtu,
+            face_scope,
+            link_type,
+            flags,
+            base_congestion_marking_interval,
+            default_congestion_threshold,
+            commands,
+            events: Mutex::new(events),
+            interest_rate: Mutex::new(interest_rate),
+            task,
+        }
+    }
+
+    pub fn face_id(&self) -> face::FaceId {
+        self.face_id
+    }
+
+    pub fn uri(&self) -> &face::Uri {
+        &self.uri
+    }
+
+    pub fn local_uri(&self) -> &face::LocalUri {
+        &self.local_uri
+    }
+
+    pub fn persistency(&self) -> face::FacePersistency {
+        self.persistency
+    }
+
+    pub fn mtu(&self) -> Option<face::Mtu> {
+        self.mtu
+    }
+
+    pub fn face_scope(&self) -> face::FaceScope {
+        self.face_scope
+    }
+
+    pub fn link_type(&self) -> face::LinkType {
+        self.link_type
+    }
+
+    /// Whether a packet may go back out of the face it has arrived on, as on ad hoc links
+    /// where the other stations may not hear each other
+    pub fn may_reflect(&self) -> bool {
+        self.link_type == face::LinkType::AdHoc
+    }
+
+    pub fn flags(&self) -> face::Flags {
+        self.flags
+    }
+
+    pub fn base_congestion_marking_interval(&self) -> Option<face::BaseCongestionMarkingInterval> {
+        self.base_congestion_marking_interval
+    }
+
+    pub fn default_congestion_threshold(&self) -> Option<face::DefaultCongestionThreshold> {
+        self.default_congestion_threshold
+    }
+
+    /// Queue `packet` for sending, waiting for room when the send queue is full
+    pub async fn send_packet(&self, packet: lpv2::LpPacket) -> io::Result<()> {
+        self.commands
+            .send(Command::Send(Box::new(packet)))
+            .await
+            .map_err(|_| closed())
+    }
+
+    /// Number of packets waiting in the send queue
+    pub fn send_queue_length(&self) -> usize {
+        self.commands.max_capacity() - self.commands.capacity()
+    }
+
+    /// Next packet or link layer event of this face
+    pub async fn recv_event(&self) -> io::Result<FaceEvent> {
+        self.events
+            .lock()
+            .await
+            .recv()
+            .await
+            .unwrap_or_else(|| Err(closed()))
+    }
+
+    pub async fn face_status(&self) -> io::Result<face::FaceStatus> {
+        let (tx, rx) = oneshot::channel();
+        self.commands
+            .send(Command::Status(tx))
+            .await
+            .map_err(|_| closed())?;
+        rx.await.map_err(|_| closed())
+    }
+
+    /// Whether the next incoming Interest fits in the Interest rate limit
+    pub async fn admit_interest(&self) -> bool {
+        self.interest_rate
+            .lock()
+            .await
+            .as_mut()
+            .is_none_or(|bucket| bucket.try_consume(now()))
+    }
+
+    /// Stop the face task, dropping the transport
+    pub fn close(&self) {
+        self.task.abort();
+    }
+}
+
+impl Drop for FaceHandle {
+    fn drop(&mut self) {
+        self.close();
+    }
+}
+
+impl Face {
+    /// Serve the handle commands and deliver whatever arrives on the transport to `events`
+    /// until the handle or the transport goes away. Reading pauses while `events` is full.
+    ///
+    /// Only the single awaits on the command queue, the transport and the reliability timer
+    /// race each other, whatever they bring is handled to completion outside of the race.
+    async fn run(
+        mut self,
+        mut commands: mpsc::Receiver<Command>,
+        events: mpsc::Sender<io::Result<FaceEvent>>,
+    ) {
+        loop {
+            let deadline = self.reliability_deadline();
+            let item = tokio::select! {
+                command = commands.recv() => match command {
+                    Some(command) => {
+                        self.handle_command(command).await;
+                        if !self.transport_closed {
+                            continue;
+                        }
+                        // The transport went away while sending, as if it had ended
+                        Some(Ok(None))
+                    }
+                    None => break,
+                },
+                item = self.transport.recv_item() => Some(item),
+                () = time::sleep_until(deadline.unwrap_or_else(time::Instant::now)),
+                    if deadline.is_some() => None,
+            };
+
+            let received = self.handle_received(item).await;
+            if self.transport_closed && self.persistency() == face::FacePersistency::Permanent {
+                if let Err(error) = &received {
+                    tracing::debug!(face = %self.face_id(), %error, "Transport failed");
+                }
+                if self.redial(&mut commands).await {
+                    continue;
+                }
+            }
+            let received = match received {
+                Ok(Some(packet)) => Some(Ok(FaceEvent::Packet(packet))),
+                Ok(None) if self.transport_closed => Some(Err(io::ErrorKind::UnexpectedEof.into())),
+                Ok(None) => None,
+                Err(error) => Some(Err(error)),
+            };
+            let dropped = self
+                .take_dropped_interests()
+                .into_iter()
+                .map(|interest| Ok(FaceEvent::InterestDropped(interest)));
+            for event in dropped.chain(received) {
+                if events.send(event).await.is_err() {
+                    return;
+                }
+            }
+            if self.transport_closed {
+                tracing::debug!(face = %self.face_id(), "Transport closed");
+                break;
+            }
+        }
+    }
+
+    /// Keep re-establishing the transport with exponential backoff. The commands are served
+    /// meanwhile, connect attempts included, so that the forwarder never waits on the face.
+    /// False if the transport cannot reconnect or the handle has gone away.
+    async fn redial(&mut self, commands: &mut mpsc::Receiver<Command>) -> bool {
+        let face = self.face_id();
+        let mut delay = INITIAL_REDIAL_DELAY;
+        loop {
+            let status = self.to_face_status();
+            let mut attempt = self.transport.reconnect();
+            let reconnected = loop {
+                tokio::select! {
+                    result = &mut attempt => break result,
+                    command = commands.recv() => match command {
+                        Some(command) => serve_while_reconnecting(face, command, &status),
+                        None => return false,
+                    },
+                }
+            };
+            drop(attempt);
+            match reconnected.and_then(|()| self.reconnected()) {
+                Ok(()) => {
+                    tracing::info!(%face, "Transport reconnected");
+                    return true;
+                }
+                Err(error) if error.kind() == io::ErrorKind::Unsupported => return false,
+                Err(error) => tracing::debug!(%face, %error, ?delay, "Failed to reconnect"),
+            }
+
+            let sleep = time::sleep(delay);
+            tokio::pin!(sleep);
+            loop {
+                tokio::select! {
+                    () = &mut sleep => break,
+                    command = commands.recv() => match command {
+                        Some(command) => serve_while_reconnecting(face, command, &status),
+                        None => return false,
+                    },
+                }
+            }
+            delay = (delay * 2).min(MAX_REDIAL_DELAY);
+        }
+    }
+
+    async fn handle_command(&mut self, command: Command) {
+        match command {
+            Command::Send(packet) => {
+                if let Err(error) = self.send_packet(*packet).await {
+                    tracing::warn!(face = %self.face_id(), %error, "Failed to send packet");
+                    if is_disconnect(&error) {
+                        self.transport_closed = true;
+                    }
+                }
+            }
+            Command::Status(reply) => {
+                let _ = reply.send(self.to_face_status());
+            }
+        }
+    }
+}
+
+/// Packets are dropped while there is no transport, the status is the one from before
+fn serve_while_reconnecting(face: face::FaceId, command: Command, status: &face::FaceStatus) {
+    match command {
+        Command::Send(_) => tracing::debug!(%face, "Dropping packet while reconnecting"),
+        Command::Status(reply) => {
+            let _ = reply.send(status.clone());
+        }
+    }
+}
+
+/// Send failures meaning the other end is gone rather than the packet being refused
+fn is_disconnect(error: &io::Error) -> bool {
+    matches!(
+        error.kind(),
+        io::ErrorKind::BrokenPipe
+            | io::ErrorKind::ConnectionReset
+            | io::ErrorKind::ConnectionAborted
+            | io::ErrorKind::NotConnected
+    )
+}
+
+fn closed() -> io::Error {
+    io::Error::new(io::ErrorKind::BrokenPipe, "Face has been closed")
+}
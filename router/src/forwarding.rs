@@ -1,11 +1,142 @@
 use super::*;
 
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct NextHop {
+    pub face: face::FaceId,
+    pub cost: u64,
+}
+
+/// FIB entry matched by a lookup
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct FibEntry {
+    pub prefix: tlv::Name,
+    /// sorted by cost
+    pub nexthops: Vec<NextHop>,
+}
+
 #[derive(Debug, Default)]
-pub struct ForwardingInformationBase;
+pub struct ForwardingInformationBase {
+    fib: RwLock<HashMap<tlv::Name, Vec<NextHop>>>,
+}
 
 impl ForwardingInformationBase {
-    pub async fn lookup(&self, interest: &Interest) -> face::FaceId {
-        println!("Looking up forward face for {:?}", interest);
-        todo!()
+    /// Add or update the nexthop of `prefix`, nexthops are kept sorted by cost
+    pub async fn add_nexthop(&self, prefix: tlv::Name, face: face::FaceId, cost: u64) {
+        let mut fib = self.fib.write().await;
+        let nexthops = fib.entry(prefix).or_default();
+        nexthops.retain(|nexthop| nexthop.face != face);
+        nexthops.push(NextHop { face, cost });
+        nexthops.sort_by_key(|nexthop| nexthop.cost);
+    }
+
+    pub async fn remove_nexthop(&self, prefix: &tlv::Name, face: face::FaceId) {
+        let mut fib = self.fib.write().await;
+        if let Some(nexthops) = fib.get_mut(prefix) {
+            nexthops.retain(|nexthop| nexthop.face != face);
+            if nexthops.is_empty() {
+                fib.remove(prefix);
+            }
+        }
+    }
+
+    /// The longest FIB entry prefix of `name`
+    pub async fn longest_prefix_match(&self, name: &tlv::Name) -> Option<FibEntry> {
+        let fib = self.fib.read().await;
+        (0..=name.len()).rev().find_map(|len| {
+            let prefix = name.prefix(len);
+            let nexthops = fib.get(&prefix)?.clone();
+            Some(FibEntry { prefix, nexthops })
+        })
+    }
+
+    /// FIB entry used to forward `interest`.
+    ///
+    /// Interest carrying a ForwardingHint (i.e. still outside the producer region) is forwarded
+    /// towards the first delegation name having nexthops, falling back to the Interest name.
+    pub async fn lookup(&self, interest: &Interest) -> Option<FibEntry> {
+        let delegations = interest
+            .forwarding_hint
+            .as_ref()
+            .map_or(&[][..], tlv::ForwardingHint::names);
+        for delegation in delegations {
+            let entry = self.longest_prefix_match(delegation).await;
+            if entry.is_some() {
+                return entry;
+            }
+        }
+
+        self.longest_prefix_match(&interest.name).await
+    }
+}
+
+/// Names of the producer regions this router belongs to
+#[derive(Debug, Default)]
+pub struct NetworkRegionTable {
+    regions: RwLock<Vec<tlv::Name>>,
+}
+
+impl NetworkRegionTable {
+    pub async fn insert(&self, region: tlv::Name) {
+        let mut regions = self.regions.write().await;
+        if !regions.contains(&region) {
+            regions.push(region);
+        }
+    }
+
+    /// Interest has reached the producer region once any delegation in its ForwardingHint
+    /// is a prefix of one of our region names
+    pub async fn is_in_producer_region(&self, forwarding_hint: &tlv::ForwardingHint) -> bool {
+        let regions = self.regions.read().await;
+        forwarding_hint
+            .names()
+            .iter()
+            .any(|delegation| regions.iter().any(|region| delegation.is_prefix_of(region)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn name(text: &str) -> tlv::Name {
+        text.parse().unwrap()
+    }
+
+    #[tokio::test]
+    async fn longest_prefix_match() {
+        let fib = ForwardingInformationBase::default();
+        fib.add_nexthop(name("/a"), face::FaceId::from(1), 10).await;
+        fib.add_nexthop(name("/a/b"), face::FaceId::from(2), 10)
+            .await;
+        fib.add_nexthop(name("/a/b"), face::FaceId::from(3), 5)
+            .await;
+
+        let entry = fib.longest_prefix_match(&name("/a/b/c")).await.unwrap();
+        assert_eq!(entry.prefix, name("/a/b"));
+        let faces = entry.nexthops.iter().map(|nexthop| nexthop.face.to_u64());
+        assert_eq!(faces.collect::<Vec<_>>(), [3, 2]);
+
+        let entry = fib.longest_prefix_match(&name("/a/x")).await.unwrap();
+        assert_eq!(entry.nexthops[0].face, face::FaceId::from(1));
+        assert!(fib.longest_prefix_match(&name("/x")).await.is_none());
+    }
+
+    #[tokio::test]
+    async fn forwarding_hint() {
+        let fib = ForwardingInformationBase::default();
+        fib.add_nexthop(name("/ucla"), face::FaceId::from(1), 0)
+            .await;
+        fib.add_nexthop(name("/telia"), face::FaceId::from(2), 0)
+            .await;
+
+        let hint = tlv::ForwardingHint::new([name("/att"), name("/telia/terabits")]);
+        let interest = Interest::new("/ucla/videos").with_forwarding_hint(hint.clone());
+        let entry = fib.lookup(&interest).await.unwrap();
+        assert_eq!(entry.nexthops[0].face, face::FaceId::from(2));
+
+        let regions = NetworkRegionTable::default();
+        assert!(!regions.is_in_producer_region(&hint).await);
+        regions.insert(name("/telia/terabits/router1")).await;
+        assert!(regions.is_in_producer_region(&hint).await);
     }
 }
@@ -0,0 +1,288 @@
+//! User code plugged into the forwarding pipeline.
+//!
+//! Hooks see the packets at the stages of [`InterestStage`] and [`DataStage`], along with the
+//! face the packet is coming from or going to. Each hook may change the packet in place,
+//! drop it, or answer an Interest with Data right away. Global hooks run first, then the hooks
+//! registered for the face, until one of them does something other than continue.
+
+use std::fmt;
+
+use super::*;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum InterestStage {
+    /// Interest has just been received from the face
+    Incoming,
+    /// Interest received from the face is about to be looked up in the Content Store
+    BeforeCsLookup,
+    /// Interest is about to be sent to the face
+    Outgoing,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DataStage {
+    /// Data has just been received from the face
+    Incoming,
+    /// Data is about to be sent to the face
+    Outgoing,
+}
+
+#[derive(Debug)]
+pub enum InterestAction {
+    /// Carry on with the Interest, possibly modified by the hook
+    Continue,
+    /// Stop processing the Interest
+    Drop,
+    /// Satisfy the Interest with the Data, as if it had come from the face on the way out
+    Answer(Box<Data>),
+}
+
+#[derive(Debug)]
+pub enum DataAction {
+    /// Carry on with the Data, possibly modified by the hook
+    Continue,
+    /// Stop processing the Data
+    Drop,
+}
+
+/// Pipeline hook, e.g. for logging, name rewriting or access control
+pub trait Hook: fmt::Debug + Send + Sync {
+    fn on_interest(
+        &self,
+        stage: InterestStage,
+        interest: &mut Interest,
+        face: &FaceHandle,
+    ) -> InterestAction {
+        let _ = (stage, interest, face);
+        InterestAction::Continue
+    }
+
+    fn on_data(&self, stage: DataStage, data: &mut Data, face: &FaceHandle) -> DataAction {
+        let _ = (stage, data, face);
+        DataAction::Continue
+    }
+}
+
+#[derive(Debug, Default)]
+struct Registered {
+    global: Vec<Arc<dyn Hook>>,
+    faces: HashMap<face::FaceId, Vec<Arc<dyn Hook>>>,
+}
+
+/// Hooks registered globally and per face
+#[derive(Debug, Default)]
+pub struct Hooks {
+    registered: RwLock<Registered>,
+}
+
+impl Hooks {
+    /// Run `hook` for the packets of `face`, or of all the faces if `None`
+    pub async fn add(&self, face: Option<face::FaceId>, hook: impl Hook + 'static) {
+        let mut registered = self.registered.write().await;
+        let hook = Arc::new(hook);
+        if let Some(face) = face {
+            registered.faces.entry(face).or_default().push(hook);
+        } else {
+            registered.global.push(hook);
+        }
+    }
+
+    /// Drop the hooks registered for `face`
+    pub async fn remove_face(&self, face: face::FaceId) {
+        self.registered.write().await.faces.remove(&face);
+    }
+
+    /// Run the hooks of `face` in order under the read lock, until one returns an action
+    async fn run<A>(
+        &self,
+        face: face::FaceId,
+        mut run: impl FnMut(&dyn Hook) -> Option<A>,
+    ) -> Option<A> {
+        let registered = self.registered.read().await;
+        if registered.global.is_empty() && registered.faces.is_empty() {
+            return None;
+        }
+        let per_face = registered.faces.get(&face).into_iter().flatten();
+        registered
+            .global
+            .iter()
+            .chain(per_face)
+            .find_map(|hook| run(hook.as_ref()))
+    }
+
+    pub(crate) async fn on_interest(
+        &self,
+        stage: InterestStage,
+        interest: &mut Interest,
+        face: &FaceHandle,
+    ) -> InterestAction {
+        self.run(face.face_id(), |hook| {
+            match hook.on_interest(stage, interest, face) {
+                InterestAction::Continue => None,
+                action => {
+                    tracing::trace!(?stage, %interest, ?hook, ?action, "Hook");
+                    Some(action)
+                }
+            }
+        })
+        .await
+        .unwrap_or(InterestAction::Continue)
+    }
+
+    pub(crate) async fn on_data(
+        &self,
+        stage: DataStage,
+        data: &mut Data,
+        face: &FaceHandle,
+    ) -> DataAction {
+        self.run(face.face_id(), |hook| {
+            match hook.on_data(stage, data, face) {
+                DataAction::Continue => None,
+                action => {
+                    tracing::trace!(?stage, name = %data.name, ?hook, ?action, "Hook");
+                    Some(action)
+                }
+            }
+        })
+        .await
+        .unwrap_or(DataAction::Continue)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::AtomicUsize;
+    use std::time::Duration;
+
+    use super::*;
+
+    /// Answers `/hooked`, drops `/blocked` and rewrites `/old` into `/hello`
+    #[derive(Debug)]
+    struct Gateway;
+
+    impl Hook for Gateway {
+        fn on_interest(
+            &self,
+            stage: InterestStage,
+            interest: &mut Interest,
+            _face: &FaceHandle,
+        ) -> InterestAction {
+            if stage != InterestStage::Incoming {
+                return InterestAction::Continue;
+            }
+            let name = interest.name.to_string();
+            if name.starts_with("/hooked") {
+                InterestAction::Answer(Box::new(Data::new(&name)))
+            } else if name.starts_with("/blocked") {
+                InterestAction::Drop
+            } else if let Some(rest) = name.strip_prefix("/old") {
+                interest.name = format!("/hello{rest}").parse().unwrap();
+                InterestAction::Continue
+            } else {
+                InterestAction::Continue
+            }
+        }
+    }
+
+    /// Counts the Interests sent to and the Data received from the face
+    #[derive(Debug, Default)]
+    struct Counter {
+        n_interests: Arc<AtomicUsize>,
+        n_data: Arc<AtomicUsize>,
+    }
+
+    impl Hook for Counter {
+        fn on_interest(
+            &self,
+            stage: InterestStage,
+            _interest: &mut Interest,
+            _face: &FaceHandle,
+        ) -> InterestAction {
+            if stage == InterestStage::Outgoing {
+                self.n_interests.fetch_add(1, Ordering::Relaxed);
+            }
+            InterestAction::Continue
+        }
+
+        fn on_data(&self, stage: DataStage, _data: &mut Data, _face: &FaceHandle) -> DataAction {
+            if stage == DataStage::Incoming {
+                self.n_data.fetch_add(1, Ordering::Relaxed);
+            }
+            DataAction::Continue
+        }
+    }
+
+    /// Answers the Interests on their way out
+    #[derive(Debug)]
+    struct Responder;
+
+    impl Hook for Responder {
+        fn on_interest(
+            &self,
+            stage: InterestStage,
+            interest: &mut Interest,
+            _face: &FaceHandle,
+        ) -> InterestAction {
+            if stage == InterestStage::Outgoing {
+                InterestAction::Answer(Box::new(Data::new(interest.name.to_string())))
+            } else {
+                InterestAction::Continue
+            }
+        }
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn hooks() {
+        let router = Arc::new(Router::new());
+        let producer = router
+            .register_producer("/hello".parse().unwrap(), |interest: Interest| async move {
+                Some(Data::new(interest.name.to_string()))
+            })
+            .await
+            .unwrap();
+
+        let counter = Counter::default();
+        let n_interests = Arc::clone(&counter.n_interests);
+        let n_data = Arc::clone(&counter.n_data);
+        router.hooks().add(None, Gateway).await;
+        router.hooks().add(Some(producer), counter).await;
+
+        let express = |name: &str| {
+            let interest = Interest::new(name).with_interest_lifetime(100);
+            router.express_interest(interest)
+        };
+
+        let data = express("/hooked/1").await.unwrap();
+        assert_eq!(data.name.to_string(), "/hooked/1");
+
+        let error = express("/blocked/1").await.unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::TimedOut);
+
+        let data = express("/old/1").await.unwrap();
+        assert_eq!(data.name.to_string(), "/hello/1");
+
+        // Only the rewritten Interest has made it to the producer
+        time::sleep(Duration::from_millis(10)).await;
+        assert_eq!(n_interests.load(Ordering::Relaxed), 1);
+        assert_eq!(n_data.load(Ordering::Relaxed), 1);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn answer_on_the_way_out() {
+        let router = Arc::new(Router::new());
+        let producer = router
+            .register_producer("/hello".parse().unwrap(), |_| async { None })
+            .await
+            .unwrap();
+        router.hooks().add(Some(producer), Responder).await;
+
+        let interest = Interest::new("/hello/1").with_interest_lifetime(100);
+        let data = router.express_interest(interest).await.unwrap();
+        assert_eq!(data.name.to_string(), "/hello/1");
+        assert!(router.shards()[0].pending_interest_table().is_empty().await);
+
+        // Face hooks go away along with the face
+        router.destroy_face(producer).await.into_result().unwrap();
+        assert!(router.hooks().registered.read().await.faces.is_empty());
+    }
+}
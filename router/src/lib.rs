@@ -1,12 +1,15 @@
-use std::collections::{HashMap, HashSet};
+use std::collections::HashMap;
 use std::io;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::{Instant, SystemTime};
 
 use tokio::sync::RwLock;
-use tokio::sync::RwLockMappedWriteGuard;
 use tokio::sync::RwLockReadGuard;
-use tokio::sync::RwLockWriteGuard;
+use tokio::time;
 
 use ndn_face as face;
+use ndn_lpv2 as lpv2;
 use ndn_management as mgmt;
 use ndn_tlv as tlv;
 use ndn_transport as transport;
@@ -14,27 +17,122 @@ use ndn_transport as transport;
 use tlv::Data;
 use tlv::Interest;
 use tlv::Tlv;
+use transport::TransportExt;
 // use tlv::TlvCodec;
 
+pub use app::Producer;
 pub use content::ContentStore;
 pub use error::Error;
 pub use faces::Face;
+pub use faces::FaceEvent;
+pub use faces::FaceHandle;
 pub use faces::FaceManegement;
+pub use forwarding::FibEntry;
 pub use forwarding::ForwardingInformationBase;
+pub use forwarding::NetworkRegionTable;
+pub use forwarding::NextHop;
+pub use hooks::DataAction;
+pub use hooks::DataStage;
+pub use hooks::Hook;
+pub use hooks::Hooks;
+pub use hooks::InterestAction;
+pub use hooks::InterestStage;
+pub use limits::InterestRateLimit;
+pub use limits::LimitCounters;
+pub use limits::LimitExceeded;
+pub use limits::ResourceLimits;
+pub use limits::TokenBucket;
+pub use measurements::FaceMeasurements;
+pub use measurements::MeasurementsEntry;
+pub use measurements::MeasurementsTable;
+pub use pending::Arrival;
 pub use pending::PendingInterestTable;
+pub use pending::PitEntry;
+pub use rib::AnnouncementValidator;
+pub use rib::Route;
+pub use rib::RoutingInformationBase;
+pub use rib::TrustAllAnnouncements;
+pub use shard::Dispatcher;
+pub use shard::Shard;
+pub use strategy::Asf;
+pub use strategy::AsfOptions;
+pub use strategy::BestRoute;
+pub use strategy::RetxDecision;
+pub use strategy::RetxSuppression;
+pub use strategy::SelfLearning;
+pub use strategy::Strategy;
+pub use strategy::StrategyChoice;
+pub use unsolicited::UnsolicitedDataPolicy;
 
+mod app;
+mod channels;
 mod content;
 mod error;
 mod faces;
 mod forwarding;
+mod hooks;
+mod limits;
+mod measurements;
 mod pending;
+mod rib;
+mod shard;
+pub mod sim;
+mod strategy;
+mod unsolicited;
 
-#[derive(Debug, Default)]
+use shard::NetworkPacket;
+
+#[derive(Debug)]
 pub struct Router {
     faces: FaceManegement,
-    pending_interest_table: PendingInterestTable,
+    /// PIT and Content Store split by name prefix
+    shards: Vec<Shard>,
+    /// router-wide, a FIB prefix may span several shards
+    measurements: RwLock<MeasurementsTable>,
+    /// name components hashed to pick the shard
+    shard_prefix_len: usize,
+    resource_limits: ResourceLimits,
+    routing_information_base: RoutingInformationBase,
     forwarding_information_base: ForwardingInformationBase,
-    content_store: ContentStore,
+    network_region_table: NetworkRegionTable,
+    strategy_choice: StrategyChoice,
+    /// announcements received along the Data are ignored unless there is one
+    announcement_validator: Option<Box<dyn AnnouncementValidator>>,
+    hooks: Hooks,
+    unsolicited_data_policy: UnsolicitedDataPolicy,
+    n_unsolicited_data: AtomicU64,
+    n_unsolicited_data_admitted: AtomicU64,
+    limit_counters: LimitCounters,
+    /// face of the in-process consumers, attached on first use
+    consumer: tokio::sync::OnceCell<app::Consumer>,
+}
+
+fn now() -> Instant {
+    time::Instant::now().into_std()
+}
+
+impl Default for Router {
+    fn default() -> Self {
+        let resource_limits = ResourceLimits::default();
+        Self {
+            faces: FaceManegement::default(),
+            shards: Shard::build(1, &resource_limits),
+            measurements: RwLock::default(),
+            shard_prefix_len: Shard::DEFAULT_PREFIX_LEN,
+            resource_limits,
+            routing_information_base: RoutingInformationBase::default(),
+            forwarding_information_base: ForwardingInformationBase::default(),
+            network_region_table: NetworkRegionTable::default(),
+            strategy_choice: StrategyChoice::default(),
+            announcement_validator: None,
+            hooks: Hooks::default(),
+            unsolicited_data_policy: UnsolicitedDataPolicy::default(),
+            n_unsolicited_data: AtomicU64::default(),
+            n_unsolicited_data_admitted: AtomicU64::default(),
+            limit_counters: LimitCounters::default(),
+            consumer: tokio::sync::OnceCell::new(),
+        }
+    }
 }
 
 impl Router {
@@ -49,23 +147,342 @@ impl Router {
         Ok(router)
     }
 
+    /// Loopback face the caller injects packets through, it is not served by the router
     pub async fn add_internal_face(&self) -> mgmt::ControlResponse {
         let params = mgmt::ControlParameters::create_face("internal://");
-        self.handle_create_face(params).await
+        self.faces.create(params).await
     }
 
+    /// Create a face and serve it by its own task, the way [`Self::attach_face`] does
     pub async fn handle_create_face(
+        self: &Arc<Self>,
+        params: mgmt::ControlParameters,
+    ) -> mgmt::ControlResponse {
+        let (response, face) = self.faces.create_face(params).await;
+        if let Some(face) = face {
+            self.spawn_serve_face(face.face_id(), face.persistency());
+        }
+        response
+    }
+
+    pub async fn handle_destroy_face(
         &self,
         params: mgmt::ControlParameters,
     ) -> mgmt::ControlResponse {
-        self.faces.create(params).await
+        let face = params.face_id;
+        let response = self.faces.destroy(params).await;
+        if let Some(face) = face {
+            self.hooks.remove_face(face).await;
+        }
+        response
+    }
+
+    /// Close `face` and drop the hooks registered for it
+    pub async fn destroy_face(&self, face: face::FaceId) -> mgmt::ControlResponse {
+        let params = mgmt::ControlParameters::destroy_face(face);
+        self.handle_destroy_face(params).await
+    }
+
+    pub fn with_strategy_choice(self, strategy_choice: StrategyChoice) -> Self {
+        Self {
+            strategy_choice,
+            ..self
+        }
+    }
+
+    /// Install the routes of the PrefixAnnouncements received along the Data, where the
+    /// strategy learns them, once trusted by `validator`
+    pub fn with_announcement_validator(
+        self,
+        validator: impl AnnouncementValidator + 'static,
+    ) -> Self {
+        Self {
+            announcement_validator: Some(Box::new(validator)),
+            ..self
+        }
+    }
+
+    /// Transports by URI scheme for the faces created by management commands
+    pub fn with_transport_registry(self, transports: transport::Registry) -> Self {
+        Self {
+            faces: self.faces.with_transport_registry(transports),
+            ..self
+        }
+    }
+
+    /// PIT and Content Store limits are split evenly between the shards
+    pub fn with_resource_limits(self, resource_limits: ResourceLimits) -> Self {
+        Self {
+            faces: self
+                .faces
+                .with_interest_rate_limit(resource_limits.interest_rate),
+            shards: Shard::build(self.shards.len(), &resource_limits),
+            resource_limits,
+            ..self
+        }
+    }
+
+    /// Split the forwarding tables into `n_shards` by a hash of the first `prefix_len`
+    /// name components, up to [`Shard::MAX_SHARDS`]. The tables start out empty.
+    pub fn with_shards(self, n_shards: usize, prefix_len: usize) -> Self {
+        Self {
+            shards: Shard::build(n_shards, &self.resource_limits),
+            shard_prefix_len: prefix_len,
+            ..self
+        }
+    }
+
+    /// Pipeline hooks, registered globally or per face
+    pub fn hooks(&self) -> &Hooks {
+        &self.hooks
+    }
+
+    pub fn shards(&self) -> &[Shard] {
+        &self.shards
+    }
+
+    pub fn n_shards(&self) -> usize {
+        self.shards.len()
+    }
+
+    /// Interests dropped for exceeding the resource limits
+    pub fn limit_counters(&self) -> &LimitCounters {
+        &self.limit_counters
+    }
+
+    pub fn with_unsolicited_data_policy(
+        self,
+        unsolicited_data_policy: UnsolicitedDataPolicy,
+    ) -> Self {
+        Self {
+            unsolicited_data_policy,
+            ..self
+        }
+    }
+
+    pub fn unsolicited_data_policy(&self) -> UnsolicitedDataPolicy {
+        self.unsolicited_data_policy
+    }
+
+    /// Number of Data packets received that matched no PIT entry
+    pub fn n_unsolicited_data(&self) -> u64 {
+        self.n_unsolicited_data.load(Ordering::Relaxed)
+    }
+
+    /// Number of unsolicited Data packets admitted into the Content Store by the policy
+    pub fn n_unsolicited_data_admitted(&self) -> u64 {
+        self.n_unsolicited_data_admitted.load(Ordering::Relaxed)
+    }
+
+    pub fn forwarding_information_base(&self) -> &ForwardingInformationBase {
+        &self.forwarding_information_base
+    }
+
+    pub fn routing_information_base(&self) -> &RoutingInformationBase {
+        &self.routing_information_base
+    }
+
+    /// Add or replace the route of the same face and origin, updating the FIB
+    pub async fn add_route(&self, prefix: tlv::Name, route: Route) {
+        self.routing_information_base
+            .add_route(prefix, route, &self.forwarding_information_base)
+            .await;
+    }
+
+    pub async fn remove_route(&self, prefix: &tlv::Name, face: face::FaceId, origin: mgmt::Origin) {
+        self.routing_information_base
+            .remove_route(prefix, face, origin, &self.forwarding_information_base)
+            .await;
+    }
+
+    /// Install the route of `announcement` towards `face`, unless it is out of its
+    /// ValidityPeriod. Data coming from `face` under the prefix carries the announcement
+    /// downstream when the strategy asks for it.
+    pub async fn announce_prefix(
+        &self,
+        announcement: tlv::PrefixAnnouncement,
+        face: face::FaceId,
+    ) -> io::Result<()> {
+        let time = SystemTime::now();
+        if !announcement.is_valid_at(time) {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("{announcement} is out of its validity period"),
+            ));
+        }
+
+        let prefix = announcement.prefix().clone();
+        let lifetime = announcement.route_lifetime(time);
+        let route = Route::new(face, mgmt::Origin::PrefixAnn, 0)
+            .with_lifetime(lifetime, now())
+            .with_announcement(announcement);
+        tracing::debug!(%prefix, %face, ?lifetime, "Installing announced route");
+        self.add_route(prefix, route).await;
+        Ok(())
+    }
+
+    pub fn network_region_table(&self) -> &NetworkRegionTable {
+        &self.network_region_table
     }
 
     pub async fn handle_face_status(&self, face: face::FaceId) -> io::Result<face::FaceStatus> {
-        self.faces
-            .get_face(face)
-            .await
-            .map(|face| face.to_face_status())
+        self.faces.get_face(face).await?.face_status().await
+    }
+
+    /// Receive next network layer packet from `face`, handling link layer events on the way
+    pub async fn recv_item(&self, face: face::FaceId) -> io::Result<tlv::Generic> {
+        loop {
+            if let Some(item) = self.recv_packet(face).await?.network_packet() {
+                break Ok(item);
+            }
+        }
+    }
+
+    /// Receive next packet from `face` along with its link layer header fields.
+    /// Malformed packets are dropped, the face keeps going.
+    pub async fn recv_packet(&self, face: face::FaceId) -> io::Result<lpv2::LpPacket> {
+        let handle = self.faces.get_face(face).await?;
+        loop {
+            let event = match handle.recv_event().await {
+                Ok(event) => event,
+                Err(error) if error.kind() == io::ErrorKind::InvalidData => {
+                    tracing::debug!(%face, %error, "Dropping malformed packet");
+                    continue;
+                }
+                Err(error) => break Err(error),
+            };
+            match event {
+                FaceEvent::Packet(packet) => break Ok(packet),
+                FaceEvent::InterestDropped(interest) => self
+                    .strategy_choice
+                    .find_effective_strategy(&interest.name)
+                    .on_dropped_interest(&interest, face),
+            }
+        }
+    }
+
+    /// Add `face` to the face table and serve it by its own task until it is closed
+    pub async fn attach_face(self: &Arc<Self>, face: Face) -> face::FaceId {
+        let persistency = face.persistency();
+        let face = self.faces.insert(face).await;
+        self.spawn_serve_face(face, persistency);
+        face
+    }
+
+    fn spawn_serve_face(self: &Arc<Self>, face: face::FaceId, persistency: face::FacePersistency) {
+        let router = Arc::clone(self);
+        tokio::spawn(async move {
+            if let Err(error) = router.serve_face(face).await {
+                tracing::debug!(%face, %error, "Face closed");
+            }
+            // Nothing brings an on-demand face back once its transport is gone
+            if persistency == face::FacePersistency::OnDemand {
+                router.destroy_face(face).await;
+            }
+        });
+    }
+
+    /// Receive the packets of `face` and handle them in place until the face is closed
+    pub async fn serve_face(&self, face: face::FaceId) -> io::Result<()> {
+        loop {
+            let packet = self.recv_packet(face).await?;
+            if let Err(error) = self.handle_packet(packet, face).await {
+                tracing::debug!(%face, %error, "Dropping packet");
+            }
+        }
+    }
+
+    /// Handle network layer packet received from `face` in place,
+    /// see [`Dispatcher`] for handing it over to the shard worker instead
+    pub async fn handle_packet(
+        &self,
+        packet: lpv2::LpPacket,
+        face: face::FaceId,
+    ) -> io::Result<()> {
+        let Some(decoded) = NetworkPacket::decode(&packet)? else {
+            return Ok(());
+        };
+        let shard = self.shard_for(&decoded, &packet);
+        self.forward(shard, decoded, face, &packet).await
+    }
+
+    /// Shard owning the namespace of `packet`.
+    /// Data and Nacks go back to the shard named by the PitToken, when there is one.
+    pub(crate) fn shard_for(&self, packet: &NetworkPacket, header: &lpv2::LpPacket) -> usize {
+        let tagged = match packet {
+            NetworkPacket::Data(_) | NetworkPacket::Nack(..) => {
+                header.pit_token.as_ref().and_then(shard::tagged_shard)
+            }
+            NetworkPacket::Interest(_) => None,
+        };
+        tagged
+            .filter(|shard| *shard < self.shards.len())
+            .unwrap_or_else(|| self.shard_of(packet.name()))
+    }
+
+    fn shard_of(&self, name: &tlv::Name) -> usize {
+        shard::shard_of(name, self.shard_prefix_len, self.shards.len())
+    }
+
+    /// Shards where the Interests `name` may satisfy are pending: the owner of `name`
+    /// and the owners of its prefixes shorter than the sharding prefix
+    fn prefix_shards(&self, name: &tlv::Name) -> Vec<usize> {
+        let mut shards = (0..=self.shard_prefix_len.min(name.len()))
+            .map(|len| self.shard_of(&name.prefix(len)))
+            .collect::<Vec<_>>();
+        shards.sort_unstable();
+        shards.dedup();
+        shards
+    }
+
+    fn shard(&self, shard: usize) -> io::Result<&Shard> {
+        self.shards
+            .get(shard)
+            .ok_or_else(|| io::Error::other("Shard not found"))
+    }
+
+    /// Run `packet` received from `face` through the forwarding pipeline of `shard`
+    pub(crate) async fn forward(
+        &self,
+        shard: usize,
+        packet: NetworkPacket,
+        face: face::FaceId,
+        header: &lpv2::LpPacket,
+    ) -> io::Result<()> {
+        let shard = self.shard(shard)?;
+        match packet {
+            NetworkPacket::Interest(interest) => {
+                self.process_interest(shard, interest, face, header).await
+            }
+            NetworkPacket::Nack(interest, nack) => {
+                self.process_nack(shard, interest, nack, face, header).await
+            }
+            NetworkPacket::Data(data) => self.process_data(shard, data, face, header).await,
+        }
+    }
+
+    /// Expire the PIT entries whose lifetime has run out, notifying their strategies,
+    /// clean up stale measurements and drop expired routes. Expected to be called periodically.
+    pub async fn handle_timeouts(&self) {
+        self.routing_information_base
+            .expire(now(), &self.forwarding_information_base)
+            .await;
+        let mut expired = Vec::new();
+        for shard in &self.shards {
+            expired.extend(shard.pending_interest_table().expire().await);
+        }
+        let now = now();
+        let mut measurements = self.measurements.write().await;
+        for pit_entry in expired {
+            self.strategy_choice
+                .find_effective_strategy(&pit_entry.interest.name)
+                .on_interest_timeout(&pit_entry, &mut measurements, now);
+        }
+        measurements.cleanup(now);
+    }
+
+    pub async fn n_measurements_entries(&self) -> usize {
+        self.measurements.read().await.len()
     }
 
     pub async fn handle_interest(
@@ -73,16 +490,491 @@ impl Router {
         interest: Interest,
         downstream: face::FaceId,
     ) -> io::Result<()> {
-        if let Some(data) = self.content_store.lookup(&interest).await {
+        let shard = self.shard(self.shard_of(&interest.name))?;
+        self.process_interest(shard, interest, downstream, &lpv2::LpPacket::new())
+            .await
+    }
+
+    /// Link layer header fields of `packet` carry the downstream PitToken and NextHopFaceId,
+    /// the latter set by a local application to bypass the FIB
+    async fn process_interest(
+        &self,
+        shard: &Shard,
+        mut interest: Interest,
+        downstream: face::FaceId,
+        packet: &lpv2::LpPacket,
+    ) -> io::Result<()> {
+        let downstream_face = self.faces.get_face(downstream).await?;
+        if !downstream_face.admit_interest().await {
+            let exceeded = LimitExceeded::InterestRate;
+            return self
+                .reject_interest(interest, downstream, packet, exceeded)
+                .await;
+        }
+
+        let stage = InterestStage::Incoming;
+        match self
+            .hooks
+            .on_interest(stage, &mut interest, &downstream_face)
+            .await
+        {
+            InterestAction::Continue => {}
+            InterestAction::Drop => return Ok(()),
+            InterestAction::Answer(data) => {
+                let pit_token = packet.pit_token.clone();
+                return self
+                    .send_data(*data, downstream, pit_token, None, None)
+                    .await;
+            }
+        }
+
+        let stage = InterestStage::BeforeCsLookup;
+        match self
+            .hooks
+            .on_interest(stage, &mut interest, &downstream_face)
+            .await
+        {
+            InterestAction::Continue => {}
+            InterestAction::Drop => return Ok(()),
+            InterestAction::Answer(data) => {
+                let pit_token = packet.pit_token.clone();
+                return self
+                    .send_data(*data, downstream, pit_token, None, None)
+                    .await;
+            }
+        }
+
+        if let Some(data) = self.lookup_content_store(shard, &interest).await {
             // TODO Check freshness
-            let data = data.clone();
-            self.faces.send_item(downstream, data).await?;
+            let pit_token = packet.pit_token.clone();
+            let incoming = Some(face::FaceId::CONTENT_STORE);
+            self.send_data(data, downstream, pit_token, incoming, None)
+                .await?;
         } else {
-            self.pending_interest_table
-                .register(&interest, downstream)
+            let registered = shard
+                .pending_interest_table()
+                .register(&interest, downstream, packet.pit_token.clone())
                 .await;
-            let upstream = self.forwarding_information_base.lookup(&interest).await;
-            self.faces.send_item(upstream, interest).await?;
+            let (pit_token, arrival) = match registered {
+                Ok(registered) => registered,
+                Err(exceeded) => {
+                    return self
+                        .reject_interest(interest, downstream, packet, exceeded)
+                        .await;
+                }
+            };
+            match arrival {
+                Arrival::New => {}
+                Arrival::Retransmission => {
+                    let suppression = self
+                        .strategy_choice
+                        .find_effective_strategy(&interest.name)
+                        .retx_suppression();
+                    let decision = shard
+                        .pending_interest_table()
+                        .decide_retransmission(&pit_token, &suppression)
+                        .await;
+                    if decision == RetxDecision::Suppress {
+                        tracing::trace!(%interest, %downstream, "Retransmission suppressed");
+                        return Ok(());
+                    }
+                }
+                Arrival::DuplicateNonce => {
+                    tracing::debug!(%interest, %downstream, "Looping Interest");
+                    // Other members of a multi-access link may well have sent the same Interest
+                    if downstream_face.link_type() != face::LinkType::PointToPoint {
+                        return Ok(());
+                    }
+                    let pit_token = packet.pit_token.clone();
+                    let reason = lpv2::NackReason::Duplicate;
+                    return self
+                        .send_nack(interest, downstream, pit_token, reason)
+                        .await;
+                }
+            }
+
+            let upstreams = if let Some(next_hop) = packet.next_hop_face_id {
+                vec![face::FaceId::from(next_hop.to_u64())]
+            } else if let Some(fib_entry) = self.lookup_fib(&mut interest).await {
+                let mut measurements = self.measurements.write().await;
+                self.strategy_choice
+                    .find_effective_strategy(&interest.name)
+                    .after_receive_interest(
+                        &interest,
+                        downstream,
+                        &fib_entry,
+                        &mut measurements,
+                        now(),
+                    )
+            } else {
+                let faces = self.faces.get_handles().await;
+                self.strategy_choice
+                    .find_effective_strategy(&interest.name)
+                    .after_no_route(&interest, downstream, &faces)
+            };
+            let mut upstream_faces = Vec::with_capacity(upstreams.len());
+            for upstream in upstreams {
+                match self.faces.get_face(upstream).await {
+                    Ok(upstream_face) => upstream_faces.push(upstream_face),
+                    Err(error) => {
+                        tracing::trace!(%interest, %upstream, %error, "Skipping upstream");
+                    }
+                }
+            }
+            if upstream_faces.is_empty() {
+                tracing::debug!(%interest, %downstream, "No route");
+                let pit_entry = shard.pending_interest_table().remove(&pit_token).await;
+                if let Some(pit_entry) = pit_entry {
+                    let nack = lpv2::Nack::new(lpv2::NackReason::NoRoute);
+                    self.nack_downstreams(pit_entry, nack).await?;
+                }
+                return Ok(());
+            }
+
+            let upstream_token = shard.tag(&pit_token);
+            for upstream_face in upstream_faces {
+                let upstream = upstream_face.face_id();
+                if upstream == downstream && !upstream_face.may_reflect() {
+                    tracing::trace!(%interest, %downstream, "Not reflecting Interest");
+                    continue;
+                }
+                let mut interest = interest.clone();
+                let stage = InterestStage::Outgoing;
+                match self
+                    .hooks
+                    .on_interest(stage, &mut interest, &upstream_face)
+                    .await
+                {
+                    InterestAction::Continue => {}
+                    InterestAction::Drop => continue,
+                    InterestAction::Answer(data) => {
+                        shard
+                            .pending_interest_table()
+                            .add_out_record(&pit_token, upstream)
+                            .await;
+                        let header = lpv2::LpPacket::new().with_pit_token(upstream_token.clone());
+                        self.process_data(shard, *data, upstream, &header).await?;
+                        continue;
+                    }
+                }
+
+                shard
+                    .pending_interest_table()
+                    .add_out_record(&pit_token, upstream)
+                    .await;
+                let packet = lpv2::LpPacket::from_tlv(interest)
+                    .with_pit_token(upstream_token.clone())
+                    .with_incoming_face_id(downstream.to_u64());
+                upstream_face.send_packet(packet).await?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Data satisfying `interest` out of the Content Store. Data is cached by the shard
+    /// owning its name, so CanBePrefix Interests shorter than the sharding prefix look into
+    /// every shard, taking the first match in canonical order.
+    async fn lookup_content_store(&self, shard: &Shard, interest: &Interest) -> Option<Data> {
+        if !interest.is_can_be_prefix() || interest.name.len() >= self.shard_prefix_len {
+            let data = shard.content_store().lookup(interest).await?;
+            return Some(data.clone());
+        }
+
+        let mut found: Option<Data> = None;
+        for shard in &self.shards {
+            if let Some(data) = shard.content_store().lookup(interest).await {
+                if found.as_ref().is_none_or(|found| data.name < found.name) {
+                    found = Some(data.clone());
+                }
+            }
+        }
+        found
+    }
+
+    /// Store `data` in the Content Store of the shard owning its name
+    async fn cache(&self, data: Data) -> io::Result<()> {
+        let shard = self.shard(self.shard_of(&data.name))?;
+        shard.content_store().insert(data).await;
+        Ok(())
+    }
+
+    /// FIB entry to forward `interest` by. Once in the producer region, the Interest name
+    /// takes over from the ForwardingHint, which is stripped if the name has a FIB entry.
+    async fn lookup_fib(&self, interest: &mut Interest) -> Option<FibEntry> {
+        if let Some(forwarding_hint) = &interest.forwarding_hint {
+            if self
+                .network_region_table
+                .is_in_producer_region(forwarding_hint)
+                .await
+            {
+                let fib_entry = self
+                    .forwarding_information_base
+                    .longest_prefix_match(&interest.name)
+                    .await;
+                if fib_entry.is_some() {
+                    tracing::trace!(%interest, "Reached producer region, stripping ForwardingHint");
+                    interest.forwarding_hint = None;
+                    return fib_entry;
+                }
+            }
+        }
+        self.forwarding_information_base.lookup(interest).await
+    }
+
+    /// Reject the Interest of `pit_entry` with `nack` to each of its downstreams,
+    /// echoing back the PitToken each one has attached
+    async fn nack_downstreams(&self, pit_entry: PitEntry, nack: lpv2::Nack) -> io::Result<()> {
+        for (downstream, pit_token) in pit_entry.downstreams {
+            tracing::debug!(interest = %pit_entry.interest, %downstream, %nack, "Sending Nack");
+            let packet = lpv2::LpPacket::from_tlv(pit_entry.interest.clone())
+                .with_pit_token(pit_token)
+                .with_nack(nack);
+            self.faces.send_packet(downstream, packet).await?;
+        }
+        Ok(())
+    }
+
+    /// Drop `interest` that exceeded a resource limit, letting `downstream` know with a Nack
+    async fn reject_interest(
+        &self,
+        interest: Interest,
+        downstream: face::FaceId,
+        packet: &lpv2::LpPacket,
+        exceeded: LimitExceeded,
+    ) -> io::Result<()> {
+        tracing::debug!(%interest, %downstream, %exceeded, "Rejecting Interest");
+        self.limit_counters.count(exceeded);
+        let pit_token = packet.pit_token.clone();
+        let reason = lpv2::NackReason::Congestion;
+        self.send_nack(interest, downstream, pit_token, reason)
+            .await
+    }
+
+    /// Reject `interest` received from `downstream` with a Nack carrying `reason`
+    async fn send_nack(
+        &self,
+        interest: Interest,
+        downstream: face::FaceId,
+        pit_token: Option<lpv2::PitToken>,
+        reason: lpv2::NackReason,
+    ) -> io::Result<()> {
+        let packet = lpv2::LpPacket::from_tlv(interest)
+            .with_pit_token(pit_token)
+            .with_nack(lpv2::Nack::new(reason));
+        self.faces.send_packet(downstream, packet).await
+    }
+
+    /// Nack of `interest` sent to `upstream`, the echoed PitToken in `packet` tells the
+    /// PIT entry. Downstreams are Nacked once no upstream is left to answer.
+    async fn process_nack(
+        &self,
+        shard: &Shard,
+        interest: Interest,
+        nack: lpv2::Nack,
+        upstream: face::FaceId,
+        packet: &lpv2::LpPacket,
+    ) -> io::Result<()> {
+        self.strategy_choice
+            .find_effective_strategy(&interest.name)
+            .after_receive_nack(&interest, upstream, nack);
+
+        let Some(pit_token) = packet
+            .pit_token
+            .as_ref()
+            .and_then(|token| shard.untag(token))
+        else {
+            tracing::debug!(%interest, %upstream, %nack, "Dropping Nack without PitToken");
+            return Ok(());
+        };
+        let nacked = shard
+            .pending_interest_table()
+            .nack(&pit_token, &interest, upstream, nack.reason())
+            .await;
+        if let Some((pit_entry, reason)) = nacked {
+            self.nack_downstreams(pit_entry, lpv2::Nack::new(reason))
+                .await?;
+        }
+        Ok(())
+    }
+
+    pub async fn handle_data(&self, data: Data, upstream: face::FaceId) -> io::Result<()> {
+        let shard = self.shard(self.shard_of(&data.name))?;
+        self.process_data(shard, data, upstream, &lpv2::LpPacket::new())
+            .await
+    }
+
+    /// Link layer header fields of `packet` carry the echoed PitToken, the congestion mark
+    /// and the cache policy
+    async fn process_data(
+        &self,
+        shard: &Shard,
+        mut data: Data,
+        upstream: face::FaceId,
+        packet: &lpv2::LpPacket,
+    ) -> io::Result<()> {
+        let upstream_face = self.faces.get_face(upstream).await?;
+        let stage = DataStage::Incoming;
+        if let DataAction::Drop = self.hooks.on_data(stage, &mut data, &upstream_face).await {
+            return Ok(());
+        }
+
+        self.strategy_choice
+            .find_effective_strategy(&data.name)
+            .after_receive_data(&data, upstream, packet.congestion_mark);
+
+        let pit_token = packet
+            .pit_token
+            .as_ref()
+            .and_then(|token| shard.untag(token));
+        let mut satisfied = shard
+            .pending_interest_table()
+            .satisfy(&data, pit_token.as_ref())
+            .await;
+        if pit_token.is_none() {
+            // CanBePrefix Interests shorter than the sharding prefix are pending elsewhere
+            for other in self.prefix_shards(&data.name) {
+                if other != shard.id() {
+                    let pit = self.shard(other)?.pending_interest_table();
+                    satisfied.extend(pit.satisfy(&data, None).await);
+                }
+            }
+        }
+        let no_cache = packet
+            .cache_policy
+            .is_some_and(|policy| policy.is_no_cache());
+
+        if satisfied.is_empty() {
+            return self.handle_unsolicited_data(data, upstream, no_cache).await;
+        }
+
+        let now = now();
+        let mut measurements = self.measurements.write().await;
+        for pit_entry in &satisfied {
+            self.strategy_choice
+                .find_effective_strategy(&pit_entry.interest.name)
+                .before_satisfy_interest(pit_entry, upstream, &data, &mut measurements, now);
+        }
+        drop(measurements);
+
+        if !no_cache {
+            self.cache(data.clone()).await?;
+        }
+
+        let announcement = self.accept_announcement(&data, upstream, packet).await;
+
+        let downstreams = satisfied
+            .into_iter()
+            .flat_map(|pit_entry| pit_entry.downstreams)
+            .collect::<HashMap<_, _>>();
+        let may_reflect = self
+            .faces
+            .get_face(upstream)
+            .await
+            .is_ok_and(|face| face.may_reflect());
+        for (downstream, pit_token) in downstreams {
+            if downstream != upstream || may_reflect {
+                self.send_data(
+                    data.clone(),
+                    downstream,
+                    pit_token,
+                    Some(upstream),
+                    announcement.clone(),
+                )
+                .await?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Install the route announced along `data` solicited from `upstream`, if the strategy
+    /// learns routes and the announcement is trusted.
+    /// Returns the announcement to pass on downstream, if the strategy attaches them:
+    /// the one received, or the one `upstream` has been routed by.
+    async fn accept_announcement(
+        &self,
+        data: &Data,
+        upstream: face::FaceId,
+        packet: &lpv2::LpPacket,
+    ) -> Option<tlv::PrefixAnnouncement> {
+        if !self
+            .strategy_choice
+            .find_effective_strategy(&data.name)
+            .attaches_prefix_announcements()
+        {
+            return None;
+        }
+
+        let received = packet
+            .prefix_announcement
+            .as_deref()
+            .filter(|announcement| announcement.prefix().is_prefix_of(&data.name))
+            .filter(|announcement| {
+                let trusted = self
+                    .announcement_validator
+                    .as_ref()
+                    .is_some_and(|validator| validator.is_trusted(announcement, upstream));
+                if !trusted {
+                    tracing::debug!(name = %data.name, %upstream, "Ignoring untrusted announcement");
+                }
+                trusted
+            });
+        if let Some(announcement) = received {
+            if let Err(error) = self.announce_prefix(announcement.clone(), upstream).await {
+                tracing::debug!(name = %data.name, %upstream, %error, "Ignoring announcement");
+            }
+            Some(announcement.clone())
+        } else {
+            self.routing_information_base
+                .find_announcement(&data.name, upstream)
+                .await
+        }
+    }
+
+    /// Send `data` to `downstream` unless dropped by the outgoing Data hooks
+    async fn send_data(
+        &self,
+        mut data: Data,
+        downstream: face::FaceId,
+        pit_token: Option<lpv2::PitToken>,
+        incoming: Option<face::FaceId>,
+        announcement: Option<tlv::PrefixAnnouncement>,
+    ) -> io::Result<()> {
+        let downstream_face = self.faces.get_face(downstream).await?;
+        let stage = DataStage::Outgoing;
+        if let DataAction::Drop = self.hooks.on_data(stage, &mut data, &downstream_face).await {
+            return Ok(());
+        }
+
+        let packet = lpv2::LpPacket::from_tlv(data)
+            .with_pit_token(pit_token)
+            .with_prefix_announcement(announcement);
+        let packet = if let Some(incoming) = incoming {
+            packet.with_incoming_face_id(incoming.to_u64())
+        } else {
+            packet
+        };
+        downstream_face.send_packet(packet).await
+    }
+
+    async fn handle_unsolicited_data(
+        &self,
+        data: Data,
+        upstream: face::FaceId,
+        no_cache: bool,
+    ) -> io::Result<()> {
+        self.n_unsolicited_data.fetch_add(1, Ordering::Relaxed);
+
+        let face_scope = self.faces.get_face(upstream).await?.face_scope();
+        let policy = self.unsolicited_data_policy;
+        if !no_cache && policy.admits(face_scope) {
+            tracing::debug!(name = %data.name, %upstream, %policy, "Admitting unsolicited Data");
+            self.n_unsolicited_data_admitted
+                .fetch_add(1, Ordering::Relaxed);
+            self.cache(data).await?;
+        } else {
+            tracing::debug!(name = %data.name, %upstream, %policy, "Dropping unsolicited Data");
         }
 
         Ok(())
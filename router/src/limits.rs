@@ -0,0 +1,136 @@
+use std::fmt;
+use std::time::Instant;
+
+use super::*;
+
+/// Resource limits protecting the router from Interest flooding, `None` means unlimited
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct ResourceLimits {
+    pub max_pit_entries: Option<usize>,
+    /// pending Interests each downstream face is allowed to have
+    pub max_pit_entries_per_face: Option<usize>,
+    /// Content Store capacity counted in encoded Data octets
+    pub max_cs_bytes: Option<usize>,
+    /// Interests accepted from each face
+    pub interest_rate: Option<InterestRateLimit>,
+}
+
+/// Token bucket parameters
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct InterestRateLimit {
+    /// Interests per second
+    pub rate: f64,
+    /// Interests accepted in a burst
+    pub burst: f64,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum LimitExceeded {
+    PitEntries,
+    PitEntriesPerFace,
+    InterestRate,
+}
+
+impl fmt::Display for LimitExceeded {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::PitEntries => "PIT entries limit exceeded",
+            Self::PitEntriesPerFace => "PIT entries per face limit exceeded",
+            Self::InterestRate => "Interest rate limit exceeded",
+        }
+        .fmt(f)
+    }
+}
+
+#[derive(Debug)]
+pub struct TokenBucket {
+    limit: InterestRateLimit,
+    tokens: f64,
+    last: Instant,
+}
+
+impl TokenBucket {
+    pub fn new(limit: InterestRateLimit, now: Instant) -> Self {
+        let tokens = limit.burst;
+        Self {
+            limit,
+            tokens,
+            last: now,
+        }
+    }
+
+    /// Take one token if available
+    pub fn try_consume(&mut self, now: Instant) -> bool {
+        let elapsed = now.saturating_duration_since(self.last).as_secs_f64();
+        self.tokens = (self.tokens + elapsed * self.limit.rate).min(self.limit.burst);
+        self.last = now.max(self.last);
+
+        if self.tokens >= 1.0 {
+            self.tokens -= 1.0;
+            true
+        } else {
+            false
+        }
+    }
+}
+
+/// Interests dropped for exceeding each of the limits
+#[derive(Debug, Default)]
+pub struct LimitCounters {
+    n_pit_entries_exceeded: AtomicU64,
+    n_pit_entries_per_face_exceeded: AtomicU64,
+    n_interest_rate_exceeded: AtomicU64,
+}
+
+impl LimitCounters {
+    pub fn n_pit_entries_exceeded(&self) -> u64 {
+        self.n_pit_entries_exceeded.load(Ordering::Relaxed)
+    }
+
+    pub fn n_pit_entries_per_face_exceeded(&self) -> u64 {
+        self.n_pit_entries_per_face_exceeded.load(Ordering::Relaxed)
+    }
+
+    pub fn n_interest_rate_exceeded(&self) -> u64 {
+        self.n_interest_rate_exceeded.load(Ordering::Relaxed)
+    }
+
+    pub(crate) fn count(&self, exceeded: LimitExceeded) {
+        let counter = match exceeded {
+            LimitExceeded::PitEntries => &self.n_pit_entries_exceeded,
+            LimitExceeded::PitEntriesPerFace => &self.n_pit_entries_per_face_exceeded,
+            LimitExceeded::InterestRate => &self.n_interest_rate_exceeded,
+        };
+        counter.fetch_add(1, Ordering::Relaxed);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::*;
+
+    #[test]
+    fn token_bucket() {
+        let start = Instant::now();
+        let limit = InterestRateLimit {
+            rate: 10.0,
+            burst: 3.0,
+        };
+        let mut bucket = TokenBucket::new(limit, start);
+
+        // Burst is allowed right away, then the bucket is empty
+        assert!((0..3).all(|_| bucket.try_consume(start)));
+        assert!(!bucket.try_consume(start));
+
+        // One token every 100ms
+        assert!(!bucket.try_consume(start + Duration::from_millis(50)));
+        assert!(bucket.try_consume(start + Duration::from_millis(100)));
+        assert!(!bucket.try_consume(start + Duration::from_millis(100)));
+
+        // Never more than the burst
+        let later = start + Duration::from_secs(10);
+        assert_eq!((0..10).filter(|_| bucket.try_consume(later)).count(), 3);
+    }
+}
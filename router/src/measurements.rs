@@ -0,0 +1,197 @@
+use std::time::{Duration, Instant};
+
+use super::*;
+
+/// What has been learned about forwarding a namespace through a particular face
+#[derive(Debug, Default)]
+pub struct FaceMeasurements {
+    rtt: lpv2::RttEstimator,
+    n_timeouts: u64,
+    n_silent_timeouts: u32,
+    last_timeout: Option<Instant>,
+    last_probe: Option<Instant>,
+}
+
+impl FaceMeasurements {
+    pub fn rtt(&self) -> &lpv2::RttEstimator {
+        &self.rtt
+    }
+
+    pub fn smoothed_rtt(&self) -> Option<Duration> {
+        self.rtt.smoothed_rtt()
+    }
+
+    /// Total number of Interests this face failed to bring Data for
+    pub fn n_timeouts(&self) -> u64 {
+        self.n_timeouts
+    }
+
+    /// Number of timeouts in a row since the last Data received from this face
+    pub fn n_silent_timeouts(&self) -> u32 {
+        self.n_silent_timeouts
+    }
+
+    pub fn last_timeout(&self) -> Option<Instant> {
+        self.last_timeout
+    }
+
+    pub fn last_probe(&self) -> Option<Instant> {
+        self.last_probe
+    }
+
+    pub fn record_rtt(&mut self, rtt: Duration) {
+        self.rtt.add_measurement(rtt);
+        self.n_silent_timeouts = 0;
+    }
+
+    pub fn record_timeout(&mut self, now: Instant) {
+        self.rtt.backoff_rto();
+        self.n_timeouts += 1;
+        self.n_silent_timeouts += 1;
+        self.last_timeout = Some(now);
+    }
+
+    pub fn record_probe(&mut self, now: Instant) {
+        self.last_probe = Some(now);
+    }
+}
+
+#[derive(Debug)]
+pub struct MeasurementsEntry {
+    faces: HashMap<face::FaceId, FaceMeasurements>,
+    expiry: Instant,
+}
+
+impl MeasurementsEntry {
+    pub fn face(&self, face: face::FaceId) -> Option<&FaceMeasurements> {
+        self.faces.get(&face)
+    }
+
+    pub fn face_mut(&mut self, face: face::FaceId) -> &mut FaceMeasurements {
+        self.faces.entry(face).or_default()
+    }
+
+    pub fn expiry(&self) -> Instant {
+        self.expiry
+    }
+
+    /// Keep the entry at least until `expiry`
+    pub fn extend_lifetime(&mut self, expiry: Instant) {
+        self.expiry = self.expiry.max(expiry);
+    }
+}
+
+/// Measurements table keeps per-namespace information strategies collect about the faces,
+/// each entry lives for `entry_lifetime` after it has been last accessed.
+#[derive(Debug)]
+pub struct MeasurementsTable {
+    entries: HashMap<tlv::Name, MeasurementsEntry>,
+    entry_lifetime: Duration,
+}
+
+impl MeasurementsTable {
+    pub const DEFAULT_ENTRY_LIFETIME: Duration = Duration::from_secs(4);
+
+    pub fn new(entry_lifetime: Duration) -> Self {
+        Self {
+            entries: HashMap::new(),
+            entry_lifetime,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    pub fn get(&self, prefix: &tlv::Name) -> Option<&MeasurementsEntry> {
+        self.entries.get(prefix)
+    }
+
+    /// Entry for `prefix`, its lifetime is extended by `entry_lifetime` from `now`
+    pub fn get_or_insert(&mut self, prefix: &tlv::Name, now: Instant) -> &mut MeasurementsEntry {
+        let expiry = now + self.entry_lifetime;
+        let entry = self
+            .entries
+            .entry(prefix.clone())
+            .or_insert_with(|| MeasurementsEntry {
+                faces: HashMap::new(),
+                expiry,
+            });
+        entry.extend_lifetime(expiry);
+        entry
+    }
+
+    /// Entry of the longest prefix of `name` present in the table
+    pub fn longest_prefix_match(&self, name: &tlv::Name) -> Option<&MeasurementsEntry> {
+        (0..=name.len())
+            .rev()
+            .find_map(|len| self.entries.get(&name.prefix(len)))
+    }
+
+    pub fn longest_prefix_match_mut(&mut self, name: &tlv::Name) -> Option<&mut MeasurementsEntry> {
+        let prefix = (0..=name.len())
+            .rev()
+            .map(|len| name.prefix(len))
+            .find(|prefix| self.entries.contains_key(prefix))?;
+        self.entries.get_mut(&prefix)
+    }
+
+    /// Drop the entries not accessed during their lifetime
+    pub fn cleanup(&mut self, now: Instant) {
+        self.entries.retain(|_, entry| entry.expiry > now);
+    }
+}
+
+impl Default for MeasurementsTable {
+    fn default() -> Self {
+        Self::new(Self::DEFAULT_ENTRY_LIFETIME)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn entry_lifetime() {
+        let now = Instant::now();
+        let lifetime = MeasurementsTable::DEFAULT_ENTRY_LIFETIME;
+        let mut table = MeasurementsTable::default();
+        let prefix = "/a/b".parse().unwrap();
+
+        let face = face::FaceId::from(1);
+        table
+            .get_or_insert(&prefix, now)
+            .face_mut(face)
+            .record_rtt(Duration::from_millis(10));
+        assert!(table
+            .longest_prefix_match(&"/a/b/c".parse().unwrap())
+            .is_some());
+
+        // Accessing the entry extends its lifetime
+        table.get_or_insert(&prefix, now + lifetime / 2);
+        table.cleanup(now + lifetime);
+        assert_eq!(table.len(), 1);
+
+        table.cleanup(now + lifetime * 2);
+        assert!(table.is_empty());
+    }
+
+    #[test]
+    fn timeouts() {
+        let mut face = FaceMeasurements::default();
+        let now = Instant::now();
+        face.record_timeout(now);
+        face.record_timeout(now);
+        assert_eq!(face.n_silent_timeouts(), 2);
+
+        face.record_rtt(Duration::from_millis(10));
+        assert_eq!(face.n_silent_timeouts(), 0);
+        assert_eq!(face.n_timeouts(), 2);
+        assert_eq!(face.smoothed_rtt(), Some(Duration::from_millis(10)));
+    }
+}
@@ -1,18 +1,554 @@
+use std::time::{Duration, Instant};
+
+use slotmap::Key;
+use slotmap::KeyData;
+use slotmap::SlotMap;
+
 use super::*;
 
+slotmap::new_key_type! { struct PitKey; }
+
+impl From<PitKey> for lpv2::PitToken {
+    fn from(key: PitKey) -> Self {
+        key.data().as_ffi().into()
+    }
+}
+
+impl TryFrom<&lpv2::PitToken> for PitKey {
+    type Error = tlv::DecodeError;
+
+    fn try_from(token: &lpv2::PitToken) -> Result<Self, Self::Error> {
+        u64::try_from(token).map(|value| KeyData::from_ffi(value).into())
+    }
+}
+
+/// Pending Interest with the downstream faces it came from and the upstream faces it went to
+#[derive(Debug)]
+pub struct PitEntry {
+    pub interest: Interest,
+    /// each downstream may have attached its own PitToken which is echoed back with the Data
+    pub downstreams: HashMap<face::FaceId, Option<lpv2::PitToken>>,
+    /// time the Interest was last sent to each upstream
+    pub upstreams: HashMap<face::FaceId, Instant>,
+    /// reason of the Nack received from each upstream
+    pub nacks: HashMap<face::FaceId, lpv2::NackReason>,
+    /// Nonces of the Interests aggregated in this entry
+    pub nonces: Vec<tlv::Nonce>,
+    /// current retransmission suppression interval, see [`RetxSuppression`]
+    pub retx_suppression_interval: Option<Duration>,
+    pub expiry: Instant,
+}
+
+/// How an incoming Interest relates to the PIT entries already present
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Arrival {
+    /// no matching PIT entry existed
+    New,
+    /// matching PIT entry exists, the Interest carries a new Nonce
+    Retransmission,
+    /// matching PIT entry has seen the same Nonce already, i.e. the Interest is looping
+    DuplicateNonce,
+}
+
+/// Interests with the same name and selectors are aggregated in the same PIT entry
+#[derive(Debug, PartialEq, Eq, Hash)]
+struct Selectors {
+    name: tlv::Name,
+    can_be_prefix: bool,
+    must_be_fresh: bool,
+    forwarding_hint: Option<tlv::ForwardingHint>,
+}
+
+impl From<&Interest> for Selectors {
+    fn from(interest: &Interest) -> Self {
+        Self {
+            name: interest.name.clone(),
+            can_be_prefix: interest.can_be_prefix.is_some(),
+            must_be_fresh: interest.must_be_fresh.is_some(),
+            forwarding_hint: interest.forwarding_hint.clone(),
+        }
+    }
+}
+
+#[derive(Debug, Default)]
+struct Pit {
+    entries: SlotMap<PitKey, PitEntry>,
+    index: HashMap<Selectors, PitKey>,
+    /// entries by Interest name, whatever their selectors
+    names: HashMap<tlv::Name, Vec<PitKey>>,
+    /// number of entries each downstream face is pending on
+    per_face: HashMap<face::FaceId, usize>,
+}
+
 #[derive(Debug, Default)]
 pub struct PendingInterestTable {
-    pit: RwLock<HashMap<Interest, HashSet<face::FaceId>>>,
+    pit: RwLock<Pit>,
+    max_entries: Option<usize>,
+    max_entries_per_face: Option<usize>,
 }
 
 impl PendingInterestTable {
-    pub async fn register(&self, interest: &Interest, downstream: face::FaceId) {
-        let interest = interest.clone();
-        self.pit
-            .write()
-            .await
-            .entry(interest)
-            .or_default()
-            .insert(downstream);
+    /// InterestLifetime assumed when the Interest does not carry one
+    pub const DEFAULT_INTEREST_LIFETIME: Duration = Duration::from_secs(4);
+
+    /// Limit the number of entries overall and per downstream face
+    pub fn with_limits(
+        self,
+        max_entries: Option<usize>,
+        max_entries_per_face: Option<usize>,
+    ) -> Self {
+        Self {
+            max_entries,
+            max_entries_per_face,
+            ..self
+        }
+    }
+
+    /// Record `interest` as pending on `downstream` until its InterestLifetime runs out.
+    /// Returns the PitToken to attach to the Interest sent upstream
+    /// and whether the Interest is new, retransmitted or looping.
+    /// Looping Interests and the ones over the limits are not recorded.
+    pub async fn register(
+        &self,
+        interest: &Interest,
+        downstream: face::FaceId,
+        downstream_token: Option<lpv2::PitToken>,
+    ) -> Result<(lpv2::PitToken, Arrival), LimitExceeded> {
+        let lifetime = interest
+            .interest_lifetime
+            .map_or(Self::DEFAULT_INTEREST_LIFETIME, |lifetime| {
+                Duration::from_millis(lifetime.to_u64())
+            });
+        let expiry = now() + lifetime;
+
+        let mut pit = self.pit.write().await;
+        let Pit {
+            entries,
+            index,
+            names,
+            per_face,
+        } = &mut *pit;
+
+        let selectors = Selectors::from(interest);
+        let existing = index.get(&selectors).and_then(|key| entries.get(*key));
+        if existing.is_none() && self.max_entries.is_some_and(|max| entries.len() >= max) {
+            return Err(LimitExceeded::PitEntries);
+        }
+        let is_new_downstream =
+            existing.is_none_or(|entry| !entry.downstreams.contains_key(&downstream));
+        let n_face_entries = per_face.get(&downstream).copied().unwrap_or_default();
+        if is_new_downstream
+            && self
+                .max_entries_per_face
+                .is_some_and(|max| n_face_entries >= max)
+        {
+            return Err(LimitExceeded::PitEntriesPerFace);
+        }
+
+        let mut arrival = Arrival::Retransmission;
+        let key = *index.entry(selectors).or_insert_with(|| {
+            arrival = Arrival::New;
+            let key = entries.insert(PitEntry {
+                interest: interest.clone(),
+                downstreams: HashMap::new(),
+                upstreams: HashMap::new(),
+                nacks: HashMap::new(),
+                nonces: Vec::new(),
+                retx_suppression_interval: None,
+                expiry,
+            });
+            names.entry(interest.name.clone()).or_default().push(key);
+            key
+        });
+
+        if let Some(entry) = entries.get_mut(key) {
+            if let Some(nonce) = interest.nonce {
+                if entry.nonces.contains(&nonce) {
+                    arrival = Arrival::DuplicateNonce;
+                } else {
+                    entry.nonces.push(nonce);
+                }
+            }
+            if arrival != Arrival::DuplicateNonce {
+                if entry
+                    .downstreams
+                    .insert(downstream, downstream_token)
+                    .is_none()
+                {
+                    *per_face.entry(downstream).or_default() += 1;
+                }
+                entry.expiry = entry.expiry.max(expiry);
+            }
+        }
+
+        Ok((key.into(), arrival))
+    }
+
+    /// Number of entries `downstream` is pending on
+    pub async fn n_face_entries(&self, downstream: face::FaceId) -> usize {
+        let pit = self.pit.read().await;
+        pit.per_face.get(&downstream).copied().unwrap_or_default()
+    }
+
+    /// Apply retransmission `suppression` to the entry referenced by `token`
+    pub async fn decide_retransmission(
+        &self,
+        token: &lpv2::PitToken,
+        suppression: &RetxSuppression,
+    ) -> RetxDecision {
+        let Ok(key) = PitKey::try_from(token) else {
+            return RetxDecision::New;
+        };
+        let mut pit = self.pit.write().await;
+        pit.entries
+            .get_mut(key)
+            .map_or(RetxDecision::New, |entry| suppression.decide(entry, now()))
+    }
+
+    /// Record the Interest of the entry referenced by `token` being sent to `upstream`
+    pub async fn add_out_record(&self, token: &lpv2::PitToken, upstream: face::FaceId) {
+        let Ok(key) = PitKey::try_from(token) else {
+            return;
+        };
+        if let Some(entry) = self.pit.write().await.entries.get_mut(key) {
+            entry.upstreams.insert(upstream, now());
+        }
+    }
+
+    /// Record the Nack of `interest` received from `upstream` on the entry referenced by
+    /// `token`. Once all the upstreams have Nacked the Interest, the entry is removed and
+    /// returned along with the least severe of their reasons.
+    pub async fn nack(
+        &self,
+        token: &lpv2::PitToken,
+        interest: &Interest,
+        upstream: face::FaceId,
+        reason: lpv2::NackReason,
+    ) -> Option<(PitEntry, lpv2::NackReason)> {
+        let key = PitKey::try_from(token).ok()?;
+        let mut pit = self.pit.write().await;
+        let entry = pit.entries.get_mut(key)?;
+        if entry.interest.name != interest.name || !entry.upstreams.contains_key(&upstream) {
+            return None;
+        }
+        entry.nacks.insert(upstream, reason);
+        if !entry
+            .upstreams
+            .keys()
+            .all(|upstream| entry.nacks.contains_key(upstream))
+        {
+            return None;
+        }
+
+        let reason = entry
+            .nacks
+            .values()
+            .copied()
+            .reduce(|least, reason| {
+                if reason.is_less_severe(&least) {
+                    reason
+                } else {
+                    least
+                }
+            })
+            .unwrap_or(reason);
+        let entry = pit.remove([key]).pop()?;
+        Some((entry, reason))
+    }
+
+    /// Remove the entry referenced by `token`, e.g. once the Interest can go nowhere
+    pub async fn remove(&self, token: &lpv2::PitToken) -> Option<PitEntry> {
+        let key = PitKey::try_from(token).ok()?;
+        self.pit.write().await.remove([key]).pop()
+    }
+
+    /// Remove the entries whose lifetime has run out
+    pub async fn expire(&self) -> Vec<PitEntry> {
+        let now = now();
+        let mut pit = self.pit.write().await;
+        let keys = pit
+            .entries
+            .iter()
+            .filter(|(_, entry)| entry.expiry <= now)
+            .map(|(key, _)| key)
+            .collect::<Vec<_>>();
+        pit.remove(keys)
+    }
+
+    pub async fn len(&self) -> usize {
+        self.pit.read().await.entries.len()
+    }
+
+    pub async fn is_empty(&self) -> bool {
+        self.pit.read().await.entries.is_empty()
+    }
+
+    /// Remove and return the entries satisfied by `data`.
+    ///
+    /// The PitToken echoed by upstream points straight at its entry. The other entries
+    /// the Data satisfies are looked up by name, the exact one and the CanBePrefix ones
+    /// under each prefix of the Data name.
+    pub async fn satisfy(&self, data: &Data, token: Option<&lpv2::PitToken>) -> Vec<PitEntry> {
+        let mut pit = self.pit.write().await;
+
+        let token_key = token
+            .and_then(|token| PitKey::try_from(token).ok())
+            .filter(|key| {
+                pit.entries
+                    .get(*key)
+                    .is_some_and(|entry| entry.interest.matches_data(data))
+            });
+        if token.is_some() && token_key.is_none() {
+            tracing::trace!(name = %data.name, "No valid PitToken, matching by name");
+        }
+
+        let mut keys = Vec::from_iter(token_key);
+        for len in 0..=data.name.len() {
+            let Some(named) = pit.names.get(&data.name.prefix(len)) else {
+                continue;
+            };
+            keys.extend(named.iter().copied().filter(|key| {
+                Some(*key) != token_key
+                    && pit
+                        .entries
+                        .get(*key)
+                        .is_some_and(|entry| entry.interest.matches_data(data))
+            }));
+        }
+
+        pit.remove(keys)
+    }
+}
+
+impl Pit {
+    fn remove(&mut self, keys: impl IntoIterator<Item = PitKey>) -> Vec<PitEntry> {
+        keys.into_iter()
+            .filter_map(|key| self.entries.remove(key))
+            .inspect(|entry| {
+                let selectors = Selectors::from(&entry.interest);
+                if let Some(key) = self.index.remove(&selectors) {
+                    let name = &entry.interest.name;
+                    if let Some(named) = self.names.get_mut(name) {
+                        named.retain(|named| *named != key);
+                        if named.is_empty() {
+                            self.names.remove(name);
+                        }
+                    }
+                }
+                for downstream in entry.downstreams.keys() {
+                    if let Some(count) = self.per_face.get_mut(downstream) {
+                        *count -= 1;
+                        if *count == 0 {
+                            self.per_face.remove(downstream);
+                        }
+                    }
+                }
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn satisfy_by_token() {
+        let pit = PendingInterestTable::default();
+        let downstream = face::FaceId::from(7);
+        let downstream_token = lpv2::PitToken::new(&b"abc"[..]);
+        let interest = Interest::new("/a/b");
+
+        let (token, arrival) = pit
+            .register(&interest, downstream, Some(downstream_token.clone()))
+            .await
+            .unwrap();
+        assert_eq!(arrival, Arrival::New);
+        let satisfied = pit.satisfy(&Data::new("/a/b"), Some(&token)).await;
+        assert_eq!(satisfied.len(), 1);
+        assert_eq!(
+            satisfied[0].downstreams[&downstream],
+            Some(downstream_token)
+        );
+
+        // Entry is gone
+        assert!(pit
+            .satisfy(&Data::new("/a/b"), Some(&token))
+            .await
+            .is_empty());
+    }
+
+    #[tokio::test]
+    async fn fallback_to_name_matching() {
+        let pit = PendingInterestTable::default();
+        let downstream = face::FaceId::from(7);
+        let prefix = Interest::new("/a").can_be_prefix();
+        let other = Interest::new("/x");
+
+        pit.register(&prefix, downstream, None).await.unwrap();
+        let (other_token, _) = pit.register(&other, downstream, None).await.unwrap();
+
+        // Token pointing to an entry the Data does not match
+        let satisfied = pit.satisfy(&Data::new("/a/b"), Some(&other_token)).await;
+        assert_eq!(satisfied.len(), 1);
+
+        // Malformed token
+        pit.register(&prefix, downstream, None).await.unwrap();
+        let garbage = lpv2::PitToken::new(&b"?"[..]);
+        let satisfied = pit.satisfy(&Data::new("/a/c"), Some(&garbage)).await;
+        assert_eq!(satisfied.len(), 1);
+
+        // No token at all
+        let satisfied = pit.satisfy(&Data::new("/x"), None).await;
+        assert_eq!(satisfied.len(), 1);
+        assert!(pit.satisfy(&Data::new("/x"), None).await.is_empty());
+    }
+
+    #[tokio::test]
+    async fn satisfy_all_matching() {
+        let pit = PendingInterestTable::default();
+        let downstream = face::FaceId::from(7);
+        let prefix = Interest::new("/a").can_be_prefix();
+        let exact = Interest::new("/a/b");
+        let fresh = Interest::new("/a/b").must_be_fresh();
+        let other = Interest::new("/a/c");
+
+        for interest in [&prefix, &fresh, &other] {
+            pit.register(interest, downstream, None).await.unwrap();
+        }
+        let (token, _) = pit.register(&exact, downstream, None).await.unwrap();
+
+        // The token leads to its own entry, the other matching ones go along
+        let satisfied = pit.satisfy(&Data::new("/a/b"), Some(&token)).await;
+        assert_eq!(satisfied.len(), 3);
+        assert_eq!(satisfied[0].interest, exact);
+        assert_eq!(pit.len().await, 1);
+        assert!(pit.satisfy(&Data::new("/a/b"), None).await.is_empty());
+        assert_eq!(pit.satisfy(&Data::new("/a/c"), None).await.len(), 1);
+        assert!(pit.pit.read().await.names.is_empty());
+    }
+
+    #[tokio::test]
+    async fn retransmission() {
+        let pit = PendingInterestTable::default();
+        let downstream = face::FaceId::from(7);
+        let other = face::FaceId::from(8);
+        let interest = Interest::new("/a/b");
+        let retransmitted = Interest::new("/a/b");
+        let fresh = Interest::new("/a/b").must_be_fresh();
+
+        let (token, arrival) = pit.register(&interest, downstream, None).await.unwrap();
+        assert_eq!(arrival, Arrival::New);
+        let (retx_token, arrival) = pit
+            .register(&retransmitted, downstream, None)
+            .await
+            .unwrap();
+        assert_eq!(arrival, Arrival::Retransmission);
+        assert_eq!(retx_token, token);
+
+        // Same Nonce coming back from elsewhere
+        let (_, arrival) = pit.register(&interest, other, None).await.unwrap();
+        assert_eq!(arrival, Arrival::DuplicateNonce);
+
+        // Different selectors make a different entry
+        let (_, arrival) = pit.register(&fresh, downstream, None).await.unwrap();
+        assert_eq!(arrival, Arrival::New);
+        assert_eq!(pit.len().await, 2);
+
+        let satisfied = pit.satisfy(&Data::new("/a/b"), Some(&token)).await;
+        assert_eq!(satisfied[0].downstreams.len(), 1);
+        assert_eq!(satisfied[0].nonces.len(), 2);
+    }
+
+    #[tokio::test]
+    async fn limits() {
+        let pit = PendingInterestTable::default().with_limits(Some(3), Some(2));
+        let first = face::FaceId::from(7);
+        let second = face::FaceId::from(8);
+
+        pit.register(&Interest::new("/a"), first, None)
+            .await
+            .unwrap();
+        pit.register(&Interest::new("/b"), first, None)
+            .await
+            .unwrap();
+        let exceeded = pit.register(&Interest::new("/c"), first, None).await;
+        assert_eq!(exceeded, Err(LimitExceeded::PitEntriesPerFace));
+
+        // Retransmission from the same face does not count as a new entry
+        assert!(pit
+            .register(&Interest::new("/a"), first, None)
+            .await
+            .is_ok());
+
+        pit.register(&Interest::new("/c"), second, None)
+            .await
+            .unwrap();
+        let exceeded = pit.register(&Interest::new("/d"), second, None).await;
+        assert_eq!(exceeded, Err(LimitExceeded::PitEntries));
+
+        // Joining an existing entry is still fine
+        assert!(pit
+            .register(&Interest::new("/a"), second, None)
+            .await
+            .is_ok());
+        assert_eq!(pit.n_face_entries(second).await, 2);
+
+        pit.satisfy(&Data::new("/a"), None).await;
+        assert_eq!(pit.n_face_entries(first).await, 1);
+        assert_eq!(pit.n_face_entries(second).await, 1);
+    }
+
+    #[tokio::test]
+    async fn nack() {
+        let pit = PendingInterestTable::default();
+        let downstream = face::FaceId::from(7);
+        let (first, second) = (face::FaceId::from(8), face::FaceId::from(9));
+        let interest = Interest::new("/a");
+
+        let (token, _) = pit.register(&interest, downstream, None).await.unwrap();
+        pit.add_out_record(&token, first).await;
+        pit.add_out_record(&token, second).await;
+
+        // Pending for as long as an upstream may still answer
+        let reason = lpv2::NackReason::NoRoute;
+        assert!(pit.nack(&token, &interest, first, reason).await.is_none());
+        let stranger = face::FaceId::from(10);
+        let reason = lpv2::NackReason::None;
+        assert!(pit
+            .nack(&token, &interest, stranger, reason)
+            .await
+            .is_none());
+        assert_eq!(pit.len().await, 1);
+
+        let reason = lpv2::NackReason::Congestion;
+        let (entry, reason) = pit.nack(&token, &interest, second, reason).await.unwrap();
+        assert!(entry.downstreams.contains_key(&downstream));
+        assert_eq!(reason, lpv2::NackReason::Congestion);
+        assert!(pit.is_empty().await);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn expire() {
+        let pit = PendingInterestTable::default();
+        let downstream = face::FaceId::from(7);
+        let upstream = face::FaceId::from(8);
+        let short = Interest::new("/short").with_interest_lifetime(100);
+        let long = Interest::new("/long");
+
+        let (token, _) = pit.register(&short, downstream, None).await.unwrap();
+        pit.add_out_record(&token, upstream).await;
+        pit.register(&long, downstream, None).await.unwrap();
+
+        time::sleep(Duration::from_millis(100)).await;
+        let expired = pit.expire().await;
+        assert_eq!(expired.len(), 1);
+        assert_eq!(expired[0].interest, short);
+        assert!(expired[0].upstreams.contains_key(&upstream));
+        assert_eq!(pit.len().await, 1);
+
+        time::sleep(PendingInterestTable::DEFAULT_INTEREST_LIFETIME).await;
+        assert_eq!(pit.expire().await.len(), 1);
+        assert!(pit.is_empty().await);
     }
 }
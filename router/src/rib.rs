@@ -0,0 +1,242 @@
+//! Routing Information Base, the routes the FIB nexthops are computed from.
+//!
+//! Each route is identified by its prefix, face and origin. The FIB gets the lowest cost
+//! route of each face, and loses the nexthop once the last route of the face is gone.
+
+use std::fmt;
+use std::time::Duration;
+
+use super::*;
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Route {
+    pub face: face::FaceId,
+    pub origin: mgmt::Origin,
+    pub cost: u64,
+    /// routes without expiration last until removed
+    pub expires: Option<Instant>,
+    /// announcement the route has been installed from, passed on along the Data
+    pub announcement: Option<tlv::PrefixAnnouncement>,
+}
+
+impl Route {
+    pub fn new(face: face::FaceId, origin: mgmt::Origin, cost: u64) -> Self {
+        Self {
+            face,
+            origin,
+            cost,
+            expires: None,
+            announcement: None,
+        }
+    }
+
+    /// The same route, removed `lifetime` after `now`
+    pub fn with_lifetime(self, lifetime: Duration, now: Instant) -> Self {
+        let expires = Some(now + lifetime);
+        Self { expires, ..self }
+    }
+
+    pub fn with_announcement(self, announcement: tlv::PrefixAnnouncement) -> Self {
+        let announcement = Some(announcement);
+        Self {
+            announcement,
+            ..self
+        }
+    }
+
+    fn is_expired(&self, now: Instant) -> bool {
+        self.expires.is_some_and(|expires| expires <= now)
+    }
+}
+
+/// Checks the PrefixAnnouncement received along the Data before its route is installed,
+/// e.g. its signature against the trust schema of the deployment
+pub trait AnnouncementValidator: fmt::Debug + Send + Sync {
+    fn is_trusted(&self, announcement: &tlv::PrefixAnnouncement, upstream: face::FaceId) -> bool;
+}
+
+/// Trusts every announcement, for closed deployments where all the routers are trusted
+#[derive(Debug, Default)]
+pub struct TrustAllAnnouncements;
+
+impl AnnouncementValidator for TrustAllAnnouncements {
+    fn is_trusted(&self, announcement: &tlv::PrefixAnnouncement, upstream: face::FaceId) -> bool {
+        let _ = (announcement, upstream);
+        true
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct RoutingInformationBase {
+    routes: RwLock<HashMap<tlv::Name, Vec<Route>>>,
+}
+
+impl RoutingInformationBase {
+    /// Add or replace the route of the same face and origin, updating the FIB
+    pub async fn add_route(
+        &self,
+        prefix: tlv::Name,
+        route: Route,
+        fib: &ForwardingInformationBase,
+    ) {
+        let face = route.face;
+        let mut routes = self.routes.write().await;
+        let entry = routes.entry(prefix.clone()).or_default();
+        entry.retain(|existing| (existing.face, existing.origin) != (route.face, route.origin));
+        entry.push(route);
+        let cost = Self::nexthop_cost(&routes, &prefix, face);
+        drop(routes);
+        Self::update_nexthop(fib, prefix, face, cost).await;
+    }
+
+    /// Remove the route of `face` and `origin`, updating the FIB
+    pub async fn remove_route(
+        &self,
+        prefix: &tlv::Name,
+        face: face::FaceId,
+        origin: mgmt::Origin,
+        fib: &ForwardingInformationBase,
+    ) {
+        let mut routes = self.routes.write().await;
+        let Some(entry) = routes.get_mut(prefix) else {
+            return;
+        };
+        entry.retain(|route| (route.face, route.origin) != (face, origin));
+        if entry.is_empty() {
+            routes.remove(prefix);
+        }
+        let cost = Self::nexthop_cost(&routes, prefix, face);
+        drop(routes);
+        Self::update_nexthop(fib, prefix.clone(), face, cost).await;
+    }
+
+    pub async fn routes(&self, prefix: &tlv::Name) -> Vec<Route> {
+        let routes = self.routes.read().await;
+        routes.get(prefix).cloned().unwrap_or_default()
+    }
+
+    pub async fn len(&self) -> usize {
+        self.routes.read().await.len()
+    }
+
+    pub async fn is_empty(&self) -> bool {
+        self.routes.read().await.is_empty()
+    }
+
+    /// Announcement of the longest prefix of `name` routed to `face`, if any
+    pub async fn find_announcement(
+        &self,
+        name: &tlv::Name,
+        face: face::FaceId,
+    ) -> Option<tlv::PrefixAnnouncement> {
+        let routes = self.routes.read().await;
+        (0..=name.len()).rev().find_map(|len| {
+            routes
+                .get(&name.prefix(len))?
+                .iter()
+                .filter(|route| route.face == face)
+                .find_map(|route| route.announcement.clone())
+        })
+    }
+
+    /// Drop the routes that have expired by `now`, updating the FIB
+    pub async fn expire(&self, now: Instant, fib: &ForwardingInformationBase) {
+        let mut routes = self.routes.write().await;
+        let mut expired = Vec::new();
+        for (prefix, entry) in routes.iter_mut() {
+            entry.retain(|route| {
+                let is_expired = route.is_expired(now);
+                if is_expired {
+                    let (face, origin) = (route.face, route.origin);
+                    tracing::debug!(%prefix, %face, %origin, "Route expired");
+                    expired.push((prefix.clone(), route.face));
+                }
+                !is_expired
+            });
+        }
+        routes.retain(|_, entry| !entry.is_empty());
+        let updates = expired
+            .into_iter()
+            .map(|(prefix, face)| {
+                let cost = Self::nexthop_cost(&routes, &prefix, face);
+                (prefix, face, cost)
+            })
+            .collect::<Vec<_>>();
+        drop(routes);
+
+        for (prefix, face, cost) in updates {
+            Self::update_nexthop(fib, prefix, face, cost).await;
+        }
+    }
+
+    /// Cost of the `face` nexthop of `prefix`, the lowest of its routes, if any is left
+    fn nexthop_cost(
+        routes: &HashMap<tlv::Name, Vec<Route>>,
+        prefix: &tlv::Name,
+        face: face::FaceId,
+    ) -> Option<u64> {
+        routes
+            .get(prefix)
+            .into_iter()
+            .flatten()
+            .filter(|route| route.face == face)
+            .map(|route| route.cost)
+            .min()
+    }
+
+    /// Apply the nexthop computed under the RIB lock to the FIB, once the lock is released
+    async fn update_nexthop(
+        fib: &ForwardingInformationBase,
+        prefix: tlv::Name,
+        face: face::FaceId,
+        cost: Option<u64>,
+    ) {
+        if let Some(cost) = cost {
+            fib.add_nexthop(prefix, face, cost).await;
+        } else {
+            fib.remove_nexthop(&prefix, face).await;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test(start_paused = true)]
+    async fn routes_update_fib() {
+        let rib = RoutingInformationBase::default();
+        let fib = ForwardingInformationBase::default();
+        let prefix: tlv::Name = "/ucla".parse().unwrap();
+        let face = face::FaceId::from(1);
+        let lookup = || async {
+            let entry = fib.longest_prefix_match(&prefix).await?;
+            Some(
+                entry
+                    .nexthops
+                    .iter()
+                    .map(|nexthop| nexthop.cost)
+                    .collect::<Vec<_>>(),
+            )
+        };
+
+        let announced = Route::new(face, mgmt::Origin::PrefixAnn, 20)
+            .with_lifetime(Duration::from_secs(10), now());
+        rib.add_route(prefix.clone(), announced, &fib).await;
+        let route = Route::new(face, mgmt::Origin::Static, 10);
+        rib.add_route(prefix.clone(), route, &fib).await;
+        assert_eq!(lookup().await, Some(vec![10]));
+
+        // The announced route takes over once the static one is gone
+        rib.remove_route(&prefix, face, mgmt::Origin::Static, &fib)
+            .await;
+        assert_eq!(lookup().await, Some(vec![20]));
+
+        rib.expire(now(), &fib).await;
+        assert_eq!(rib.len().await, 1);
+        time::advance(Duration::from_secs(10)).await;
+        rib.expire(now(), &fib).await;
+        assert!(rib.is_empty().await);
+        assert_eq!(lookup().await, None);
+    }
+}
@@ -0,0 +1,381 @@
+//! Forwarding tables split into shards by a hash of the name prefix, as in NDN-DPDK.
+//!
+//! Each shard owns the PIT and Content Store of its part of the namespace. Measurements are
+//! kept router-wide, since a FIB prefix shorter than the sharding prefix spans several shards.
+//! Faces served by the router forward their packets in place under the owning shard,
+//! a [`Dispatcher`] hands them over to a worker task per shard instead.
+//! Data finds the shard its Interest went through by the shard number carried in the PitToken,
+//! and by its own name prefix when the upstream did not echo the token back.
+//!
+//! Names shorter than the sharding prefix hash on their own, so a CanBePrefix Interest may
+//! live in another shard than the Data under it. Data is cached by the shard owning its name,
+//! such Interests look into the Content Store of every shard, and Data coming back without
+//! a PitToken is matched against the PITs of the shards owning its shorter prefixes as well.
+
+use std::hash::{DefaultHasher, Hash, Hasher};
+
+use tokio::sync::mpsc;
+
+use super::*;
+
+/// Forwarding tables of the namespaces whose name prefix hashes to this shard
+#[derive(Debug)]
+pub struct Shard {
+    id: u8,
+    pending_interest_table: PendingInterestTable,
+    content_store: ContentStore,
+}
+
+impl Shard {
+    /// Shards are numbered with a single octet
+    pub const MAX_SHARDS: usize = 256;
+
+    /// Name components hashed to pick the shard
+    pub const DEFAULT_PREFIX_LEN: usize = 2;
+
+    /// `n_shards` shards splitting the PIT and Content Store `limits` evenly
+    pub(crate) fn build(n_shards: usize, limits: &ResourceLimits) -> Vec<Self> {
+        let n_shards = n_shards.clamp(1, Self::MAX_SHARDS);
+        let share = |max: Option<usize>| max.map(|max| max.div_ceil(n_shards));
+        (0..n_shards)
+            .filter_map(|id| u8::try_from(id).ok())
+            .map(|id| Self {
+                id,
+                pending_interest_table: PendingInterestTable::default().with_limits(
+                    share(limits.max_pit_entries),
+                    share(limits.max_pit_entries_per_face),
+                ),
+                content_store: ContentStore::default().with_max_bytes(share(limits.max_cs_bytes)),
+            })
+            .collect()
+    }
+
+    pub fn id(&self) -> usize {
+        self.id.into()
+    }
+
+    pub fn pending_interest_table(&self) -> &PendingInterestTable {
+        &self.pending_interest_table
+    }
+
+    pub fn content_store(&self) -> &ContentStore {
+        &self.content_store
+    }
+
+    /// PitToken sent upstream, the shard PIT token prefixed with the shard number
+    pub(crate) fn tag(&self, token: &lpv2::PitToken) -> lpv2::PitToken {
+        let mut bytes = Vec::with_capacity(token.as_bytes().len() + 1);
+        bytes.push(self.id);
+        bytes.extend_from_slice(token.as_bytes());
+        lpv2::PitToken::new(bytes)
+    }
+
+    /// Shard PIT token out of the PitToken echoed by upstream, if it belongs to this shard
+    pub(crate) fn untag(&self, token: &lpv2::PitToken) -> Option<lpv2::PitToken> {
+        let (id, token) = token.as_bytes().split_first()?;
+        (*id == self.id).then(|| lpv2::PitToken::new(token.to_vec()))
+    }
+}
+
+/// Shard number carried by the PitToken echoed by upstream
+pub(crate) fn tagged_shard(token: &lpv2::PitToken) -> Option<usize> {
+    token.as_bytes().first().map(|id| usize::from(*id))
+}
+
+/// Shard owning `name` out of `n_shards`, decided by its first `prefix_len` components
+pub(crate) fn shard_of(name: &tlv::Name, prefix_len: usize, n_shards: usize) -> usize {
+    let mut hasher = DefaultHasher::new();
+    name.prefix(prefix_len.min(name.len())).hash(&mut hasher);
+    let hash = hasher.finish() % n_shards.max(1) as u64;
+    usize::try_from(hash).unwrap_or_default()
+}
+
+/// Network layer packet decoded off an LpPacket
+#[derive(Debug)]
+pub(crate) enum NetworkPacket {
+    Interest(Interest),
+    Nack(Interest, lpv2::Nack),
+    Data(Data),
+}
+
+impl NetworkPacket {
+    pub(crate) fn decode(packet: &lpv2::LpPacket) -> io::Result<Option<Self>> {
+        let Some(generic) = packet.network_packet() else {
+            return Ok(None);
+        };
+
+        let decoded = match generic.r#type {
+            tlv::Type::Interest => {
+                let interest = Interest::decode_from_generic(generic).map_err(io::Error::from)?;
+                if let Some(nack) = packet.nack {
+                    Some(Self::Nack(interest, nack))
+                } else {
+                    Some(Self::Interest(interest))
+                }
+            }
+            tlv::Type::Data => {
+                let data = Data::decode_from_generic(generic).map_err(io::Error::from)?;
+                Some(Self::Data(data))
+            }
+            other => {
+                tracing::debug!(r#type = %other, "Dropping unsupported network layer packet");
+                None
+            }
+        };
+        Ok(decoded)
+    }
+
+    pub(crate) fn name(&self) -> &tlv::Name {
+        match self {
+            Self::Interest(interest) | Self::Nack(interest, _) => &interest.name,
+            Self::Data(data) => &data.name,
+        }
+    }
+}
+
+#[derive(Debug)]
+struct Work {
+    packet: NetworkPacket,
+    face: face::FaceId,
+    header: lpv2::LpPacket,
+}
+
+/// Hands the packets over to the worker task of the owning shard
+#[derive(Clone, Debug)]
+pub struct Dispatcher {
+    router: Arc<Router>,
+    workers: Vec<mpsc::Sender<Work>>,
+}
+
+impl Dispatcher {
+    /// Packets each worker buffers before pushing back on the faces
+    pub const DEFAULT_QUEUE_CAPACITY: usize = 1024;
+
+    /// Spawn a worker task for each shard of `router`
+    pub fn spawn(router: Arc<Router>, queue_capacity: usize) -> Self {
+        let workers = (0..router.n_shards())
+            .map(|id| {
+                let (tx, mut rx) = mpsc::channel::<Work>(queue_capacity);
+                let router = Arc::clone(&router);
+                tokio::spawn(async move {
+                    while let Some(Work {
+                        packet,
+                        face,
+                        header,
+                    }) = rx.recv().await
+                    {
+                        if let Err(error) = router.forward(id, packet, face, &header).await {
+                            tracing::debug!(shard = id, %face, %error, "Forwarding failed");
+                        }
+                    }
+                });
+                tx
+            })
+            .collect();
+        Self { router, workers }
+    }
+
+    pub fn router(&self) -> &Arc<Router> {
+        &self.router
+    }
+
+    /// Hand `packet` received from `face` over to the owning shard
+    pub async fn dispatch(&self, packet: lpv2::LpPacket, face: face::FaceId) -> io::Result<()> {
+        let Some(decoded) = NetworkPacket::decode(&packet)? else {
+            return Ok(());
+        };
+        let shard = self.router.shard_for(&decoded, &packet);
+        let worker = self
+            .workers
+            .get(shard)
+            .ok_or_else(|| io::Error::other("No worker for shard"))?;
+        let work = Work {
+            packet: decoded,
+            face,
+            header: packet,
+        };
+        worker
+            .send(work)
+            .await
+            .map_err(|_| io::Error::other("Shard worker has stopped"))
+    }
+
+    /// Receive the packets of `face` and dispatch them until the face is closed
+    pub async fn serve_face(&self, face: face::FaceId) -> io::Result<()> {
+        loop {
+            let packet = self.router.recv_packet(face).await?;
+            if let Err(error) = self.dispatch(packet, face).await {
+                tracing::debug!(%face, %error, "Dropping packet");
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::*;
+
+    #[test]
+    fn tokens() {
+        let shards = Shard::build(4, &ResourceLimits::default());
+        let token = lpv2::PitToken::from(42);
+        let [first, second, ..] = shards.as_slice() else {
+            panic!("Expected 4 shards");
+        };
+
+        let tagged = second.tag(&token);
+        assert_eq!(tagged_shard(&tagged), Some(1));
+        assert_eq!(second.untag(&tagged), Some(token));
+        assert_eq!(first.untag(&tagged), None);
+    }
+
+    #[tokio::test]
+    async fn limits_are_shared() {
+        let limits = ResourceLimits {
+            max_pit_entries: Some(3),
+            ..ResourceLimits::default()
+        };
+        assert_eq!(Shard::build(0, &limits).len(), 1);
+
+        // Each of the 2 shards gets room for 2 entries
+        let shards = Shard::build(2, &limits);
+        let pit = shards[0].pending_interest_table();
+        let face = face::FaceId::from(1);
+        for name in ["/a", "/b"] {
+            assert!(pit.register(&Interest::new(name), face, None).await.is_ok());
+        }
+        let exceeded = pit.register(&Interest::new("/c"), face, None).await;
+        assert_eq!(exceeded.unwrap_err(), LimitExceeded::PitEntries);
+    }
+
+    #[tokio::test]
+    async fn dispatch_by_name_prefix() {
+        let router = Router::with_internal_face()
+            .await
+            .unwrap()
+            .with_shards(4, 1);
+        let face = router.faces.get_faces().await[0];
+        let router = Arc::new(router);
+        let dispatcher = Dispatcher::spawn(Arc::clone(&router), 16);
+        // Producer never answering keeps the Interests pending
+        router
+            .register_producer("/".parse().unwrap(), |_| async { None })
+            .await
+            .unwrap();
+
+        let names = ["/a/1", "/a/2", "/b/1", "/c/1", "/d/1", "/e/1"];
+        for name in names {
+            let packet = lpv2::LpPacket::from_tlv(Interest::new(name));
+            dispatcher.dispatch(packet, face).await.unwrap();
+        }
+
+        let pending = async {
+            loop {
+                let mut total = 0;
+                for shard in router.shards() {
+                    total += shard.pending_interest_table().len().await;
+                }
+                if total == names.len() {
+                    break;
+                }
+                tokio::task::yield_now().await;
+            }
+        };
+        time::timeout(Duration::from_secs(5), pending)
+            .await
+            .unwrap();
+
+        // Interests sharing the first component end up in the same shard
+        let owner = |name: &str| shard_of(&name.parse().unwrap(), 1, 4);
+        assert_eq!(owner("/a/1"), owner("/a/2"));
+        for shard in router.shards() {
+            let expected = names
+                .iter()
+                .filter(|name| owner(name) == shard.id())
+                .count();
+            assert_eq!(shard.pending_interest_table().len().await, expected);
+        }
+
+        // Data is routed back to the shard by its PitToken
+        let data = Data::new("/a/1");
+        let owner = owner("/a/1");
+        let token = router.shards()[owner].tag(&lpv2::PitToken::from(0));
+        let packet = lpv2::LpPacket::from_tlv(data).with_pit_token(Some(token));
+        let decoded = NetworkPacket::decode(&packet).unwrap().unwrap();
+        assert_eq!(router.shard_for(&decoded, &packet), owner);
+    }
+
+    #[tokio::test]
+    async fn short_can_be_prefix() {
+        let router = Arc::new(Router::new().with_shards(16, 2));
+        let n_interests = Arc::new(AtomicU64::new(0));
+        let producer = router
+            .register_producer("/a".parse().unwrap(), {
+                let n_interests = Arc::clone(&n_interests);
+                move |_| {
+                    n_interests.fetch_add(1, Ordering::Relaxed);
+                    async { Some(Data::new("/a/b/c")) }
+                }
+            })
+            .await
+            .unwrap();
+        let owner = |name: &str| router.shard_of(&name.parse().unwrap());
+        assert_ne!(owner("/a"), owner("/a/b/c"));
+
+        let data = router
+            .express_interest(Interest::new("/a").can_be_prefix())
+            .await
+            .unwrap();
+        assert_eq!(data.name.to_string(), "/a/b/c");
+        assert_eq!(n_interests.load(Ordering::Relaxed), 1);
+
+        // Cached under the Data name, found by both the short and the exact Interests
+        assert_eq!(
+            router.shards()[owner("/a/b/c")].content_store().len().await,
+            1
+        );
+        for interest in [Interest::new("/a").can_be_prefix(), Interest::new("/a/b/c")] {
+            let data = router.express_interest(interest).await.unwrap();
+            assert_eq!(data.name.to_string(), "/a/b/c");
+        }
+        assert_eq!(n_interests.load(Ordering::Relaxed), 1);
+
+        // Data without a PitToken finds the Interest in the shard of the short name
+        let pit = router.shards()[owner("/x")].pending_interest_table();
+        let interest = Interest::new("/x").can_be_prefix();
+        pit.register(&interest, producer, None).await.unwrap();
+        router
+            .handle_data(Data::new("/x/y/z"), producer)
+            .await
+            .unwrap();
+        assert!(pit.is_empty().await);
+    }
+
+    #[tokio::test]
+    async fn measurements_span_shards() {
+        let prefix: tlv::Name = "/a".parse().unwrap();
+        let strategy = Asf::new(AsfOptions::default());
+        let strategy_choice = StrategyChoice::default().with_strategy(prefix.clone(), strategy);
+        let router = Arc::new(
+            Router::new()
+                .with_strategy_choice(strategy_choice)
+                .with_shards(16, 2),
+        );
+        router
+            .register_producer(prefix, |interest: Interest| async move {
+                Some(Data::new(interest.name.to_string()))
+            })
+            .await
+            .unwrap();
+        let owner = |name: &str| router.shard_of(&name.parse().unwrap());
+        assert_ne!(owner("/a/b/1"), owner("/a/c/1"));
+
+        // Both namespaces feed the single measurements entry of the FIB prefix
+        for name in ["/a/b/1", "/a/c/1"] {
+            router.express_interest(Interest::new(name)).await.unwrap();
+        }
+        assert_eq!(router.n_measurements_entries().await, 1);
+    }
+}
@@ -0,0 +1,448 @@
+//! In-process network of routers for reproducible forwarding tests.
+//!
+//! Routers are connected by emulated links with delay, bandwidth, loss and reordering.
+//! Each link direction draws from its own random generator seeded by the simulation seed,
+//! so under tokio's paused clock the same scenario always plays out the same way.
+//! Every packet crossing a link is recorded in the simulation traces.
+
+use std::fmt;
+use std::time::Duration;
+
+use bytes::BytesMut;
+use rand::rngs::StdRng;
+use rand::Rng;
+use rand::SeedableRng;
+use tlv::TlvCodec;
+use tokio::sync::Mutex;
+
+use super::*;
+
+/// Index of a router in the simulation
+pub type NodeId = usize;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct LinkOptions {
+    /// propagation delay
+    pub delay: Duration,
+    /// bits per second, unlimited if `None`
+    pub bandwidth: Option<u64>,
+    /// probability of a packet being lost
+    pub loss: f64,
+    /// probability of a packet being held back by up to another `delay`,
+    /// letting the packets sent after it overtake
+    pub reordering: f64,
+}
+
+impl LinkOptions {
+    pub fn with_delay(self, delay: Duration) -> Self {
+        Self { delay, ..self }
+    }
+
+    pub fn with_bandwidth(self, bandwidth: u64) -> Self {
+        let bandwidth = Some(bandwidth);
+        Self { bandwidth, ..self }
+    }
+
+    pub fn with_loss(self, loss: f64) -> Self {
+        Self { loss, ..self }
+    }
+
+    pub fn with_reordering(self, reordering: f64) -> Self {
+        Self { reordering, ..self }
+    }
+
+    fn transmission_time(&self, size: usize) -> Duration {
+        self.bandwidth
+            .filter(|bandwidth| *bandwidth > 0)
+            .map_or(Duration::ZERO, |bandwidth| {
+                Duration::from_secs_f64(size as f64 * 8.0 / bandwidth as f64)
+            })
+    }
+}
+
+impl Default for LinkOptions {
+    fn default() -> Self {
+        Self {
+            delay: Duration::from_millis(10),
+            bandwidth: None,
+            loss: 0.0,
+            reordering: 0.0,
+        }
+    }
+}
+
+/// Faces of both ends of a link
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Link {
+    pub a: NodeId,
+    pub a_face: face::FaceId,
+    pub b: NodeId,
+    pub b_face: face::FaceId,
+}
+
+impl Link {
+    /// Face of `node` on this link
+    pub fn face(&self, node: NodeId) -> Option<face::FaceId> {
+        if node == self.a {
+            Some(self.a_face)
+        } else if node == self.b {
+            Some(self.b_face)
+        } else {
+            None
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TraceEvent {
+    Sent,
+    Lost,
+    Delivered,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PacketKind {
+    Interest,
+    Data,
+    Nack,
+    Other,
+}
+
+/// Packet crossing a link
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Trace {
+    /// since the start of the simulation
+    pub time: Duration,
+    pub from: NodeId,
+    pub to: NodeId,
+    pub event: TraceEvent,
+    pub kind: PacketKind,
+    pub name: Option<tlv::Name>,
+    pub size: usize,
+}
+
+impl fmt::Display for Trace {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{:>10?} {} -> {} {:?} {:?}",
+            self.time, self.from, self.to, self.event, self.kind
+        )?;
+        if let Some(name) = &self.name {
+            write!(f, " {name}")?;
+        }
+        write!(f, " ({} octets)", self.size)
+    }
+}
+
+type Traces = Arc<Mutex<Vec<Trace>>>;
+
+#[derive(Debug)]
+pub struct Simulation {
+    seed: u64,
+    start: time::Instant,
+    routers: Vec<Arc<Router>>,
+    links: Vec<Link>,
+    traces: Traces,
+}
+
+impl Simulation {
+    /// How often the routers expire their PIT entries
+    pub const TIMEOUT_INTERVAL: Duration = Duration::from_millis(100);
+
+    pub fn new(seed: u64) -> Self {
+        Self {
+            seed,
+            start: time::Instant::now(),
+            routers: Vec::new(),
+            links: Vec::new(),
+            traces: Traces::default(),
+        }
+    }
+
+    pub fn add_router(&mut self, router: Router) -> NodeId {
+        let router = Arc::new(router);
+        let node = self.routers.len();
+        self.routers.push(Arc::clone(&router));
+        tokio::spawn(async move {
+            let mut interval = time::interval(Self::TIMEOUT_INTERVAL);
+            loop {
+                interval.tick().await;
+                router.handle_timeouts().await;
+            }
+        });
+        node
+    }
+
+    pub fn router(&self, node: NodeId) -> Option<&Arc<Router>> {
+        self.routers.get(node)
+    }
+
+    pub fn links(&self) -> &[Link] {
+        &self.links
+    }
+
+    /// Connect routers `a` and `b` by a link with the same `options` in both directions
+    pub async fn connect(
+        &mut self,
+        a: NodeId,
+        b: NodeId,
+        options: LinkOptions,
+    ) -> io::Result<Link> {
+        let (Some(router_a), Some(router_b)) = (self.router(a), self.router(b)) else {
+            return Err(io::Error::other("Router not found"));
+        };
+        let (router_a, router_b) = (Arc::clone(router_a), Arc::clone(router_b));
+
+        let (face_a, end_a) = transport::Internal::pair();
+        let (face_b, end_b) = transport::Internal::pair();
+        let a_face = router_a.attach_face(link_face(face_a)?).await;
+        let b_face = router_b.attach_face(link_face(face_b)?).await;
+
+        let index = self.links.len() as u64;
+        let (end_a, end_b) = (Arc::new(end_a), Arc::new(end_b));
+        for (from, to, from_end, to_end, direction) in [
+            (a, b, Arc::clone(&end_a), Arc::clone(&end_b), 0),
+            (b, a, end_b, end_a, 1),
+        ] {
+            let seed = self.seed ^ (index << 1 | direction);
+            let channel = Channel {
+                from,
+                to,
+                options,
+                rng: StdRng::seed_from_u64(seed),
+                start: self.start,
+                traces: Arc::clone(&self.traces),
+            };
+            tokio::spawn(channel.run(from_end, to_end));
+        }
+
+        let link = Link {
+            a,
+            a_face,
+            b,
+            b_face,
+        };
+        self.links.push(link);
+        Ok(link)
+    }
+
+    pub async fn traces(&self) -> Vec<Trace> {
+        self.traces.lock().await.clone()
+    }
+}
+
+fn link_face(transport: transport::Internal) -> io::Result<Face> {
+    let uri = face::Uri::from(face::Internal::PREFIX);
+    let face = Face::with_transport(uri, face::FacePersistency::Permanent, transport)?;
+    Ok(face.update_face_scope(face::FaceScope::NonLocal))
+}
+
+/// One direction of a link
+#[derive(Debug)]
+struct Channel {
+    from: NodeId,
+    to: NodeId,
+    options: LinkOptions,
+    rng: StdRng,
+    start: time::Instant,
+    traces: Traces,
+}
+
+impl Channel {
+    async fn run(mut self, from_end: Arc<transport::Internal>, to_end: Arc<transport::Internal>) {
+        // time the link is done transmitting the packets accepted so far
+        let mut idle_at = time::Instant::now();
+        while let Ok(bytes) = from_end.recv().await {
+            let now = time::Instant::now();
+            self.trace(TraceEvent::Sent, &bytes, now).await;
+            if self.rng.gen_bool(self.options.loss.clamp(0.0, 1.0)) {
+                self.trace(TraceEvent::Lost, &bytes, now).await;
+                continue;
+            }
+
+            idle_at = idle_at.max(now) + self.options.transmission_time(bytes.len());
+            let mut arrival = idle_at + self.options.delay;
+            if self.rng.gen_bool(self.options.reordering.clamp(0.0, 1.0)) {
+                arrival += self.options.delay.mul_f64(self.rng.gen());
+            }
+
+            let to_end = Arc::clone(&to_end);
+            let traces = Arc::clone(&self.traces);
+            let (from, to, start) = (self.from, self.to, self.start);
+            tokio::spawn(async move {
+                time::sleep_until(arrival).await;
+                let trace = trace(from, to, start, TraceEvent::Delivered, &bytes, arrival);
+                traces.lock().await.push(trace);
+                let _ = to_end.send(bytes).await;
+            });
+        }
+    }
+
+    async fn trace(&self, event: TraceEvent, bytes: &bytes::Bytes, now: time::Instant) {
+        let trace = trace(self.from, self.to, self.start, event, bytes, now);
+        self.traces.lock().await.push(trace);
+    }
+}
+
+fn trace(
+    from: NodeId,
+    to: NodeId,
+    start: time::Instant,
+    event: TraceEvent,
+    bytes: &bytes::Bytes,
+    now: time::Instant,
+) -> Trace {
+    let (kind, name) = summarize(bytes).unwrap_or((PacketKind::Other, None));
+    Trace {
+        time: now - start,
+        from,
+        to,
+        event,
+        kind,
+        name,
+        size: bytes.len(),
+    }
+}
+
+fn summarize(bytes: &bytes::Bytes) -> Option<(PacketKind, Option<tlv::Name>)> {
+    let generic = tlv::Generic::decode(&mut BytesMut::from(bytes.as_ref())).ok()?;
+    let packet = if generic.r#type == tlv::Type::LpPacket {
+        lpv2::LpPacket::decode_from_generic(generic).ok()?
+    } else {
+        lpv2::LpPacket::from_tlv(generic)
+    };
+    let generic = packet.network_packet()?;
+    let summary = match generic.r#type {
+        tlv::Type::Interest => {
+            let interest = Interest::decode_from_generic(generic).ok()?;
+            let kind = if packet.nack.is_some() {
+                PacketKind::Nack
+            } else {
+                PacketKind::Interest
+            };
+            (kind, Some(interest.name))
+        }
+        tlv::Type::Data => {
+            let data = Data::decode_from_generic(generic).ok()?;
+            (PacketKind::Data, Some(data.name))
+        }
+        _ => (PacketKind::Other, None),
+    };
+    Some(summary)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::future::Future;
+
+    use super::*;
+
+    /// Consumer on router 0, producer of `/hello` on router 2, routers chained by `options` links
+    async fn chain(options: LinkOptions) -> Simulation {
+        let mut sim = Simulation::new(7);
+        let nodes = [(); 3].map(|()| sim.add_router(Router::new()));
+        for pair in nodes.windows(2) {
+            let link = sim.connect(pair[0], pair[1], options).await.unwrap();
+            let router = sim.router(link.a).unwrap();
+            router
+                .forwarding_information_base()
+                .add_nexthop("/hello".parse().unwrap(), link.a_face, 0)
+                .await;
+        }
+        sim.router(2)
+            .unwrap()
+            .register_producer("/hello".parse().unwrap(), |interest: Interest| async move {
+                Some(Data::new(interest.name.to_string()))
+            })
+            .await
+            .unwrap();
+        sim
+    }
+
+    fn fetch(sim: &Simulation, name: &str) -> impl Future<Output = io::Result<Data>> {
+        let router = Arc::clone(sim.router(0).unwrap());
+        let interest = Interest::new(name).with_interest_lifetime(1000);
+        async move { router.express_interest(interest).await }
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn round_trip() {
+        let sim = chain(LinkOptions::default()).await;
+
+        let start = time::Instant::now();
+        let data = fetch(&sim, "/hello/1").await.unwrap();
+        assert_eq!(data.name.to_string(), "/hello/1");
+        assert_eq!(start.elapsed(), Duration::from_millis(40));
+
+        let traces = sim.traces().await;
+        let delivered = traces
+            .iter()
+            .filter(|trace| trace.event == TraceEvent::Delivered)
+            .map(|trace| (trace.from, trace.to, trace.kind))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            delivered,
+            [
+                (0, 1, PacketKind::Interest),
+                (1, 2, PacketKind::Interest),
+                (2, 1, PacketKind::Data),
+                (1, 0, PacketKind::Data),
+            ]
+        );
+
+        // Cached by the consumer router, nothing goes over the links
+        fetch(&sim, "/hello/1").await.unwrap();
+        assert_eq!(sim.traces().await.len(), traces.len());
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn bandwidth() {
+        let options = LinkOptions::default().with_bandwidth(8_000);
+        let sim = chain(options).await;
+
+        let start = time::Instant::now();
+        fetch(&sim, "/hello/1").await.unwrap();
+        // Each packet spends a millisecond per octet on each link on top of the delay
+        let traces = sim.traces().await;
+        let transmission = traces
+            .iter()
+            .filter(|trace| trace.event == TraceEvent::Sent)
+            .map(|trace| Duration::from_millis(trace.size as u64))
+            .sum::<Duration>();
+        assert_eq!(start.elapsed(), Duration::from_millis(40) + transmission);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn loss() {
+        let options = LinkOptions::default().with_loss(1.0);
+        let sim = chain(options).await;
+
+        let error = fetch(&sim, "/hello/1").await.unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::TimedOut);
+        let events = sim
+            .traces()
+            .await
+            .into_iter()
+            .map(|trace| trace.event)
+            .collect::<Vec<_>>();
+        assert_eq!(events, [TraceEvent::Sent, TraceEvent::Lost]);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn reproducible() {
+        let options = LinkOptions::default().with_loss(0.2).with_reordering(0.5);
+
+        let mut runs = Vec::new();
+        for _ in 0..2 {
+            let sim = chain(options).await;
+            let start = time::Instant::now();
+            for n in 0..20 {
+                let _ = fetch(&sim, &format!("/hello/{n}")).await;
+            }
+            let traces = sim.traces().await;
+            runs.push((start.elapsed(), traces));
+        }
+        assert_eq!(runs[0], runs[1]);
+    }
+}
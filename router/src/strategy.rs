@@ -0,0 +1,170 @@
+use std::fmt;
+use std::time::Instant;
+
+use super::*;
+
+pub use asf::Asf;
+pub use asf::AsfOptions;
+pub use retx::RetxDecision;
+pub use retx::RetxSuppression;
+pub use selflearning::SelfLearning;
+
+mod asf;
+mod retx;
+mod selflearning;
+
+/// Forwarding strategy makes forwarding decisions for Interests in its namespace
+/// and gets notified about the events relevant to these decisions.
+pub trait Strategy: fmt::Debug + Send + Sync {
+    /// Strategy name, e.g. `/localhost/nfd/strategy/best-route`
+    fn name(&self) -> &'static str;
+
+    /// How consumer retransmissions are suppressed before reaching `after_receive_interest`
+    fn retx_suppression(&self) -> RetxSuppression {
+        RetxSuppression::default()
+    }
+
+    /// Decide which of the `fib_entry` nexthops to forward `interest` to,
+    /// after it has been received from `downstream` and missed the Content Store.
+    /// Retransmissions get here only if not suppressed.
+    fn after_receive_interest(
+        &self,
+        interest: &Interest,
+        downstream: face::FaceId,
+        fib_entry: &FibEntry,
+        measurements: &mut MeasurementsTable,
+        now: Instant,
+    ) -> Vec<face::FaceId> {
+        let _ = (interest, measurements, now);
+        fib_entry
+            .nexthops
+            .iter()
+            .map(|nexthop| nexthop.face)
+            .find(|face| *face != downstream)
+            .into_iter()
+            .collect()
+    }
+
+    /// Decide which of `faces` to forward `interest` to, after it has been received
+    /// from `downstream` and matched no FIB entry
+    fn after_no_route(
+        &self,
+        interest: &Interest,
+        downstream: face::FaceId,
+        faces: &[Arc<FaceHandle>],
+    ) -> Vec<face::FaceId> {
+        let _ = (interest, downstream, faces);
+        Vec::new()
+    }
+
+    /// Whether Data sent downstream carries the PrefixAnnouncement of the route it came over
+    fn attaches_prefix_announcements(&self) -> bool {
+        false
+    }
+
+    /// `pit_entry` is about to be satisfied by `data` received from `upstream`
+    fn before_satisfy_interest(
+        &self,
+        pit_entry: &PitEntry,
+        upstream: face::FaceId,
+        data: &Data,
+        measurements: &mut MeasurementsTable,
+        now: Instant,
+    ) {
+        let _ = (pit_entry, upstream, data, measurements, now);
+    }
+
+    /// `pit_entry` has expired without being satisfied
+    fn on_interest_timeout(
+        &self,
+        pit_entry: &PitEntry,
+        measurements: &mut MeasurementsTable,
+        now: Instant,
+    ) {
+        let _ = (measurements, now);
+        tracing::debug!(strategy = self.name(), interest = %pit_entry.interest, "Interest timed out");
+    }
+
+    /// `interest` sent to `upstream` has been rejected with `nack`
+    fn after_receive_nack(&self, interest: &Interest, upstream: face::FaceId, nack: lpv2::Nack) {
+        tracing::debug!(strategy = self.name(), %interest, %upstream, %nack, "Nack received");
+    }
+
+    /// Link layer has given up delivering `interest` to `upstream`
+    fn on_dropped_interest(&self, interest: &Interest, upstream: face::FaceId) {
+        tracing::debug!(strategy = self.name(), %interest, %upstream, "Interest dropped by link layer");
+    }
+
+    /// `data` has arrived from `upstream`, possibly carrying a congestion mark set along the way
+    fn after_receive_data(
+        &self,
+        data: &Data,
+        upstream: face::FaceId,
+        congestion_mark: Option<lpv2::CongestionMark>,
+    ) {
+        if congestion_mark.is_some_and(lpv2::CongestionMark::is_congested) {
+            tracing::debug!(strategy = self.name(), name = %data.name, %upstream, "Congestion mark received");
+        }
+    }
+}
+
+/// Forward Interests to the lowest cost nexthop
+#[derive(Debug, Default)]
+pub struct BestRoute {
+    retx_suppression: RetxSuppression,
+}
+
+impl BestRoute {
+    pub fn new(retx_suppression: RetxSuppression) -> Self {
+        Self { retx_suppression }
+    }
+}
+
+impl Strategy for BestRoute {
+    fn name(&self) -> &'static str {
+        "/localhost/nfd/strategy/best-route"
+    }
+
+    fn retx_suppression(&self) -> RetxSuppression {
+        self.retx_suppression
+    }
+}
+
+/// Strategy Choice table
+#[derive(Debug)]
+pub struct StrategyChoice {
+    default: Box<dyn Strategy>,
+    strategies: HashMap<tlv::Name, Box<dyn Strategy>>,
+}
+
+impl StrategyChoice {
+    pub fn new(default: impl Strategy + 'static) -> Self {
+        let default = Box::new(default);
+        let strategies = HashMap::new();
+        Self {
+            default,
+            strategies,
+        }
+    }
+
+    /// Use `strategy` for the `prefix` namespace
+    pub fn with_strategy(mut self, prefix: tlv::Name, strategy: impl Strategy + 'static) -> Self {
+        self.strategies.insert(prefix, Box::new(strategy));
+        self
+    }
+
+    /// Strategy responsible for `name`
+    pub fn find_effective_strategy(&self, name: &tlv::Name) -> &dyn Strategy {
+        (0..=name.len())
+            .rev()
+            .find_map(|len| self.strategies.get(&name.prefix(len)))
+            .unwrap_or(&self.default)
+            .as_ref()
+    }
+}
+
+impl Default for StrategyChoice {
+    fn default() -> Self {
+        Self::new(BestRoute::default())
+    }
+}
@@ -0,0 +1,252 @@
+//! Adaptive SRTT-based Forwarding strategy
+//!
+//! Forwards each Interest to the nexthop with the lowest smoothed RTT measured for the namespace.
+//! Nexthops without measurements come next, in the order of their cost,
+//! and the ones that keep timing out go last.
+//! Every probing interval the Interest is also sent to one of the alternatives
+//! so that their measurements stay fresh.
+
+use std::cmp::Ordering;
+use std::time::Duration;
+
+use super::*;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct AsfOptions {
+    /// how often the alternative nexthops are probed
+    pub probing_interval: Duration,
+    /// timeouts in a row tolerated before switching away from a face
+    pub max_silent_timeouts: u32,
+    /// how long the measurements are kept after the namespace has been last used
+    pub measurements_lifetime: Duration,
+    pub retx_suppression: RetxSuppression,
+}
+
+impl Default for AsfOptions {
+    fn default() -> Self {
+        Self {
+            probing_interval: Duration::from_secs(60),
+            max_silent_timeouts: 0,
+            measurements_lifetime: Duration::from_secs(300),
+            retx_suppression: RetxSuppression::default(),
+        }
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct Asf {
+    options: AsfOptions,
+}
+
+impl Asf {
+    pub fn new(options: AsfOptions) -> Self {
+        Self { options }
+    }
+
+    pub fn options(&self) -> &AsfOptions {
+        &self.options
+    }
+
+    /// Nexthops ordered from the most to the least preferred
+    fn rank(&self, nexthops: Vec<NextHop>, entry: &MeasurementsEntry) -> Vec<NextHop> {
+        let class = |nexthop: &NextHop| match entry.face(nexthop.face) {
+            Some(face) if face.n_silent_timeouts() > self.options.max_silent_timeouts => 2,
+            Some(face) if face.smoothed_rtt().is_some() => 0,
+            _ => 1,
+        };
+        let srtt = |nexthop: &NextHop| {
+            entry
+                .face(nexthop.face)
+                .and_then(FaceMeasurements::smoothed_rtt)
+        };
+
+        let mut nexthops = nexthops;
+        // Sorting is stable, so nexthops of the same class and RTT stay ordered by cost
+        nexthops.sort_by(|a, b| match class(a).cmp(&class(b)) {
+            Ordering::Equal => srtt(a).cmp(&srtt(b)),
+            other => other,
+        });
+        nexthops
+    }
+
+    fn is_probing_due(
+        &self,
+        entry: &MeasurementsEntry,
+        candidates: &[NextHop],
+        now: Instant,
+    ) -> bool {
+        candidates
+            .iter()
+            .filter_map(|nexthop| entry.face(nexthop.face)?.last_probe())
+            .max()
+            .is_none_or(|last| now >= last + self.options.probing_interval)
+    }
+}
+
+impl Strategy for Asf {
+    fn name(&self) -> &'static str {
+        "/localhost/nfd/strategy/asf"
+    }
+
+    fn retx_suppression(&self) -> RetxSuppression {
+        self.options.retx_suppression
+    }
+
+    fn after_receive_interest(
+        &self,
+        interest: &Interest,
+        downstream: face::FaceId,
+        fib_entry: &FibEntry,
+        measurements: &mut MeasurementsTable,
+        now: Instant,
+    ) -> Vec<face::FaceId> {
+        let entry = measurements.get_or_insert(&fib_entry.prefix, now);
+        entry.extend_lifetime(now + self.options.measurements_lifetime);
+
+        let candidates = fib_entry
+            .nexthops
+            .iter()
+            .copied()
+            .filter(|nexthop| nexthop.face != downstream)
+            .collect::<Vec<_>>();
+        let ranked = self.rank(candidates, entry);
+        let Some((best, alternatives)) = ranked.split_first() else {
+            return Vec::new();
+        };
+
+        let mut upstreams = vec![best.face];
+        if !alternatives.is_empty() && self.is_probing_due(entry, &ranked, now) {
+            let probe = alternatives
+                .iter()
+                .min_by_key(|nexthop| {
+                    entry
+                        .face(nexthop.face)
+                        .and_then(FaceMeasurements::last_probe)
+                })
+                .map(|nexthop| nexthop.face);
+            if let Some(probe) = probe {
+                tracing::trace!(%interest, %probe, "Probing alternative nexthop");
+                entry.face_mut(probe).record_probe(now);
+                upstreams.push(probe);
+            }
+        }
+
+        upstreams
+    }
+
+    fn before_satisfy_interest(
+        &self,
+        pit_entry: &PitEntry,
+        upstream: face::FaceId,
+        data: &Data,
+        measurements: &mut MeasurementsTable,
+        now: Instant,
+    ) {
+        let _ = data;
+        let Some(sent) = pit_entry.upstreams.get(&upstream) else {
+            return;
+        };
+        if let Some(entry) = measurements.longest_prefix_match_mut(&pit_entry.interest.name) {
+            let rtt = now.saturating_duration_since(*sent);
+            entry.face_mut(upstream).record_rtt(rtt);
+        }
+    }
+
+    fn on_interest_timeout(
+        &self,
+        pit_entry: &PitEntry,
+        measurements: &mut MeasurementsTable,
+        now: Instant,
+    ) {
+        if let Some(entry) = measurements.longest_prefix_match_mut(&pit_entry.interest.name) {
+            for upstream in pit_entry.upstreams.keys() {
+                tracing::debug!(interest = %pit_entry.interest, %upstream, "Upstream timed out");
+                entry.face_mut(*upstream).record_timeout(now);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fib_entry(faces: &[u64]) -> FibEntry {
+        let prefix = "/a".parse().unwrap();
+        let nexthops = faces
+            .iter()
+            .map(|&face| NextHop {
+                face: face::FaceId::from(face),
+                cost: face,
+            })
+            .collect();
+        FibEntry { prefix, nexthops }
+    }
+
+    fn pit_entry(interest: &Interest, upstream: face::FaceId, sent: Instant) -> PitEntry {
+        PitEntry {
+            interest: interest.clone(),
+            downstreams: HashMap::new(),
+            upstreams: HashMap::from([(upstream, sent)]),
+            nacks: HashMap::new(),
+            nonces: Vec::new(),
+            retx_suppression_interval: None,
+            expiry: sent,
+        }
+    }
+
+    #[test]
+    fn lowest_rtt_and_probing() {
+        let asf = Asf::default();
+        let mut measurements = MeasurementsTable::default();
+        let now = Instant::now();
+        let interest = Interest::new("/a/b");
+        let downstream = face::FaceId::from(100);
+        let slow = face::FaceId::from(1);
+        let fast = face::FaceId::from(2);
+        let fib_entry = fib_entry(&[1, 2]);
+
+        // No measurements yet, lowest cost goes first and the other one is probed
+        let upstreams =
+            asf.after_receive_interest(&interest, downstream, &fib_entry, &mut measurements, now);
+        assert_eq!(upstreams, [slow, fast]);
+
+        for (face, rtt) in [(slow, 50), (fast, 10)] {
+            let entry = pit_entry(&interest, face, now);
+            let data = Data::new("/a/b");
+            let later = now + Duration::from_millis(rtt);
+            asf.before_satisfy_interest(&entry, face, &data, &mut measurements, later);
+        }
+
+        // Lowest RTT wins, no probing until the interval passes
+        let upstreams =
+            asf.after_receive_interest(&interest, downstream, &fib_entry, &mut measurements, now);
+        assert_eq!(upstreams, [fast]);
+
+        let later = now + asf.options().probing_interval;
+        let upstreams =
+            asf.after_receive_interest(&interest, downstream, &fib_entry, &mut measurements, later);
+        assert_eq!(upstreams, [fast, slow]);
+    }
+
+    #[test]
+    fn switch_away_on_timeout() {
+        let asf = Asf::default();
+        let mut measurements = MeasurementsTable::default();
+        let now = Instant::now();
+        let interest = Interest::new("/a/b");
+        let downstream = face::FaceId::from(100);
+        let first = face::FaceId::from(1);
+        let second = face::FaceId::from(2);
+        let fib_entry = fib_entry(&[1, 2]);
+
+        let upstreams =
+            asf.after_receive_interest(&interest, downstream, &fib_entry, &mut measurements, now);
+        assert_eq!(upstreams[0], first);
+
+        asf.on_interest_timeout(&pit_entry(&interest, first, now), &mut measurements, now);
+        let upstreams =
+            asf.after_receive_interest(&interest, downstream, &fib_entry, &mut measurements, now);
+        assert_eq!(upstreams, [second]);
+    }
+}
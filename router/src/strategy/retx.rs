@@ -0,0 +1,117 @@
+//! Exponential retransmission suppression, as in NFD's RetxSuppressionExponential
+//!
+//! A consumer retransmission is forwarded again only if the PIT entry has not been forwarded
+//! during the suppression interval. Each forwarded retransmission multiplies the interval
+//! of the entry, up to the maximum.
+
+use std::time::Duration;
+
+use super::*;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct RetxSuppression {
+    pub initial_interval: Duration,
+    pub multiplier: f64,
+    pub max_interval: Duration,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RetxDecision {
+    /// PIT entry has not been forwarded yet
+    New,
+    /// retransmission to be forwarded
+    Forward,
+    /// retransmission arrived too soon after the last forwarding
+    Suppress,
+}
+
+impl RetxSuppression {
+    pub fn new(initial_interval: Duration, multiplier: f64, max_interval: Duration) -> Self {
+        Self {
+            initial_interval,
+            multiplier,
+            max_interval,
+        }
+    }
+
+    /// Decide whether to forward the Interest of `pit_entry` again,
+    /// growing the entry suppression interval when it is
+    pub fn decide(&self, pit_entry: &mut PitEntry, now: Instant) -> RetxDecision {
+        let Some(last_outgoing) = pit_entry.upstreams.values().max() else {
+            return RetxDecision::New;
+        };
+
+        let interval = pit_entry
+            .retx_suppression_interval
+            .unwrap_or(self.initial_interval);
+        if now < *last_outgoing + interval {
+            return RetxDecision::Suppress;
+        }
+
+        let next = interval.mul_f64(self.multiplier).min(self.max_interval);
+        pit_entry.retx_suppression_interval = Some(next);
+        RetxDecision::Forward
+    }
+}
+
+impl Default for RetxSuppression {
+    fn default() -> Self {
+        Self::new(Duration::from_millis(10), 2.0, Duration::from_millis(250))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn exponential() {
+        let suppression = RetxSuppression::default();
+        let start = Instant::now();
+        let upstream = face::FaceId::from(1);
+        let mut pit_entry = PitEntry {
+            interest: Interest::new("/a"),
+            downstreams: HashMap::new(),
+            upstreams: HashMap::new(),
+            nacks: HashMap::new(),
+            nonces: Vec::new(),
+            retx_suppression_interval: None,
+            expiry: start,
+        };
+        assert_eq!(suppression.decide(&mut pit_entry, start), RetxDecision::New);
+
+        pit_entry.upstreams.insert(upstream, start);
+        let ms = Duration::from_millis;
+        assert_eq!(
+            suppression.decide(&mut pit_entry, start + ms(5)),
+            RetxDecision::Suppress
+        );
+        assert_eq!(
+            suppression.decide(&mut pit_entry, start + ms(10)),
+            RetxDecision::Forward
+        );
+
+        // Interval doubles after each forwarded retransmission
+        let sent = start + ms(10);
+        pit_entry.upstreams.insert(upstream, sent);
+        assert_eq!(
+            suppression.decide(&mut pit_entry, sent + ms(19)),
+            RetxDecision::Suppress
+        );
+        assert_eq!(
+            suppression.decide(&mut pit_entry, sent + ms(20)),
+            RetxDecision::Forward
+        );
+        assert_eq!(pit_entry.retx_suppression_interval, Some(ms(40)));
+
+        // Up to the maximum
+        for _ in 0..10 {
+            let now = start + Duration::from_secs(10);
+            pit_entry
+                .upstreams
+                .insert(upstream, now - Duration::from_secs(1));
+            suppression.decide(&mut pit_entry, now);
+        }
+        assert_eq!(pit_entry.retx_suppression_interval, Some(ms(250)));
+    }
+}
@@ -0,0 +1,151 @@
+//! Self-learning strategy, for ad-hoc deployments without a routing protocol
+//!
+//! Interests without a route are flooded to all the non-local faces but the downstream.
+//! Data coming back carries the PrefixAnnouncement of its producer, from which the routers
+//! along the way install a route towards the upstream, so that the next Interests under the
+//! announced prefix are forwarded to the best nexthop only.
+
+use super::*;
+
+#[derive(Debug, Default)]
+pub struct SelfLearning {
+    retx_suppression: RetxSuppression,
+}
+
+impl SelfLearning {
+    pub fn new(retx_suppression: RetxSuppression) -> Self {
+        Self { retx_suppression }
+    }
+}
+
+impl Strategy for SelfLearning {
+    fn name(&self) -> &'static str {
+        "/localhost/nfd/strategy/self-learning"
+    }
+
+    fn retx_suppression(&self) -> RetxSuppression {
+        self.retx_suppression
+    }
+
+    fn after_no_route(
+        &self,
+        interest: &Interest,
+        downstream: face::FaceId,
+        faces: &[Arc<FaceHandle>],
+    ) -> Vec<face::FaceId> {
+        tracing::trace!(strategy = self.name(), %interest, "Flooding discovery Interest");
+        faces
+            .iter()
+            .filter(|face| face.face_scope() == face::FaceScope::NonLocal)
+            .map(|face| face.face_id())
+            .filter(|face| *face != downstream)
+            .collect()
+    }
+
+    fn attaches_prefix_announcements(&self) -> bool {
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use crate::sim::{LinkOptions, PacketKind, Simulation, TraceEvent};
+
+    use super::*;
+
+    #[tokio::test(start_paused = true)]
+    async fn learn_routes_from_announcements() {
+        // Consumer on router 0, producer of `/hello` on router 2 behind router 1,
+        // router 3 is a dead end
+        let mut sim = Simulation::new(7);
+        let nodes = [(); 4].map(|()| {
+            let strategy_choice = StrategyChoice::new(SelfLearning::default());
+            let router = Router::new()
+                .with_strategy_choice(strategy_choice)
+                .with_announcement_validator(TrustAllAnnouncements);
+            sim.add_router(router)
+        });
+        let options = LinkOptions::default();
+        let link = sim.connect(nodes[0], nodes[1], options).await.unwrap();
+        sim.connect(nodes[1], nodes[2], options).await.unwrap();
+        sim.connect(nodes[0], nodes[3], options).await.unwrap();
+
+        let prefix: tlv::Name = "/hello".parse().unwrap();
+        let producer = sim.router(2).unwrap();
+        let face = producer
+            .register_producer(prefix.clone(), |interest: Interest| async move {
+                Some(Data::new(interest.name.to_string()))
+            })
+            .await
+            .unwrap();
+        let announcement = tlv::PrefixAnnouncement::new(prefix.clone(), Duration::from_secs(60));
+        producer.announce_prefix(announcement, face).await.unwrap();
+
+        let consumer = Arc::clone(sim.router(0).unwrap());
+        let fetch = |name: &str| {
+            let interest = Interest::new(name).with_interest_lifetime(1000);
+            consumer.express_interest(interest)
+        };
+        let interests_to_dead_end = |traces: &[sim::Trace]| {
+            traces
+                .iter()
+                .filter(|trace| trace.event == TraceEvent::Delivered)
+                .filter(|trace| trace.kind == PacketKind::Interest && trace.to == 3)
+                .count()
+        };
+
+        // Discovery Interest is flooded, the route is learned from the Data
+        fetch("/hello/1").await.unwrap();
+        assert_eq!(interests_to_dead_end(&sim.traces().await), 1);
+        let routes = consumer.routing_information_base().routes(&prefix).await;
+        assert_eq!(routes.len(), 1);
+        assert_eq!(routes[0].face, link.a_face);
+        assert_eq!(routes[0].origin, mgmt::Origin::PrefixAnn);
+        let routes = sim.router(1).unwrap().routing_information_base();
+        assert_eq!(routes.routes(&prefix).await.len(), 1);
+
+        // Known route is followed without flooding
+        fetch("/hello/2").await.unwrap();
+        assert_eq!(interests_to_dead_end(&sim.traces().await), 1);
+
+        // Learned routes expire along with the announcement
+        time::sleep(Duration::from_secs(61)).await;
+        assert!(consumer
+            .forwarding_information_base()
+            .longest_prefix_match(&prefix)
+            .await
+            .is_none());
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn ignore_untrusted_announcements() {
+        let mut sim = Simulation::new(7);
+        let nodes = [(); 2].map(|()| {
+            let strategy_choice = StrategyChoice::new(SelfLearning::default());
+            sim.add_router(Router::new().with_strategy_choice(strategy_choice))
+        });
+        sim.connect(nodes[0], nodes[1], LinkOptions::default())
+            .await
+            .unwrap();
+
+        let prefix: tlv::Name = "/hello".parse().unwrap();
+        let producer = sim.router(1).unwrap();
+        let face = producer
+            .register_producer(prefix.clone(), |interest: Interest| async move {
+                Some(Data::new(interest.name.to_string()))
+            })
+            .await
+            .unwrap();
+        let announcement = tlv::PrefixAnnouncement::new(prefix.clone(), Duration::from_secs(60));
+        producer.announce_prefix(announcement, face).await.unwrap();
+
+        // Data still makes it, the route does not without a validator
+        let consumer = Arc::clone(sim.router(0).unwrap());
+        let interest = Interest::new("/hello/1").with_interest_lifetime(1000);
+        consumer.express_interest(interest).await.unwrap();
+        let routes = consumer.routing_information_base();
+        assert!(routes.routes(&prefix).await.is_empty());
+    }
+}
@@ -0,0 +1,81 @@
+use std::fmt;
+use std::str;
+
+use super::*;
+
+/// What to do with Data that matches no PIT entry, as NFD `tables.cs_unsolicited_policy`
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum UnsolicitedDataPolicy {
+    /// never admit unsolicited Data into the Content Store
+    #[default]
+    DropAll,
+    /// admit unsolicited Data received on local faces
+    AdmitLocal,
+    /// admit unsolicited Data received on non-local faces
+    AdmitNetwork,
+    /// admit all unsolicited Data
+    AdmitAll,
+}
+
+impl UnsolicitedDataPolicy {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::DropAll => "drop-all",
+            Self::AdmitLocal => "admit-local",
+            Self::AdmitNetwork => "admit-network",
+            Self::AdmitAll => "admit-all",
+        }
+    }
+
+    /// Whether unsolicited Data received on a face of `face_scope` goes into the Content Store
+    pub fn admits(&self, face_scope: face::FaceScope) -> bool {
+        match self {
+            Self::DropAll => false,
+            Self::AdmitLocal => face_scope == face::FaceScope::Local,
+            Self::AdmitNetwork => face_scope == face::FaceScope::NonLocal,
+            Self::AdmitAll => true,
+        }
+    }
+}
+
+impl str::FromStr for UnsolicitedDataPolicy {
+    type Err = io::Error;
+
+    fn from_str(text: &str) -> Result<Self, Self::Err> {
+        match text {
+            "drop-all" => Ok(Self::DropAll),
+            "admit-local" => Ok(Self::AdmitLocal),
+            "admit-network" => Ok(Self::AdmitNetwork),
+            "admit-all" => Ok(Self::AdmitAll),
+            other => Err(io::Error::other(format!(
+                "Invalid unsolicited Data policy '{other}'"
+            ))),
+        }
+    }
+}
+
+impl fmt::Display for UnsolicitedDataPolicy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.as_str().fmt(f)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn admits() {
+        let local = face::FaceScope::Local;
+        let network = face::FaceScope::NonLocal;
+        let policies = ["drop-all", "admit-local", "admit-network", "admit-all"]
+            .map(|text| text.parse::<UnsolicitedDataPolicy>().unwrap());
+        let admitted = policies.map(|policy| (policy.admits(local), policy.admits(network)));
+        assert_eq!(
+            admitted,
+            [(false, false), (true, false), (false, true), (true, true)]
+        );
+        assert_eq!(policies[1].to_string(), "admit-local");
+        assert!("admit-some".parse::<UnsolicitedDataPolicy>().is_err());
+    }
+}
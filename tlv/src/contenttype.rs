@@ -7,4 +7,5 @@ impl ContentType {
     pub const LINK: Self = Self(NonNegativeNumber(1));
     pub const KEY: Self = Self(NonNegativeNumber(2));
     pub const NACK: Self = Self(NonNegativeNumber(3));
+    pub const PREFIX_ANN: Self = Self(NonNegativeNumber(5));
 }
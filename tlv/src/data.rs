@@ -10,6 +10,17 @@ pub struct Data {
 }
 
 impl Data {
+    pub fn new(name: impl AsRef<str>) -> Self {
+        let name = name.as_ref().parse().expect("Valid Name");
+        let data_signature = DataSignature::digest();
+        Self {
+            name,
+            metainfo: None,
+            content: None,
+            data_signature,
+        }
+    }
+
     pub fn name(&self) -> String {
         self.name.to_string()
     }
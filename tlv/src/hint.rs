@@ -1,11 +1,93 @@
 use super::*;
 
-#[derive(Clone, Debug, PartialEq, Eq, Hash, Tlv)]
-#[tlv(r#type = Type::ForwardingHint, error = DecodeError)]
-pub struct ForwardingHint;
+// ForwardingHint ::= FORWARDING-HINT-TYPE TLV-LENGTH
+//                      1*Name
+//
+// Names are delegations in the order of preference.
+
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct ForwardingHint {
+    names: Vec<Name>,
+}
+
+impl ForwardingHint {
+    pub fn new(names: impl IntoIterator<Item = Name>) -> Self {
+        let names = names.into_iter().collect();
+        Self { names }
+    }
+
+    pub fn names(&self) -> &[Name] {
+        &self.names
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.names.is_empty()
+    }
+}
+
+impl Tlv for ForwardingHint {
+    type Error = DecodeError;
+    const TYPE: Type = Type::ForwardingHint;
+
+    fn length(&self) -> usize {
+        self.names.total_size()
+    }
+
+    fn encode_value(&self, dst: &mut BytesMut) {
+        self.names.encode(dst)
+    }
+
+    fn decode_value(r#type: Type, length: usize, src: &mut BytesMut) -> Result<Self, Self::Error> {
+        let _ = (r#type, length);
+        let names = Vec::<Name>::decode(src)?;
+        if names.is_empty() {
+            Err(DecodeError::invalid(
+                "ForwardingHint must have at least one Name",
+            ))
+        } else {
+            Ok(Self { names })
+        }
+    }
+}
 
 impl fmt::Display for ForwardingHint {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        "<ForwardingHint>".fmt(f)
+        let names = self
+            .names
+            .iter()
+            .map(|name| name.to_string())
+            .collect::<Vec<_>>()
+            .join(",");
+        if f.alternate() {
+            format_args!("forwarding_hint=[{names}]").fmt(f)
+        } else {
+            format_args!("{}=[{names}]", self.r#type()).fmt(f)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn interest_roundtrip() {
+        let hint =
+            ForwardingHint::new(["/telia/terabits", "/ucla"].map(|name| name.parse().unwrap()));
+        let interest = Interest::new("/ucla/videos").with_forwarding_hint(hint.clone());
+
+        let mut bytes = BytesMut::from(interest.bytes().as_ref());
+        let decoded = Interest::decode(&mut bytes).unwrap();
+        assert_eq!(decoded.forwarding_hint, Some(hint));
+        assert_eq!(decoded.forwarding_hint.unwrap().names().len(), 2);
+    }
+
+    #[test]
+    fn empty() {
+        let interest = Interest::new("/ucla/videos").with_forwarding_hint(ForwardingHint::new([]));
+
+        let mut bytes = BytesMut::from(interest.bytes().as_ref());
+        let error = Interest::decode(&mut bytes).unwrap_err();
+        assert!(matches!(error, DecodeError::InvalidData(_)));
     }
 }
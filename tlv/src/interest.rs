@@ -52,6 +52,22 @@ impl Interest {
         }
     }
 
+    pub fn with_forwarding_hint(self, forwarding_hint: impl Into<Option<ForwardingHint>>) -> Self {
+        let forwarding_hint = forwarding_hint.into();
+        Self {
+            forwarding_hint,
+            ..self
+        }
+    }
+
+    pub fn with_interest_lifetime(self, milliseconds: u64) -> Self {
+        let interest_lifetime = Some(InterestLifetime::from(milliseconds));
+        Self {
+            interest_lifetime,
+            ..self
+        }
+    }
+
     pub fn name(&self) -> String {
         self.name.to_string()
     }
@@ -59,6 +75,25 @@ impl Interest {
     pub fn is_can_be_prefix(&self) -> bool {
         self.can_be_prefix.is_some()
     }
+
+    /// Whether `data` satisfies this Interest
+    pub fn matches_data(&self, data: &Data) -> bool {
+        if self.is_can_be_prefix() {
+            self.name.is_prefix_of(&data.name)
+        } else {
+            self.name == data.name
+        }
+    }
+
+    pub fn decode_from_generic(generic: Generic) -> Result<Self, DecodeError> {
+        let Generic {
+            r#type,
+            length,
+            mut value,
+        } = generic.check_type(Type::Interest)?;
+        let length = length.to_usize();
+        Self::decode_value(r#type, length, &mut value)
+    }
 }
 
 impl fmt::Display for Interest {
@@ -1,3 +1,4 @@
+use std::cmp;
 use std::fmt;
 use std::io;
 use std::ops;
@@ -49,6 +50,11 @@ pub use name::TimestampNameComponent;
 pub use name::VersionNameComponent;
 pub use nonce::Nonce;
 pub use number::NonNegativeNumber;
+pub use prefixann::ExpirationPeriod;
+pub use prefixann::NotAfter;
+pub use prefixann::NotBefore;
+pub use prefixann::PrefixAnnouncement;
+pub use prefixann::ValidityPeriod;
 pub use signature::DataSignature;
 pub use signature::InterestSignature;
 pub use signature::InterestSignatureInfo;
@@ -76,6 +82,7 @@ mod name;
 mod nonce;
 mod number;
 mod octets;
+mod prefixann;
 mod signature;
 mod string;
 mod timestamp;
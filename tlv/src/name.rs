@@ -16,7 +16,9 @@ pub use component::VersionNameComponent;
 mod block;
 mod component;
 
-#[derive(Clone, Debug, PartialEq, Eq, Hash, Tlv)]
+/// Names are ordered canonically, component by component, a prefix coming before the names
+/// it is a prefix of
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Tlv)]
 #[tlv(r#type = Type::Name, error = DecodeError)]
 pub struct Name {
     components: Vec<NameComponent>,
@@ -30,6 +32,35 @@ impl Name {
         let components = vec![digest.into()];
         Self { components }
     }
+
+    pub fn len(&self) -> usize {
+        self.components.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.components.is_empty()
+    }
+
+    pub fn components(&self) -> &[NameComponent] {
+        &self.components
+    }
+
+    /// The first `len` components of this name
+    pub fn prefix(&self, len: usize) -> Self {
+        let components = self.components.iter().take(len).cloned().collect();
+        Self { components }
+    }
+
+    /// The same name with `component` added at the end
+    pub fn append(mut self, component: impl Into<NameComponent>) -> Self {
+        self.components.push(component.into());
+        self
+    }
+
+    /// Whether this name is a prefix of (or equal to) `other`
+    pub fn is_prefix_of(&self, other: &Self) -> bool {
+        other.components.starts_with(&self.components)
+    }
 }
 
 impl str::FromStr for Name {
@@ -67,3 +98,16 @@ pub enum NameError {
     #[error("Component Type ({0}) out of range [1..65535]")]
     TypeOutOfRange(Type),
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn canonical_order() {
+        // Shorter components come first whatever their octets
+        let names =
+            ["/", "/a", "/a/b", "/a/bb", "/b", "/aa"].map(|name| name.parse::<Name>().unwrap());
+        assert!(names.windows(2).all(|pair| pair[0] < pair[1]));
+    }
+}
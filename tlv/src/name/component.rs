@@ -115,6 +115,27 @@ impl From<OtherTypeComponent> for NameComponent {
     }
 }
 
+/// Canonical order: by TLV-TYPE, then TLV-LENGTH, then TLV-VALUE octet by octet
+impl Ord for NameComponent {
+    fn cmp(&self, other: &Self) -> cmp::Ordering {
+        let value = |component: &Self| {
+            let mut value = BytesMut::with_capacity(component.length());
+            component.encode_value(&mut value);
+            value
+        };
+        self.r#type()
+            .cmp(&other.r#type())
+            .then_with(|| self.length().cmp(&other.length()))
+            .then_with(|| value(self).cmp(&value(other)))
+    }
+}
+
+impl PartialOrd for NameComponent {
+    fn partial_cmp(&self, other: &Self) -> Option<cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
 impl fmt::Display for NameComponent {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         let component = match self {
@@ -0,0 +1,413 @@
+use std::time::{Duration, SystemTime};
+
+use super::*;
+
+// PrefixAnnouncement is a Data packet announcing a route to its prefix:
+//
+//   Name ::= <prefix>/32=PA/<version>/<segment>
+//   MetaInfo ContentType ::= PrefixAnn (5)
+//   Content ::= ExpirationPeriod
+//                 [ValidityPeriod]
+//
+// Attached to Data in the PrefixAnnouncement LpPacket header field, see
+// https://redmine.named-data.net/projects/nfd/wiki/PrefixAnnouncement
+
+non_negative_number!(ExpirationPeriod => Type::ExpirationPeriod);
+
+// NotBefore and NotAfter are ISO 8601 compact UTC timestamps, e.g. 20241019T120000
+utf8_string!(NotBefore => Type::NotBefore);
+utf8_string!(NotAfter => Type::NotAfter);
+
+// ValidityPeriod ::= VALIDITY-PERIOD-TYPE TLV-LENGTH
+//                      NotBefore
+//                      NotAfter
+#[derive(Clone, Debug, PartialEq, Eq, Hash, Tlv)]
+#[tlv(r#type = Type::ValidityPeriod, error = DecodeError)]
+pub struct ValidityPeriod {
+    pub not_before: NotBefore,
+    pub not_after: NotAfter,
+}
+
+impl ValidityPeriod {
+    pub fn new(not_before: SystemTime, not_after: SystemTime) -> Self {
+        let not_before = NotBefore(format_timestamp(not_before));
+        let not_after = NotAfter(format_timestamp(not_after));
+        Self {
+            not_before,
+            not_after,
+        }
+    }
+
+    pub fn not_before(&self) -> Option<SystemTime> {
+        parse_timestamp(&self.not_before)
+    }
+
+    pub fn not_after(&self) -> Option<SystemTime> {
+        parse_timestamp(&self.not_after)
+    }
+
+    /// Whether `time` falls within the period, malformed timestamps never do
+    pub fn contains(&self, time: SystemTime) -> bool {
+        match (self.not_before(), self.not_after()) {
+            (Some(not_before), Some(not_after)) => not_before <= time && time <= not_after,
+            _ => false,
+        }
+    }
+}
+
+impl fmt::Display for ValidityPeriod {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "validity=[{:#}..{:#}]", self.not_before, self.not_after)
+    }
+}
+
+fn format_timestamp(time: SystemTime) -> String {
+    let time = time::OffsetDateTime::from(time);
+    format!(
+        "{:04}{:02}{:02}T{:02}{:02}{:02}",
+        time.year(),
+        u8::from(time.month()),
+        time.day(),
+        time.hour(),
+        time.minute(),
+        time.second()
+    )
+}
+
+fn parse_timestamp(text: &str) -> Option<SystemTime> {
+    let (date, time) = text.split_once('T')?;
+    if date.len() != 8
+        || time.len() != 6
+        || !(date.chars().chain(time.chars())).all(|c| c.is_ascii_digit())
+    {
+        return None;
+    }
+    let number = |text: &str, range: ops::Range<usize>| text.get(range)?.parse::<u8>().ok();
+    let year = date.get(0..4)?.parse().ok()?;
+    let month = time::Month::try_from(number(date, 4..6)?).ok()?;
+    let date = time::Date::from_calendar_date(year, month, number(date, 6..8)?).ok()?;
+    let time = time::Time::from_hms(
+        number(time, 0..2)?,
+        number(time, 2..4)?,
+        number(time, 4..6)?,
+    )
+    .ok()?;
+    let datetime = time::PrimitiveDateTime::new(date, time).assume_utc();
+    Some(datetime.into())
+}
+
+/// Route announcement for a prefix, carried as a Data packet
+#[derive(Clone, Debug)]
+pub struct PrefixAnnouncement {
+    prefix: Name,
+    expiration_period: ExpirationPeriod,
+    validity_period: Option<ValidityPeriod>,
+    data: Data,
+}
+
+impl PrefixAnnouncement {
+    /// Keyword name component marking the announcement Data
+    pub const KEYWORD: &'static [u8] = b"PA";
+
+    /// Announcement of `prefix` valid for `expiration` since it is received,
+    /// versioned by the current time
+    pub fn new(prefix: Name, expiration: Duration) -> Self {
+        let version = SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_millis();
+        let version = u64::try_from(version).unwrap_or(u64::MAX);
+        let expiration_period =
+            ExpirationPeriod::from(u64::try_from(expiration.as_millis()).unwrap_or(u64::MAX));
+        let data = Self::to_data(&prefix, version, expiration_period, None);
+        Self {
+            prefix,
+            expiration_period,
+            validity_period: None,
+            data,
+        }
+    }
+
+    /// The same announcement, only honored within `validity_period`
+    pub fn with_validity_period(self, validity_period: ValidityPeriod) -> Self {
+        let version = self.version().unwrap_or_default();
+        let validity_period = Some(validity_period);
+        let data = Self::to_data(
+            &self.prefix,
+            version,
+            self.expiration_period,
+            validity_period.as_ref(),
+        );
+        Self {
+            validity_period,
+            data,
+            ..self
+        }
+    }
+
+    /// The same announcement signed with `data_signature`
+    pub fn with_signature(self, data_signature: DataSignature) -> Self {
+        let data = Data {
+            data_signature,
+            ..self.data
+        };
+        Self { data, ..self }
+    }
+
+    pub fn prefix(&self) -> &Name {
+        &self.prefix
+    }
+
+    pub fn version(&self) -> Option<u64> {
+        let len = self.data.name.len();
+        match self.data.name.components().get(len.checked_sub(2)?)? {
+            NameComponent::Version(version) => Some(version.to_u64()),
+            _ => None,
+        }
+    }
+
+    pub fn expiration_period(&self) -> Duration {
+        Duration::from_millis(self.expiration_period.to_u64())
+    }
+
+    pub fn validity_period(&self) -> Option<&ValidityPeriod> {
+        self.validity_period.as_ref()
+    }
+
+    /// Whether the announcement may be honored at `time`
+    pub fn is_valid_at(&self, time: SystemTime) -> bool {
+        self.validity_period
+            .as_ref()
+            .is_none_or(|validity_period| validity_period.contains(time))
+    }
+
+    /// How long the announced route lasts if installed at `time`,
+    /// the ExpirationPeriod cut short by the end of the ValidityPeriod
+    pub fn route_lifetime(&self, time: SystemTime) -> Duration {
+        let expiration = self.expiration_period();
+        self.validity_period
+            .as_ref()
+            .and_then(ValidityPeriod::not_after)
+            .and_then(|not_after| not_after.duration_since(time).ok())
+            .map_or(expiration, |remaining| remaining.min(expiration))
+    }
+
+    pub fn data(&self) -> &Data {
+        &self.data
+    }
+
+    pub fn into_data(self) -> Data {
+        self.data
+    }
+
+    /// Announcement carried by `data`, checking its name, ContentType and content
+    pub fn from_data(data: Data) -> Result<Self, DecodeError> {
+        if data.content_type() != Some(ContentType::PREFIX_ANN) {
+            return Err(DecodeError::invalid(
+                "PrefixAnnouncement must have PrefixAnn ContentType",
+            ));
+        }
+
+        let components = data.name.components();
+        let prefix = match components {
+            [.., keyword, NameComponent::Version(_), NameComponent::Segment(_)]
+                if is_keyword(keyword) =>
+            {
+                data.name.prefix(components.len() - 3)
+            }
+            _ => {
+                return Err(DecodeError::invalid(format!(
+                    "Invalid PrefixAnnouncement name {}",
+                    data.name
+                )))
+            }
+        };
+
+        let mut expiration_period = None;
+        let mut validity_period = None;
+        for item in data.content.clone().into_iter().flatten() {
+            match item.r#type {
+                Type::ExpirationPeriod => expiration_period = Some(item.try_into()?),
+                Type::ValidityPeriod => {
+                    let Generic {
+                        r#type,
+                        length,
+                        mut value,
+                    } = item;
+                    let length = length.to_usize();
+                    validity_period =
+                        Some(ValidityPeriod::decode_value(r#type, length, &mut value)?);
+                }
+                _ => {}
+            }
+        }
+        let expiration_period = expiration_period
+            .ok_or_else(|| DecodeError::invalid("PrefixAnnouncement without ExpirationPeriod"))?;
+
+        Ok(Self {
+            prefix,
+            expiration_period,
+            validity_period,
+            data,
+        })
+    }
+
+    fn to_data(
+        prefix: &Name,
+        version: u64,
+        expiration_period: ExpirationPeriod,
+        validity_period: Option<&ValidityPeriod>,
+    ) -> Data {
+        let name = prefix
+            .clone()
+            .append(KeywordNameComponent::new(Self::KEYWORD))
+            .append(VersionNameComponent::from(version))
+            .append(SegmentNameComponent::from(0));
+        let metainfo = MetaInfo {
+            content_type: Some(ContentType::PREFIX_ANN),
+            ..MetaInfo::default()
+        };
+        let mut content = BytesMut::new();
+        expiration_period.encode(&mut content);
+        if let Some(validity_period) = validity_period {
+            validity_period.encode(&mut content);
+        }
+        Data {
+            name,
+            metainfo: Some(metainfo),
+            content: Some(Content::new(content)),
+            data_signature: DataSignature::digest(),
+        }
+    }
+}
+
+fn is_keyword(component: &NameComponent) -> bool {
+    match component {
+        NameComponent::Keyword(keyword) => {
+            *keyword == KeywordNameComponent::new(PrefixAnnouncement::KEYWORD)
+        }
+        NameComponent::OtherType(other) => other.as_keyword().is_some_and(|keyword| {
+            keyword == KeywordNameComponent::new(PrefixAnnouncement::KEYWORD)
+        }),
+        _ => false,
+    }
+}
+
+impl PartialEq for PrefixAnnouncement {
+    fn eq(&self, other: &Self) -> bool {
+        self.data.name == other.data.name && self.validity_period == other.validity_period
+    }
+}
+
+// The PrefixAnnouncement LpPacket header field wraps the announcement Data
+impl Tlv for PrefixAnnouncement {
+    type Error = DecodeError;
+    const TYPE: Type = Type::PrefixAnnouncement;
+
+    fn length(&self) -> usize {
+        self.data.total_size()
+    }
+
+    fn encode_value(&self, dst: &mut BytesMut) {
+        self.data.encode(dst)
+    }
+
+    fn decode_value(r#type: Type, length: usize, src: &mut BytesMut) -> Result<Self, Self::Error> {
+        let _ = (r#type, length);
+        Data::decode(src).and_then(Self::from_data)
+    }
+}
+
+impl fmt::Display for PrefixAnnouncement {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let expiration_period = self.expiration_period.to_u64();
+        write!(
+            f,
+            "prefix_announcement=[{} {expiration_period}ms",
+            self.prefix
+        )?;
+        if let Some(validity_period) = &self.validity_period {
+            write!(f, " {validity_period}")?;
+        }
+        write!(f, "]")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn roundtrip() {
+        let now = SystemTime::now();
+        let hour = Duration::from_secs(3600);
+        let validity = ValidityPeriod::new(now - hour, now + hour);
+        let announcement =
+            PrefixAnnouncement::new("/ucla/videos".parse().unwrap(), Duration::from_secs(600))
+                .with_validity_period(validity);
+
+        let name = &announcement.data().name;
+        assert!(matches!(
+            name.components(),
+            [
+                _,
+                _,
+                NameComponent::Keyword(_),
+                NameComponent::Version(_),
+                NameComponent::Segment(_)
+            ]
+        ));
+
+        let mut bytes = BytesMut::from(announcement.bytes().as_ref());
+        let decoded = PrefixAnnouncement::decode(&mut bytes).unwrap();
+        assert!(bytes.is_empty());
+        assert_eq!(decoded, announcement);
+        assert_eq!(decoded.prefix().to_string(), "/ucla/videos");
+        assert_eq!(decoded.expiration_period(), Duration::from_secs(600));
+        assert!(decoded.is_valid_at(now));
+        assert!(!decoded.is_valid_at(now + 2 * hour));
+        // Route lasts for the ExpirationPeriod, or until the end of the ValidityPeriod
+        assert_eq!(decoded.route_lifetime(now), Duration::from_secs(600));
+        let late = decoded.route_lifetime(now + hour - Duration::from_secs(60));
+        assert!(late <= Duration::from_secs(61));
+    }
+
+    #[test]
+    fn timestamps() {
+        let time = SystemTime::UNIX_EPOCH + Duration::from_secs(1_700_000_000);
+        assert_eq!(format_timestamp(time), "20231114T221320");
+        assert_eq!(parse_timestamp("20231114T221320"), Some(time));
+        assert_eq!(parse_timestamp("20231314T221320"), None);
+        assert_eq!(parse_timestamp("2023-11-14T22:13:20"), None);
+    }
+
+    #[test]
+    fn invalid_announcements() {
+        let name = |keyword: &[u8]| {
+            "/ucla"
+                .parse::<Name>()
+                .unwrap()
+                .append(KeywordNameComponent::new(keyword))
+                .append(VersionNameComponent::from(1))
+                .append(SegmentNameComponent::from(0))
+        };
+
+        // Not a PrefixAnn
+        let mut data = Data::new("/ucla");
+        data.name = name(PrefixAnnouncement::KEYWORD);
+        assert!(PrefixAnnouncement::from_data(data).is_err());
+
+        // Wrong keyword
+        let mut data =
+            PrefixAnnouncement::new("/ucla".parse().unwrap(), Duration::from_secs(1)).into_data();
+        data.name = name(b"XX");
+        assert!(PrefixAnnouncement::from_data(data.clone()).is_err());
+
+        // Missing ExpirationPeriod
+        data.name = name(PrefixAnnouncement::KEYWORD);
+        let decoded = PrefixAnnouncement::from_data(data.clone()).unwrap();
+        assert_eq!(decoded.prefix().to_string(), "/ucla");
+        data.content = Some(Content::new([]));
+        assert!(PrefixAnnouncement::from_data(data).is_err());
+    }
+}
@@ -56,6 +56,7 @@ macro_rules! utf8_string {
                 src: &mut bytes::BytesMut,
             ) -> Result<Self, Self::Error> {
                 use $crate::TlvCodec;
+                let _ = (r#type, length);
                 String::decode(src)
                     .map(Self)
                     .map_err($crate::DecodeError::from)
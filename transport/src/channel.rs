@@ -1,21 +1,101 @@
+use std::time::Duration;
+
+use tokio::time;
+
 use super::*;
 
+/// Listening end accepting the transports initiated by the remote endpoints
 #[derive(Debug)]
 pub enum Channel {
+    Quic(quinn::Endpoint),
     Tcp(net::TcpListener),
+    Ws(net::TcpListener),
 }
 
 impl Channel {
+    /// Connections not through their handshake by then are dropped
+    pub const HANDSHAKE_TIMEOUT: Duration = Duration::from_secs(10);
+
     pub async fn new(local: face::Addr) -> io::Result<Self> {
         match local {
             face::Addr::Internal(_) => todo!(),
+            face::Addr::Quic(_) => Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "QUIC channel needs certificates, see Channel::quic",
+            )),
             face::Addr::Tcp(local) => Self::tcp(local.addr).await,
             face::Addr::Udp(_) => todo!(),
             face::Addr::Unix(_) => todo!(),
+            face::Addr::Ws(local) => Self::ws(local.addr).await,
         }
     }
 
+    /// QUIC channel presenting the certificate of `config` and accepting the clients
+    /// holding a trusted one
+    pub fn quic(local: SocketAddr, config: &QuicConfig) -> io::Result<Self> {
+        quinn::Endpoint::server(config.server_config()?, local).map(Self::Quic)
+    }
+
     async fn tcp(addr: impl net::ToSocketAddrs) -> io::Result<Self> {
         net::TcpListener::bind(addr).await.map(Self::Tcp)
     }
+
+    async fn ws(addr: impl net::ToSocketAddrs) -> io::Result<Self> {
+        net::TcpListener::bind(addr).await.map(Self::Ws)
+    }
+
+    pub fn local_uri(&self) -> io::Result<face::LocalUri> {
+        match self {
+            Self::Quic(endpoint) => socket_uri(face::Quic::PREFIX, endpoint.local_addr()),
+            Self::Tcp(listener) => socket_uri(face::Tcp::PREFIX, listener.local_addr()),
+            Self::Ws(listener) => socket_uri(face::Ws::PREFIX, listener.local_addr()),
+        }
+        .map(Into::into)
+    }
+
+    /// Wait for the next connection. The returned future completes its handshake, if any,
+    /// so that a slow client does not hold up accepting the others. The handshake fails
+    /// with `TimedOut` after [`Self::HANDSHAKE_TIMEOUT`].
+    pub async fn accept(&self) -> io::Result<BoxFuture<'static, io::Result<Box<dyn Transport>>>> {
+        let handshake = self.accept_connection().await?;
+        Ok(async move {
+            time::timeout(Self::HANDSHAKE_TIMEOUT, handshake)
+                .await
+                .map_err(|_| io::Error::new(io::ErrorKind::TimedOut, "Handshake timed out"))?
+        }
+        .boxed())
+    }
+
+    async fn accept_connection(
+        &self,
+    ) -> io::Result<BoxFuture<'static, io::Result<Box<dyn Transport>>>> {
+        match self {
+            Self::Quic(endpoint) => {
+                let incoming = endpoint
+                    .accept()
+                    .await
+                    .ok_or_else(|| io::Error::other("QUIC endpoint has been closed"))?;
+                let endpoint = endpoint.clone();
+                Ok(async move {
+                    let quic: Box<dyn Transport> =
+                        Box::new(Quic::accept(endpoint, incoming).await?);
+                    Ok(quic)
+                }
+                .boxed())
+            }
+            Self::Tcp(listener) => {
+                let (socket, _) = listener.accept().await?;
+                let tcp: Box<dyn Transport> = Box::new(Tcp::from_stream(socket));
+                Ok(futures::future::ready(Ok(tcp)).boxed())
+            }
+            Self::Ws(listener) => {
+                let (socket, _) = listener.accept().await?;
+                Ok(async move {
+                    let ws: Box<dyn Transport> = Box::new(Ws::accept(socket).await?);
+                    Ok(ws)
+                }
+                .boxed())
+            }
+        }
+    }
 }
@@ -2,12 +2,76 @@ use tokio_util::codec;
 
 use super::*;
 
-#[derive(Debug, Default)]
-pub struct TlvCodec;
+/// Frames network layer packets on a byte stream.
+///
+/// Only Interest, Data and LpPacket are accepted at the top level, each no larger than
+/// [`MAX_NDN_PACKET_SIZE`](face::Mtu::MAX_NDN_PACKET_SIZE) unless configured otherwise. A
+/// stream failing either check cannot be trusted to be in sync any longer, so the codec
+/// reports a [`FramingError`] once and discards whatever arrives afterwards.
+#[derive(Debug)]
+pub struct TlvCodec {
+    max_packet_size: usize,
+    corrupted: bool,
+}
 
 impl TlvCodec {
+    /// Packet types allowed at the top level of a stream
+    pub const TYPES: [tlv::Type; 3] = [tlv::Type::Interest, tlv::Type::Data, tlv::Type::LpPacket];
+
     pub fn new() -> Self {
-        Self
+        Self::default()
+    }
+
+    /// Largest packet accepted, TLV-TYPE and TLV-LENGTH included
+    pub fn with_max_packet_size(self, max_packet_size: usize) -> Self {
+        Self {
+            max_packet_size,
+            ..self
+        }
+    }
+
+    pub fn max_packet_size(&self) -> usize {
+        self.max_packet_size
+    }
+
+    /// Total size of the next packet, `None` until its header is complete. The TLV-TYPE is
+    /// checked as soon as it is known.
+    fn peek_size(&self, src: &[u8]) -> Result<Option<usize>, FramingError> {
+        let mut src = io::Cursor::new(src);
+        let Some(r#type) = tlv::Type::from_buf(&mut src) else {
+            return Ok(None);
+        };
+        if !Self::TYPES.contains(&r#type) {
+            return Err(FramingError::UnsupportedType(r#type));
+        }
+        let Some(length) = tlv::VarNumber::from_buf(&mut src) else {
+            return Ok(None);
+        };
+        let size = length.to_u64().saturating_add(src.position());
+        usize::try_from(size)
+            .ok()
+            .filter(|size| *size <= self.max_packet_size)
+            .map(Some)
+            .ok_or(FramingError::PacketTooLarge {
+                size,
+                max: self.max_packet_size,
+            })
+    }
+
+    fn corrupted(&mut self, src: &mut BytesMut, error: FramingError) -> io::Error {
+        tracing::debug!(%error, discarded = src.len(), "Corrupted stream");
+        self.corrupted = true;
+        src.clear();
+        error.into()
+    }
+}
+
+impl Default for TlvCodec {
+    fn default() -> Self {
+        Self {
+            max_packet_size: face::Mtu::MAX_NDN_PACKET_SIZE.to_usize(),
+            corrupted: false,
+        }
     }
 }
 
@@ -25,8 +89,58 @@ impl codec::Decoder for TlvCodec {
     type Error = io::Error;
 
     fn decode(&mut self, src: &mut BytesMut) -> Result<Option<Self::Item>, Self::Error> {
+        if self.corrupted {
+            src.clear();
+            return Ok(None);
+        }
+        let size = match self.peek_size(src) {
+            Ok(Some(size)) => size,
+            Ok(None) => return Ok(None),
+            Err(error) => return Err(self.corrupted(src, error)),
+        };
+        if src.len() < size {
+            src.reserve(size - src.len());
+            return Ok(None);
+        }
         Ok(tlv::Generic::from_bytes_mut(src))
     }
+
+    fn decode_eof(&mut self, src: &mut BytesMut) -> Result<Option<Self::Item>, Self::Error> {
+        match self.decode(src)? {
+            Some(item) => Ok(Some(item)),
+            None if src.is_empty() => Ok(None),
+            None => {
+                let error = FramingError::Truncated(src.len());
+                Err(self.corrupted(src, error))
+            }
+        }
+    }
+}
+
+/// Reason a byte stream or a datagram could not be split into packets.
+///
+/// Reported as the inner error of an [`io::ErrorKind::InvalidData`] error.
+#[derive(Clone, Debug, PartialEq, Eq, thiserror::Error)]
+pub enum FramingError {
+    #[error("Unsupported packet type {0}")]
+    UnsupportedType(tlv::Type),
+    #[error("{size} octet packet exceeds {max} octets")]
+    PacketTooLarge { size: u64, max: usize },
+    #[error("Stream ended {0} octets into a packet")]
+    Truncated(usize),
+}
+
+impl FramingError {
+    /// Framing error behind `error`, if any
+    pub fn from_io(error: &io::Error) -> Option<&Self> {
+        error.get_ref()?.downcast_ref()
+    }
+}
+
+impl From<FramingError> for io::Error {
+    fn from(error: FramingError) -> Self {
+        Self::new(io::ErrorKind::InvalidData, error)
+    }
 }
 
 #[cfg(test)]
@@ -57,3 +57,121 @@ fn decode_value() {
     assert!(data.metainfo.is_some());
     assert!(data.content.is_some());
 }
+
+fn interest(name: &str) -> Bytes {
+    tlv::TlvCodec::bytes(&tlv::Interest::new(name))
+}
+
+fn framing_error(error: &io::Error) -> Option<FramingError> {
+    assert_eq!(error.kind(), io::ErrorKind::InvalidData);
+    FramingError::from_io(error).cloned()
+}
+
+#[test]
+fn partial_reads() {
+    let mut codec = TlvCodec::new();
+    let packets = [interest("/a"), interest("/b")].concat();
+    let mut src = BytesMut::new();
+    let mut items = Vec::new();
+
+    // One octet at a time, the header itself split across reads
+    for octet in packets {
+        src.extend_from_slice(&[octet]);
+        if let Some(item) = codec.decode(&mut src).unwrap() {
+            items.push(item);
+        }
+    }
+    assert_eq!(items.len(), 2);
+    assert!(src.is_empty());
+    assert!(codec.decode_eof(&mut src).unwrap().is_none());
+
+    // Stream ending in the middle of a packet
+    let packet = interest("/c");
+    let mut src = BytesMut::from(&packet[..packet.len() - 1]);
+    assert!(codec.decode(&mut src).unwrap().is_none());
+    let error = codec.decode_eof(&mut src).unwrap_err();
+    assert_eq!(
+        framing_error(&error),
+        Some(FramingError::Truncated(packet.len() - 1))
+    );
+}
+
+#[test]
+fn oversized_packets() {
+    // A huge TLV-LENGTH is rejected from the header alone, before any buffering
+    let mut src = BytesMut::from(&[5, 255, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff][..]);
+    let error = TlvCodec::new().decode(&mut src).unwrap_err();
+    assert!(matches!(
+        framing_error(&error),
+        Some(FramingError::PacketTooLarge { max: 8800, .. })
+    ));
+
+    let mut data = tlv::Data::new("/big");
+    data.content = Some(tlv::Content::new(vec![0; 8800]));
+    let mut src = BytesMut::from(tlv::TlvCodec::bytes(&data).as_ref());
+    let error = TlvCodec::new().decode(&mut src).unwrap_err();
+    assert!(matches!(
+        framing_error(&error),
+        Some(FramingError::PacketTooLarge { .. })
+    ));
+
+    let mut codec = TlvCodec::new().with_max_packet_size(10000);
+    assert!(codec.decode(&mut src).is_ok());
+}
+
+#[test]
+fn unsupported_types() {
+    // Name is not a network layer packet
+    let name: tlv::Name = "/a".parse().unwrap();
+    let mut src = BytesMut::from(tlv::TlvCodec::bytes(&name).as_ref());
+    let error = TlvCodec::new().decode(&mut src).unwrap_err();
+    assert_eq!(
+        framing_error(&error),
+        Some(FramingError::UnsupportedType(tlv::Type::Name))
+    );
+
+    // Rejected before the rest of the header arrives
+    let mut src = BytesMut::from(&[0xff; 9][..]);
+    let error = TlvCodec::new().decode(&mut src).unwrap_err();
+    assert!(matches!(
+        framing_error(&error),
+        Some(FramingError::UnsupportedType(_))
+    ));
+
+    // Garbage
+    let mut src = BytesMut::from(&b"GET / HTTP/1.1\r\n"[..]);
+    let error = TlvCodec::new().decode(&mut src).unwrap_err();
+    assert!(matches!(
+        framing_error(&error),
+        Some(FramingError::UnsupportedType(_))
+    ));
+}
+
+#[test]
+fn discard_after_corruption() {
+    let mut codec = TlvCodec::new();
+    let mut src = BytesMut::from(&[0, 0][..]);
+    assert!(codec.decode(&mut src).is_err());
+    assert!(src.is_empty());
+
+    // Valid packets no longer come out, the stream is out of sync
+    src.extend_from_slice(&interest("/a"));
+    assert!(codec.decode(&mut src).unwrap().is_none());
+    assert!(src.is_empty());
+}
+
+#[tokio::test]
+async fn stream_ends_on_corruption() {
+    let (mut one, other) = tokio::io::duplex(64);
+    let mut framed = Framed::new(other, TlvCodec::new());
+    tokio::io::AsyncWriteExt::write_all(&mut one, &interest("/a"))
+        .await
+        .unwrap();
+    tokio::io::AsyncWriteExt::write_all(&mut one, &[0; 16])
+        .await
+        .unwrap();
+
+    assert!(framed.try_next().await.unwrap().is_some());
+    assert!(framed.try_next().await.is_err());
+    assert!(framed.try_next().await.unwrap().is_none());
+}
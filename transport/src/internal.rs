@@ -1,51 +1,53 @@
 use tokio::sync::mpsc;
 use tokio::sync::Mutex;
+use tokio_util::codec;
+use tokio_util::sync::PollSender;
 
 use super::*;
 
+/// In-process link, each message carries a single packet
 #[derive(Debug)]
 pub struct Internal {
     tx: mpsc::Sender<Bytes>,
+    sink: PollSender<Bytes>,
     rx: Mutex<mpsc::Receiver<Bytes>>,
 }
 
 impl Internal {
-    pub(super) async fn new() -> io::Result<Self> {
-        let (tx, rx) = mpsc::channel(16);
-        let rx = Mutex::new(rx);
-        Ok(Self { tx, rx })
-    }
+    const CAPACITY: usize = 16;
 
-    fn local_addr(&self) -> &'static str {
-        ""
+    /// Two ends of an in-process link, whatever is sent on one end is received on the other
+    pub fn pair() -> (Self, Self) {
+        let (tx1, rx1) = mpsc::channel(Self::CAPACITY);
+        let (tx2, rx2) = mpsc::channel(Self::CAPACITY);
+        (Self::new(tx1, rx2), Self::new(tx2, rx1))
     }
 
-    pub(super) fn face_uri(&self) -> io::Result<String> {
-        let uri = format!(
-            "{}{}{}",
-            face::Internal::PREFIX,
-            face::URI_DELIMITER,
-            self.local_addr(),
-        );
-        Ok(uri)
+    /// Link looped back onto itself, whatever is sent is received on the same end
+    pub(crate) fn loopback() -> Self {
+        let (tx, rx) = mpsc::channel(Self::CAPACITY);
+        Self::new(tx, rx)
     }
 
-    pub(super) async fn send_item(&mut self, item: impl tlv::Tlv) -> io::Result<()> {
-        let _ = item;
-        todo!()
+    fn new(tx: mpsc::Sender<Bytes>, rx: mpsc::Receiver<Bytes>) -> Self {
+        let sink = PollSender::new(tx.clone());
+        let rx = Mutex::new(rx);
+        Self { tx, sink, rx }
     }
 
-    pub(super) async fn recv_item(&mut self) -> io::Result<Option<tlv::Generic>> {
-        Ok(None)
+    fn face_uri() -> String {
+        format!("{}{}", face::Internal::PREFIX, face::URI_DELIMITER)
     }
 
+    /// Send a single encoded packet through a shared end of the link
     #[tracing::instrument(level = "trace", skip_all, err(level = "error"))]
-    pub(super) async fn send(&self, bytes: Bytes) -> io::Result<()> {
+    pub async fn send(&self, bytes: Bytes) -> io::Result<()> {
         self.tx.send(bytes).await.map_err(io::Error::other)
     }
 
+    /// Next encoded packet from a shared end of the link
     #[tracing::instrument(level = "trace", skip_all, err(level = "error"))]
-    pub(super) async fn recv(&self, _bytes: BytesMut) -> io::Result<Bytes> {
+    pub async fn recv(&self) -> io::Result<Bytes> {
         self.rx
             .lock()
             .await
@@ -53,4 +55,97 @@ impl Internal {
             .await
             .ok_or_else(|| io::Error::other("channel has been closed"))
     }
+
+    /// Messages are not streams, a message must hold exactly one TLV
+    fn decode(bytes: &[u8]) -> io::Result<tlv::Generic> {
+        let mut src = BytesMut::from(bytes);
+        codec::Decoder::decode(&mut TlvCodec::new(), &mut src)?
+            .filter(|_| src.is_empty())
+            .ok_or_else(|| {
+                io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    "Internal message must hold exactly one packet",
+                )
+            })
+    }
+}
+
+impl Transport for Internal {
+    fn local_uri(&self) -> io::Result<face::LocalUri> {
+        Ok(Self::face_uri().into())
+    }
+
+    fn remote_uri(&self) -> io::Result<face::Uri> {
+        Ok(Self::face_uri().into())
+    }
+
+    fn face_scope(&self) -> face::FaceScope {
+        face::FaceScope::Local
+    }
+
+    fn persistency(&self) -> face::FacePersistency {
+        face::FacePersistency::OnDemand
+    }
+}
+
+impl Stream for Internal {
+    type Item = io::Result<tlv::Generic>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        self.get_mut()
+            .rx
+            .get_mut()
+            .poll_recv(cx)
+            .map(|bytes| bytes.map(|bytes| Self::decode(&bytes)))
+    }
+}
+
+impl Sink<Bytes> for Internal {
+    type Error = io::Error;
+
+    fn poll_ready(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Pin::new(&mut self.get_mut().sink)
+            .poll_ready(cx)
+            .map_err(io::Error::other)
+    }
+
+    fn start_send(self: Pin<&mut Self>, bytes: Bytes) -> io::Result<()> {
+        Pin::new(&mut self.get_mut().sink)
+            .start_send(bytes)
+            .map_err(io::Error::other)
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Pin::new(&mut self.get_mut().sink)
+            .poll_flush(cx)
+            .map_err(io::Error::other)
+    }
+
+    fn poll_close(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Pin::new(&mut self.get_mut().sink)
+            .poll_close(cx)
+            .map_err(io::Error::other)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn pair() {
+        let (mut one, mut other) = Internal::pair();
+        one.send_item(tlv::Interest::new("/a")).await.unwrap();
+        let item = other.recv_item().await.unwrap().unwrap();
+        assert_eq!(item.r#type, tlv::Type::Interest);
+
+        // Messages are not streams, partial packets are rejected
+        let bytes = tlv::TlvCodec::bytes(&tlv::Interest::new("/b"));
+        other.send(bytes.slice(..bytes.len() - 1)).await.unwrap();
+        let error = one.recv_item().await.unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::InvalidData);
+
+        drop(other);
+        assert!(one.recv_item().await.unwrap().is_none());
+    }
 }
@@ -1,9 +1,16 @@
+use std::fmt;
 use std::io;
 use std::net::SocketAddr;
+use std::pin::Pin;
+use std::task::{Context, Poll};
 
 use bytes::Bytes;
 use bytes::BytesMut;
+use futures::future::BoxFuture;
+use futures::FutureExt;
+use futures::Sink;
 use futures::SinkExt;
+use futures::Stream;
 use futures::TryStreamExt;
 use tokio::net;
 use tokio_util::codec::Framed;
@@ -12,97 +19,148 @@ use ndn_face as face;
 use ndn_tlv as tlv;
 
 pub use channel::Channel;
+pub use codec::FramingError;
 pub use codec::TlvCodec;
-
-use internal::Internal;
-use tcp::Tcp;
-use udp::Udp;
-use unix::Unix;
+pub use internal::Internal;
+pub use multicast::UdpMulticast;
+pub use quic::Quic;
+pub use quic::QuicConfig;
+pub use quic::QuicFraming;
+pub use registry::Connector;
+pub use registry::Registry;
+pub use split::ReadHalf;
+pub use split::WriteHalf;
+pub use tcp::Tcp;
+pub use tcp::TcpOptions;
+pub use udp::Udp;
+pub use unix::Unix;
+pub use ws::Ws;
 
 mod channel;
 mod codec;
 mod internal;
+mod multicast;
+mod queue;
+mod quic;
+mod registry;
+mod split;
 mod tcp;
 mod udp;
 mod unix;
+mod ws;
+
+/// Packet link to a remote endpoint.
+///
+/// Incoming packets come out of the [`Stream`], one TLV at a time, and `None` once the other
+/// end has gone away. Outgoing packets go into the [`Sink`] already encoded, one TLV per item.
+/// Framing the packets on the wire is up to the transport.
+pub trait Transport:
+    Stream<Item = io::Result<tlv::Generic>> + Sink<Bytes, Error = io::Error> + fmt::Debug + Send + Unpin
+{
+    fn local_uri(&self) -> io::Result<face::LocalUri>;
+
+    fn remote_uri(&self) -> io::Result<face::Uri>;
+
+    /// Largest packet the transport is able to send at once, `None` if unlimited as on streams
+    fn mtu(&self) -> Option<face::Mtu> {
+        None
+    }
+
+    fn face_scope(&self) -> face::FaceScope {
+        face::FaceScope::NonLocal
+    }
+
+    fn link_type(&self) -> face::LinkType {
+        face::LinkType::PointToPoint
+    }
 
-#[derive(Debug)]
-pub enum Transport {
-    Internal(Internal),
-    Tcp(Tcp),
-    Udp(Udp),
-    Unix(Unix),
+    /// Persistency of the face over this transport, unless asked for otherwise
+    fn persistency(&self) -> face::FacePersistency {
+        face::FacePersistency::Persistent
+    }
+
+    /// Number of octets queued for sending, if the transport is able to tell
+    fn send_queue_length(&self) -> Option<usize> {
+        None
+    }
+
+    /// Re-establish the link once it has gone down, as permanent faces do. Fails with
+    /// `Unsupported` unless the transport is able to.
+    fn reconnect(&mut self) -> BoxFuture<'_, io::Result<()>> {
+        let error = io::Error::new(io::ErrorKind::Unsupported, "Transport cannot reconnect");
+        futures::future::ready(Err(error)).boxed()
+    }
 }
 
-impl Transport {
-    #[tracing::instrument]
-    pub async fn new(local: face::Addr, remote: face::Addr) -> io::Result<Self> {
-        match (local, remote) {
-            (face::Addr::Internal(_), face::Addr::Internal(_)) => {
-                Internal::new().await.map(Self::Internal)
-            }
-            (face::Addr::Tcp(local), face::Addr::Tcp(remote)) => {
-                Tcp::new(local.addr, remote.addr).await.map(Self::Tcp)
-            }
-            (face::Addr::Udp(local), face::Addr::Udp(remote)) => {
-                Udp::new(local.addr, remote.addr).await.map(Self::Udp)
-            }
-            (face::Addr::Unix(_local), face::Addr::Unix(remote)) => {
-                Unix::new(remote.path).await.map(Self::Unix)
-            }
-            _ => Err(io::Error::other("Invalid local/remote combination")),
-        }
+impl<T: Transport + ?Sized> Transport for Box<T> {
+    fn local_uri(&self) -> io::Result<face::LocalUri> {
+        (**self).local_uri()
     }
 
-    pub fn local_uri(&self) -> io::Result<face::LocalUri> {
-        let text = match self {
-            Self::Internal(internal) => internal.face_uri(),
-            Self::Tcp(tcp) => tcp.face_uri(),
-            Self::Udp(udp) => udp.face_uri(),
-            Self::Unix(unix) => unix.face_uri(),
-        };
-        text.map(Into::into)
+    fn remote_uri(&self) -> io::Result<face::Uri> {
+        (**self).remote_uri()
     }
 
-    pub fn mtu(&self) -> face::Mtu {
-        face::Mtu::MAX_NDN_PACKET_SIZE
+    fn mtu(&self) -> Option<face::Mtu> {
+        (**self).mtu()
     }
 
-    pub async fn send_item(&mut self, item: impl tlv::Tlv) -> io::Result<()> {
-        match self {
-            Self::Internal(internal) => internal.send_item(item).await,
-            Self::Tcp(tcp) => tcp.send_item(item).await,
-            Self::Udp(udp) => udp.send_item(item).await,
-            Self::Unix(unix) => unix.send_item(item).await,
-        }
+    fn face_scope(&self) -> face::FaceScope {
+        (**self).face_scope()
     }
 
-    pub async fn recv_item(&mut self) -> io::Result<Option<tlv::Generic>> {
-        match self {
-            Self::Internal(internal) => internal.recv_item().await,
-            Self::Tcp(tcp) => tcp.recv_item().await,
-            Self::Udp(udp) => udp.recv_item().await,
-            Self::Unix(unix) => unix.recv_item().await,
-        }
+    fn link_type(&self) -> face::LinkType {
+        (**self).link_type()
     }
 
-    #[tracing::instrument]
-    pub async fn send(&self, bytes: Bytes) -> io::Result<()> {
-        match self {
-            Self::Internal(internal) => internal.send(bytes).await,
-            Self::Tcp(tcp) => tcp.send(bytes).await,
-            Self::Udp(udp) => udp.send(bytes).await,
-            Self::Unix(unix) => unix.send(bytes).await,
-        }
+    fn persistency(&self) -> face::FacePersistency {
+        (**self).persistency()
+    }
+
+    fn send_queue_length(&self) -> Option<usize> {
+        (**self).send_queue_length()
+    }
+
+    fn reconnect(&mut self) -> BoxFuture<'_, io::Result<()>> {
+        (**self).reconnect()
+    }
+}
+
+/// Packet level helpers for any [`Transport`]
+pub trait TransportExt: Transport {
+    /// Encode and send a single packet
+    fn send_item(&mut self, item: impl tlv::Tlv) -> futures::sink::Send<'_, Self, Bytes> {
+        let bytes = tlv::TlvCodec::bytes(&item);
+        SinkExt::send(self, bytes)
     }
 
-    #[tracing::instrument]
-    pub async fn recv(&self, bytes: BytesMut) -> io::Result<Bytes> {
-        match self {
-            Self::Internal(internal) => internal.recv(bytes).await,
-            Self::Tcp(tcp) => tcp.recv(bytes).await,
-            Self::Udp(udp) => udp.recv(bytes).await,
-            Self::Unix(unix) => unix.recv(bytes).await,
+    /// Next packet, `None` once the other end has gone away
+    fn recv_item(&mut self) -> futures::stream::TryNext<'_, Self> {
+        TryStreamExt::try_next(self)
+    }
+
+    /// Independent receiving and sending halves, so that waiting for the next packet does
+    /// not hold up sending and the other way round
+    fn split(self) -> (ReadHalf<Self>, WriteHalf<Self>)
+    where
+        Self: Sized,
+    {
+        split::split(self)
+    }
+}
+
+impl<T: Transport + ?Sized> TransportExt for T {}
+
+/// Faces to local applications are local, so are the ones talking over loopback
+fn scope_of(local: io::Result<SocketAddr>, remote: io::Result<SocketAddr>) -> face::FaceScope {
+    match (local, remote) {
+        (Ok(local), Ok(remote)) if local.ip().is_loopback() && remote.ip().is_loopback() => {
+            face::FaceScope::Local
         }
+        _ => face::FaceScope::NonLocal,
     }
 }
+
+fn socket_uri(prefix: &str, addr: io::Result<SocketAddr>) -> io::Result<String> {
+    Ok(format!("{prefix}{}{}", face::URI_DELIMITER, addr?))
+}
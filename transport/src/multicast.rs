@@ -0,0 +1,188 @@
+use std::net::Ipv4Addr;
+use std::net::SocketAddrV4;
+
+use socket2::{Domain, Protocol, Socket, Type};
+use tokio::io::ReadBuf;
+
+use super::*;
+
+/// Multicast group on the local segment. Packets are sent to the group and received from any
+/// of its members, one packet per datagram.
+#[derive(Debug)]
+pub struct UdpMulticast {
+    group: SocketAddrV4,
+    /// bound to the group, receives from all the members
+    recv: net::UdpSocket,
+    /// connected to the group, its address tells our own datagrams looped back apart
+    send: net::UdpSocket,
+    own: SocketAddr,
+    mtu: face::Mtu,
+    buffer: Box<[u8]>,
+    /// datagram accepted by the sink, not sent yet
+    pending: Option<Bytes>,
+}
+
+impl UdpMulticast {
+    /// Join `group` on the interface owning the `interface` address,
+    /// the one picked by the routing table if unspecified
+    pub fn new(interface: Ipv4Addr, group: SocketAddrV4) -> io::Result<Self> {
+        if !group.ip().is_multicast() {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("{group} is not a multicast group"),
+            ));
+        }
+
+        let recv = Socket::new(Domain::IPV4, Type::DGRAM, Some(Protocol::UDP))?;
+        // All the members on this host share the group port
+        recv.set_reuse_address(true)?;
+        recv.bind(&SocketAddr::V4(group).into())?;
+        recv.join_multicast_v4(group.ip(), &interface)?;
+        recv.set_nonblocking(true)?;
+        let recv = net::UdpSocket::from_std(recv.into())?;
+
+        let send = Socket::new(Domain::IPV4, Type::DGRAM, Some(Protocol::UDP))?;
+        send.set_multicast_if_v4(&interface)?;
+        // Local segment only, the other members on this host included
+        send.set_multicast_ttl_v4(1)?;
+        send.set_multicast_loop_v4(true)?;
+        send.bind(&SocketAddr::from((interface, 0)).into())?;
+        send.connect(&SocketAddr::V4(group).into())?;
+        send.set_nonblocking(true)?;
+        let send = net::UdpSocket::from_std(send.into())?;
+
+        let own = send.local_addr()?;
+        let mtu = Udp::compute_mtu(&send);
+        let buffer = vec![0; Udp::MAX_DATAGRAM_SIZE].into_boxed_slice();
+        tracing::debug!(%group, %own, "Joined multicast group");
+        Ok(Self {
+            group,
+            recv,
+            send,
+            own,
+            mtu,
+            buffer,
+            pending: None,
+        })
+    }
+
+    fn poll_send_pending(&mut self, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        let Some(bytes) = &self.pending else {
+            return Poll::Ready(Ok(()));
+        };
+        let count = futures::ready!(self.send.poll_send(cx, bytes))?;
+        let sent = count == bytes.len();
+        self.pending = None;
+        if sent {
+            Poll::Ready(Ok(()))
+        } else {
+            Poll::Ready(Err(io::Error::other("Failed to send UDP packet")))
+        }
+    }
+}
+
+impl Transport for UdpMulticast {
+    fn local_uri(&self) -> io::Result<face::LocalUri> {
+        socket_uri(face::Udp::PREFIX, Ok(self.own)).map(Into::into)
+    }
+
+    fn remote_uri(&self) -> io::Result<face::Uri> {
+        socket_uri(face::Udp::PREFIX, Ok(SocketAddr::V4(self.group))).map(Into::into)
+    }
+
+    /// Largest packet sent in a single datagram
+    fn mtu(&self) -> Option<face::Mtu> {
+        Some(self.mtu)
+    }
+
+    fn link_type(&self) -> face::LinkType {
+        face::LinkType::MultiAccess
+    }
+
+    /// The group is there for as long as we are a member
+    fn persistency(&self) -> face::FacePersistency {
+        face::FacePersistency::Permanent
+    }
+
+    fn send_queue_length(&self) -> Option<usize> {
+        queue::outq(&self.send)
+    }
+}
+
+impl Stream for UdpMulticast {
+    type Item = io::Result<tlv::Generic>;
+
+    /// Receive the packet carried by the next datagram of the other members
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let this = self.get_mut();
+        loop {
+            let mut buf = ReadBuf::new(&mut this.buffer);
+            let from = futures::ready!(this.recv.poll_recv_from(cx, &mut buf))?;
+            if from == this.own {
+                continue;
+            }
+            tracing::trace!(count = buf.filled().len(), %from, "Got datagram");
+            return Poll::Ready(Some(Udp::decode(buf.filled())));
+        }
+    }
+}
+
+impl Sink<Bytes> for UdpMulticast {
+    type Error = io::Error;
+
+    fn poll_ready(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        self.get_mut().poll_send_pending(cx)
+    }
+
+    /// Queue `bytes` as a single datagram, unless it does not fit into the MTU
+    fn start_send(self: Pin<&mut Self>, bytes: Bytes) -> io::Result<()> {
+        let mtu = self.mtu.to_usize();
+        if bytes.len() > mtu {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("{} octet packet exceeds MTU {mtu}", bytes.len()),
+            ));
+        }
+        self.get_mut().pending = Some(bytes);
+        Ok(())
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        self.get_mut().poll_send_pending(cx)
+    }
+
+    fn poll_close(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        self.get_mut().poll_send_pending(cx)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn loopback_group() {
+        let group = SocketAddrV4::new(Ipv4Addr::new(224, 0, 23, 170), 56363);
+        let interface = Ipv4Addr::LOCALHOST;
+        let mut a = UdpMulticast::new(interface, group).unwrap();
+        let mut b = UdpMulticast::new(interface, group).unwrap();
+        let mut c = UdpMulticast::new(interface, group).unwrap();
+        assert_eq!(a.link_type(), face::LinkType::MultiAccess);
+        assert_eq!(
+            a.remote_uri().unwrap().to_string(),
+            b.remote_uri().unwrap().to_string()
+        );
+
+        // Every other member gets the packet, the sender does not
+        a.send_item(tlv::Interest::new("/a")).await.unwrap();
+        for member in [&mut b, &mut c] {
+            let item = member.recv_item().await.unwrap().unwrap();
+            assert_eq!(item.r#type, tlv::Type::Interest);
+        }
+        b.send_item(tlv::Data::new("/a")).await.unwrap();
+        let item = a.recv_item().await.unwrap().unwrap();
+        assert_eq!(item.r#type, tlv::Type::Data);
+
+        assert!(UdpMulticast::new(interface, SocketAddrV4::new(interface, 56363)).is_err());
+    }
+}
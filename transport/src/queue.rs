@@ -0,0 +1,20 @@
+use std::os::fd::AsRawFd;
+
+/// Number of unsent octets in the socket send buffer (SIOCOUTQ)
+#[cfg(target_os = "linux")]
+pub(crate) fn outq(socket: &impl AsRawFd) -> Option<usize> {
+    let mut outq: libc::c_int = 0;
+    // SAFETY: TIOCOUTQ (aka SIOCOUTQ) only writes a single c_int into the supplied pointer
+    let rc = unsafe { libc::ioctl(socket.as_raw_fd(), libc::TIOCOUTQ, &mut outq) };
+    if rc == 0 {
+        usize::try_from(outq).ok()
+    } else {
+        None
+    }
+}
+
+#[cfg(not(target_os = "linux"))]
+pub(crate) fn outq(socket: &impl AsRawFd) -> Option<usize> {
+    let _ = socket;
+    None
+}
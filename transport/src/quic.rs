@@ -0,0 +1,497 @@
+use std::future::Future;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::Duration;
+
+use futures::stream;
+use futures::stream::BoxStream;
+use futures::stream::FuturesUnordered;
+use futures::StreamExt;
+use rustls::pki_types::pem::PemObject;
+use rustls::pki_types::{CertificateDer, PrivateKeyDer, ServerName};
+use tokio_util::codec;
+
+use super::*;
+
+/// How the packets are carried over a QUIC connection, agreed upon by ALPN
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum QuicFraming {
+    /// One packet per unreliable datagram, the larger ones are fragmented by the face
+    #[default]
+    Datagram,
+    /// One packet per unidirectional stream, delivered reliably but in no particular order,
+    /// so that a lost packet does not hold up the others
+    Stream,
+}
+
+impl QuicFraming {
+    const ALL: [Self; 2] = [Self::Datagram, Self::Stream];
+
+    fn alpn(self) -> &'static [u8] {
+        match self {
+            Self::Datagram => b"ndn-datagram",
+            Self::Stream => b"ndn-stream",
+        }
+    }
+
+    fn from_alpn(alpn: &[u8]) -> Option<Self> {
+        Self::ALL.into_iter().find(|framing| framing.alpn() == alpn)
+    }
+}
+
+/// Certificate, private key and trusted certificates of a QUIC endpoint, all PEM files.
+/// Both ends present their certificate, which the other end verifies against its trusted ones.
+#[derive(Clone, Debug)]
+pub struct QuicConfig {
+    certificate: PathBuf,
+    private_key: PathBuf,
+    trusted: PathBuf,
+    framing: QuicFraming,
+}
+
+impl QuicConfig {
+    /// Idle links are kept alive, as the connections are closed after 30 s of silence
+    const KEEP_ALIVE_INTERVAL: Duration = Duration::from_secs(10);
+
+    pub fn new(
+        certificate: impl Into<PathBuf>,
+        private_key: impl Into<PathBuf>,
+        trusted: impl Into<PathBuf>,
+    ) -> Self {
+        Self {
+            certificate: certificate.into(),
+            private_key: private_key.into(),
+            trusted: trusted.into(),
+            framing: QuicFraming::default(),
+        }
+    }
+
+    /// Framing asked for on the outgoing connections, the channels accept either
+    pub fn with_framing(self, framing: QuicFraming) -> Self {
+        Self { framing, ..self }
+    }
+
+    pub fn framing(&self) -> QuicFraming {
+        self.framing
+    }
+
+    fn client_config(&self) -> io::Result<quinn::ClientConfig> {
+        let mut config = rustls::ClientConfig::builder_with_provider(provider())
+            .with_protocol_versions(&[&rustls::version::TLS13])
+            .map_err(tls_error)?
+            .with_root_certificates(self.roots()?)
+            .with_client_auth_cert(self.certificates()?, self.private_key()?)
+            .map_err(tls_error)?;
+        config.alpn_protocols = vec![self.framing.alpn().to_vec()];
+        let crypto =
+            quinn::crypto::rustls::QuicClientConfig::try_from(config).map_err(io::Error::other)?;
+        let mut config = quinn::ClientConfig::new(Arc::new(crypto));
+        config.transport_config(Self::transport_config());
+        Ok(config)
+    }
+
+    pub(crate) fn server_config(&self) -> io::Result<quinn::ServerConfig> {
+        let verifier = rustls::server::WebPkiClientVerifier::builder_with_provider(
+            Arc::new(self.roots()?),
+            provider(),
+        )
+        .build()
+        .map_err(tls_error)?;
+        let mut config = rustls::ServerConfig::builder_with_provider(provider())
+            .with_protocol_versions(&[&rustls::version::TLS13])
+            .map_err(tls_error)?
+            .with_client_cert_verifier(verifier)
+            .with_single_cert(self.certificates()?, self.private_key()?)
+            .map_err(tls_error)?;
+        config.alpn_protocols = QuicFraming::ALL
+            .map(|framing| framing.alpn().to_vec())
+            .to_vec();
+        let crypto =
+            quinn::crypto::rustls::QuicServerConfig::try_from(config).map_err(io::Error::other)?;
+        let mut config = quinn::ServerConfig::with_crypto(Arc::new(crypto));
+        config.transport_config(Self::transport_config());
+        Ok(config)
+    }
+
+    fn transport_config() -> Arc<quinn::TransportConfig> {
+        let mut config = quinn::TransportConfig::default();
+        config.keep_alive_interval(Some(Self::KEEP_ALIVE_INTERVAL));
+        Arc::new(config)
+    }
+
+    fn certificates(&self) -> io::Result<Vec<CertificateDer<'static>>> {
+        CertificateDer::pem_file_iter(&self.certificate)
+            .and_then(Iterator::collect)
+            .map_err(|error| pem_error(&self.certificate, error))
+    }
+
+    fn private_key(&self) -> io::Result<PrivateKeyDer<'static>> {
+        PrivateKeyDer::from_pem_file(&self.private_key)
+            .map_err(|error| pem_error(&self.private_key, error))
+    }
+
+    fn roots(&self) -> io::Result<rustls::RootCertStore> {
+        let trusted = CertificateDer::pem_file_iter(&self.trusted)
+            .and_then(Iterator::collect::<Result<Vec<_>, _>>)
+            .map_err(|error| pem_error(&self.trusted, error))?;
+        let mut roots = rustls::RootCertStore::empty();
+        for certificate in trusted {
+            roots.add(certificate).map_err(tls_error)?;
+        }
+        Ok(roots)
+    }
+}
+
+/// Encrypted link over a QUIC connection
+#[derive(Debug)]
+pub struct Quic {
+    endpoint: quinn::Endpoint,
+    connection: quinn::Connection,
+    framing: QuicFraming,
+    incoming: Incoming,
+    /// packets on their way over their own streams
+    sending: Sending,
+    /// where the connection has been made to, if made by us
+    dialed: Option<(SocketAddr, String, QuicConfig)>,
+}
+
+struct Incoming(BoxStream<'static, io::Result<tlv::Generic>>);
+
+struct Sending(FuturesUnordered<BoxFuture<'static, io::Result<()>>>);
+
+impl Quic {
+    /// Streams carrying the packets being sent at once, further packets wait for their turn
+    const MAX_CONCURRENT_SENDS: usize = 64;
+    /// Streams being received at once
+    const MAX_CONCURRENT_RECEIVES: usize = 64;
+
+    /// Connect from `local` to `remote`, verifying that its certificate is valid for
+    /// `server_name`
+    pub async fn new(
+        local: SocketAddr,
+        remote: SocketAddr,
+        server_name: &str,
+        config: &QuicConfig,
+    ) -> io::Result<Self> {
+        let endpoint = quinn::Endpoint::client(local)?;
+        let connection = dial(&endpoint, remote, server_name, config).await?;
+        let dialed = Some((remote, server_name.to_string(), config.clone()));
+        Ok(Self {
+            dialed,
+            ..Self::with_connection(endpoint, connection, config.framing)
+        })
+    }
+
+    /// Transport over a connection accepted by `endpoint`, framed the way the client asked for
+    pub async fn accept(endpoint: quinn::Endpoint, incoming: quinn::Incoming) -> io::Result<Self> {
+        let connection = incoming.await?;
+        let framing = connection
+            .handshake_data()
+            .and_then(|data| data.downcast::<quinn::crypto::rustls::HandshakeData>().ok())
+            .and_then(|data| data.protocol)
+            .and_then(|alpn| QuicFraming::from_alpn(&alpn))
+            .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, "Unknown QUIC framing"))?;
+        tracing::debug!(remote = %connection.remote_address(), ?framing, "Accepted");
+        Ok(Self::with_connection(endpoint, connection, framing))
+    }
+
+    fn with_connection(
+        endpoint: quinn::Endpoint,
+        connection: quinn::Connection,
+        framing: QuicFraming,
+    ) -> Self {
+        let incoming = Incoming::new(&connection, framing);
+        Self {
+            endpoint,
+            connection,
+            framing,
+            incoming,
+            sending: Sending(FuturesUnordered::new()),
+            dialed: None,
+        }
+    }
+
+    pub fn framing(&self) -> QuicFraming {
+        self.framing
+    }
+
+    /// Connect again to the same remote with the same certificates
+    pub async fn redial(&mut self) -> io::Result<()> {
+        let Some((remote, server_name, config)) = &self.dialed else {
+            return Err(io::Error::new(
+                io::ErrorKind::Unsupported,
+                "Accepted connections cannot be re-established",
+            ));
+        };
+        let connection = dial(&self.endpoint, *remote, server_name, config).await?;
+        self.incoming = Incoming::new(&connection, self.framing);
+        self.sending = Sending(FuturesUnordered::new());
+        self.connection = connection;
+        Ok(())
+    }
+
+    /// Messages are not streams, a message must hold exactly one TLV
+    fn decode(message: &[u8]) -> io::Result<tlv::Generic> {
+        let mut src = BytesMut::from(message);
+        codec::Decoder::decode(&mut TlvCodec::new(), &mut src)?
+            .filter(|_| src.is_empty())
+            .ok_or_else(|| {
+                io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    "QUIC message must hold exactly one packet",
+                )
+            })
+    }
+
+    /// Wait for the packets being sent until no more than `limit` are left
+    fn poll_sent(&mut self, cx: &mut Context<'_>, limit: usize) -> Poll<io::Result<()>> {
+        while self.sending.0.len() > limit {
+            if let Some(sent) = futures::ready!(self.sending.0.poll_next_unpin(cx)) {
+                sent?;
+            }
+        }
+        Poll::Ready(Ok(()))
+    }
+}
+
+impl Incoming {
+    fn new(connection: &quinn::Connection, framing: QuicFraming) -> Self {
+        let connection = connection.clone();
+        let incoming = match framing {
+            QuicFraming::Datagram => until_closed(connection, |connection| async move {
+                connection.read_datagram().await
+            })
+            .map(|datagram| Quic::decode(&datagram?))
+            .boxed(),
+            QuicFraming::Stream => until_closed(connection, |connection| async move {
+                connection.accept_uni().await
+            })
+            .map(|stream| async move {
+                let size = face::Mtu::MAX_NDN_PACKET_SIZE.to_usize();
+                let message = stream?
+                    .read_to_end(size)
+                    .await
+                    .map_err(|error| io::Error::new(io::ErrorKind::InvalidData, error))?;
+                Quic::decode(&message)
+            })
+            .buffer_unordered(Quic::MAX_CONCURRENT_RECEIVES)
+            .boxed(),
+        };
+        Self(incoming)
+    }
+}
+
+impl fmt::Debug for Incoming {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Incoming").finish_non_exhaustive()
+    }
+}
+
+impl fmt::Debug for Sending {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("Sending").field(&self.0.len()).finish()
+    }
+}
+
+impl Transport for Quic {
+    fn local_uri(&self) -> io::Result<face::LocalUri> {
+        socket_uri(face::Quic::PREFIX, self.endpoint.local_addr()).map(Into::into)
+    }
+
+    fn remote_uri(&self) -> io::Result<face::Uri> {
+        socket_uri(face::Quic::PREFIX, Ok(self.connection.remote_address())).map(Into::into)
+    }
+
+    /// Largest datagram the connection is able to carry, streams are unlimited
+    fn mtu(&self) -> Option<face::Mtu> {
+        match self.framing {
+            QuicFraming::Datagram => self.connection.max_datagram_size().map(|size| {
+                let size = size.min(face::Mtu::MAX_NDN_PACKET_SIZE.to_usize());
+                face::Mtu::from(size as u64)
+            }),
+            QuicFraming::Stream => None,
+        }
+    }
+
+    fn face_scope(&self) -> face::FaceScope {
+        scope_of(
+            self.endpoint.local_addr(),
+            Ok(self.connection.remote_address()),
+        )
+    }
+
+    fn reconnect(&mut self) -> BoxFuture<'_, io::Result<()>> {
+        self.redial().boxed()
+    }
+}
+
+impl Stream for Quic {
+    type Item = io::Result<tlv::Generic>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        self.incoming.0.poll_next_unpin(cx)
+    }
+}
+
+impl Sink<Bytes> for Quic {
+    type Error = io::Error;
+
+    fn poll_ready(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        self.get_mut().poll_sent(cx, Self::MAX_CONCURRENT_SENDS - 1)
+    }
+
+    fn start_send(self: Pin<&mut Self>, bytes: Bytes) -> io::Result<()> {
+        match self.framing {
+            QuicFraming::Datagram => self
+                .connection
+                .send_datagram(bytes)
+                .map_err(|error| io::Error::new(io::ErrorKind::InvalidInput, error)),
+            QuicFraming::Stream => {
+                let connection = self.connection.clone();
+                let send = async move {
+                    let mut stream = connection.open_uni().await?;
+                    stream.write_all(&bytes).await?;
+                    stream.finish().map_err(io::Error::other)
+                };
+                self.get_mut().sending.0.push(send.boxed());
+                Ok(())
+            }
+        }
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        self.get_mut().poll_sent(cx, 0)
+    }
+
+    fn poll_close(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        let this = self.get_mut();
+        futures::ready!(this.poll_sent(cx, 0))?;
+        this.connection.close(0_u32.into(), b"");
+        Poll::Ready(Ok(()))
+    }
+}
+
+async fn dial(
+    endpoint: &quinn::Endpoint,
+    remote: SocketAddr,
+    server_name: &str,
+    config: &QuicConfig,
+) -> io::Result<quinn::Connection> {
+    // Fail early on a name the certificate could never be valid for
+    ServerName::try_from(server_name)
+        .map_err(|error| io::Error::new(io::ErrorKind::InvalidInput, error))?;
+    let connection = endpoint
+        .connect_with(config.client_config()?, remote, server_name)
+        .map_err(|error| io::Error::new(io::ErrorKind::InvalidInput, error))?
+        .await?;
+    tracing::debug!(%remote, framing = ?config.framing, "Connected");
+    Ok(connection)
+}
+
+/// Whatever `next` receives until the connection is closed, a failure ends it as well
+fn until_closed<T, F, Fut>(
+    connection: quinn::Connection,
+    next: F,
+) -> impl Stream<Item = io::Result<T>>
+where
+    F: Fn(quinn::Connection) -> Fut,
+    Fut: Future<Output = Result<T, quinn::ConnectionError>>,
+{
+    stream::unfold(Some(connection), move |connection| {
+        let received = connection.clone().map(&next);
+        async move {
+            let connection = connection?;
+            match received?.await {
+                Ok(item) => Some((Ok(item), Some(connection))),
+                Err(
+                    quinn::ConnectionError::ApplicationClosed(_)
+                    | quinn::ConnectionError::LocallyClosed,
+                ) => None,
+                Err(error) => Some((Err(error.into()), None)),
+            }
+        }
+    })
+}
+
+fn provider() -> Arc<rustls::crypto::CryptoProvider> {
+    Arc::new(rustls::crypto::ring::default_provider())
+}
+
+fn tls_error(error: impl Into<Box<dyn std::error::Error + Send + Sync>>) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidInput, error)
+}
+
+fn pem_error(path: &Path, error: rustls::pki_types::pem::Error) -> io::Error {
+    io::Error::new(
+        io::ErrorKind::InvalidData,
+        format!("{}: {error}", path.display()),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use super::*;
+
+    /// Self-signed certificate valid for the loopback, trusted by itself
+    fn config(name: &str) -> QuicConfig {
+        let dir = std::env::temp_dir().join(format!("ndn-quic-{}-{name}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let names = vec!["localhost".to_string(), "127.0.0.1".to_string()];
+        let certified = rcgen::generate_simple_self_signed(names).unwrap();
+        let (certificate, private_key) = (dir.join("cert.pem"), dir.join("key.pem"));
+        fs::write(&certificate, certified.cert.pem()).unwrap();
+        fs::write(&private_key, certified.key_pair.serialize_pem()).unwrap();
+        QuicConfig::new(&certificate, private_key, &certificate)
+    }
+
+    /// Client connected to a channel along with the transport accepted on the other end
+    async fn connect(
+        client: &QuicConfig,
+        server: &QuicConfig,
+    ) -> (io::Result<Quic>, io::Result<Box<dyn Transport>>) {
+        let local = SocketAddr::from(([127, 0, 0, 1], 0));
+        let channel = Channel::quic(local, server).unwrap();
+        let face::Addr::Quic(remote) = channel.local_uri().unwrap().to_addr().await.unwrap() else {
+            panic!("Not a QUIC channel");
+        };
+        tokio::join!(Quic::new(local, remote.addr, "localhost", client), async {
+            channel.accept().await?.await
+        })
+    }
+
+    #[tokio::test]
+    async fn exchange() {
+        let config = config("exchange");
+        for framing in QuicFraming::ALL {
+            let client = config.clone().with_framing(framing);
+            let (client, server) = connect(&client, &config).await;
+            let (mut client, mut server) = (client.unwrap(), server.unwrap());
+            assert_eq!(client.framing(), framing);
+            // The channel takes the framing the client asks for
+            assert_eq!(server.mtu().is_some(), framing == QuicFraming::Datagram);
+            assert_eq!(server.face_scope(), face::FaceScope::Local);
+
+            client.send_item(tlv::Interest::new("/a")).await.unwrap();
+            let item = server.recv_item().await.unwrap().unwrap();
+            assert_eq!(item.r#type, tlv::Type::Interest);
+            server.send_item(tlv::Data::new("/a")).await.unwrap();
+            let item = client.recv_item().await.unwrap().unwrap();
+            assert_eq!(item.r#type, tlv::Type::Data);
+
+            client.close().await.unwrap();
+            assert!(server.recv_item().await.unwrap().is_none());
+        }
+    }
+
+    #[tokio::test]
+    async fn untrusted_peers() {
+        let (trusted, untrusted) = (config("trusted"), config("untrusted"));
+        // Neither end accepts a certificate it does not trust
+        let (client, server) = connect(&untrusted, &trusted).await;
+        assert!(client.is_err() || server.is_err());
+        let (client, _) = connect(&trusted, &untrusted).await;
+        assert!(client.is_err());
+    }
+}
@@ -0,0 +1,248 @@
+use std::collections::HashMap;
+use std::future::Future;
+use std::sync::Arc;
+
+use super::*;
+
+/// Opens transports for the face URIs of a single scheme
+pub trait Connector: Send + Sync {
+    fn connect(
+        &self,
+        remote: face::Uri,
+        local: Option<face::LocalUri>,
+    ) -> BoxFuture<'static, io::Result<Box<dyn Transport>>>;
+}
+
+impl<F, Fut> Connector for F
+where
+    F: Fn(face::Uri, Option<face::LocalUri>) -> Fut + Send + Sync,
+    Fut: Future<Output = io::Result<Box<dyn Transport>>> + Send + 'static,
+{
+    fn connect(
+        &self,
+        remote: face::Uri,
+        local: Option<face::LocalUri>,
+    ) -> BoxFuture<'static, io::Result<Box<dyn Transport>>> {
+        self(remote, local).boxed()
+    }
+}
+
+/// Connectors by URI scheme.
+///
+/// The default registry knows the transports of this crate, other crates add their own
+/// schemes with [`Registry::with_scheme`].
+#[derive(Clone)]
+pub struct Registry {
+    connectors: HashMap<String, Arc<dyn Connector>>,
+}
+
+impl Registry {
+    /// Registry without any scheme
+    pub fn empty() -> Self {
+        let connectors = HashMap::new();
+        Self { connectors }
+    }
+
+    /// The same registry with `scheme` handled by `connector`, replacing the previous one
+    pub fn with_scheme(
+        mut self,
+        scheme: impl ToString,
+        connector: impl Connector + 'static,
+    ) -> Self {
+        self.connectors
+            .insert(scheme.to_string(), Arc::new(connector));
+        self
+    }
+
+    /// The same registry with the `tcp` schemes connecting with `options`
+    pub fn with_tcp_options(self, options: TcpOptions) -> Self {
+        ["tcp", "tcp4", "tcp6"]
+            .into_iter()
+            .fold(self, |registry, scheme| {
+                registry.with_scheme(scheme, move |remote: face::Uri, local| async move {
+                    connect_tcp(remote, local, options).await
+                })
+            })
+    }
+
+    /// The same registry with the `quic` scheme, connecting with the certificates of `config`
+    pub fn with_quic(self, config: QuicConfig) -> Self {
+        self.with_scheme(face::Quic::PREFIX, move |remote: face::Uri, local| {
+            let config = config.clone();
+            async move { connect_quic(remote, local, config).await }
+        })
+    }
+
+    pub fn schemes(&self) -> impl Iterator<Item = &str> {
+        self.connectors.keys().map(String::as_str)
+    }
+
+    /// Transport to `remote`, bound to `local` if given
+    #[tracing::instrument(skip(self))]
+    pub async fn connect(
+        &self,
+        remote: face::Uri,
+        local: Option<face::LocalUri>,
+    ) -> io::Result<Box<dyn Transport>> {
+        let (scheme, _) = remote
+            .split_once(face::URI_DELIMITER)
+            .ok_or_else(|| io::Error::other("missing '://' delimiter"))?;
+        let connector = self.connectors.get(scheme).ok_or_else(|| {
+            io::Error::new(
+                io::ErrorKind::Unsupported,
+                format!("unknown Uri scheme: {scheme}"),
+            )
+        })?;
+        connector.connect(remote, local).await
+    }
+}
+
+impl Default for Registry {
+    fn default() -> Self {
+        let schemes = [
+            face::Internal::PREFIX,
+            "udp",
+            "udp4",
+            "udp6",
+            face::Unix::PREFIX,
+            face::Ws::PREFIX,
+        ];
+        let registry = schemes.into_iter().fold(Self::empty(), |registry, scheme| {
+            registry.with_scheme(scheme, connect)
+        });
+        registry.with_tcp_options(TcpOptions::default())
+    }
+}
+
+impl fmt::Debug for Registry {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut schemes = self.schemes().collect::<Vec<_>>();
+        schemes.sort_unstable();
+        f.debug_struct("Registry")
+            .field("schemes", &schemes)
+            .finish()
+    }
+}
+
+async fn connect_tcp(
+    remote: face::Uri,
+    local: Option<face::LocalUri>,
+    options: TcpOptions,
+) -> io::Result<Box<dyn Transport>> {
+    let face::Addr::Tcp(remote) = remote.to_addr().await? else {
+        return Err(io::Error::other("Invalid TCP remote"));
+    };
+    let local = match local {
+        Some(local) => match local.to_addr().await? {
+            face::Addr::Tcp(local) => Some(local.addr),
+            _ => return Err(io::Error::other("Invalid local/remote combination")),
+        },
+        None => None,
+    };
+    let tcp = Tcp::connect(local, remote.addr, options).await?;
+    Ok(Box::new(tcp))
+}
+
+async fn connect_quic(
+    remote: face::Uri,
+    local: Option<face::LocalUri>,
+    config: QuicConfig,
+) -> io::Result<Box<dyn Transport>> {
+    let face::Addr::Quic(remote) = remote.to_addr().await? else {
+        return Err(io::Error::other("Invalid QUIC remote"));
+    };
+    let local = match local {
+        Some(local) => match local.to_addr().await? {
+            face::Addr::Quic(local) => local.addr,
+            _ => return Err(io::Error::other("Invalid local/remote combination")),
+        },
+        None if remote.addr.is_ipv4() => SocketAddr::from((std::net::Ipv4Addr::UNSPECIFIED, 0)),
+        None => SocketAddr::from((std::net::Ipv6Addr::UNSPECIFIED, 0)),
+    };
+    let quic = Quic::new(local, remote.addr, &remote.server_name, &config).await?;
+    Ok(Box::new(quic))
+}
+
+/// Connector of the other transports of this crate
+async fn connect(
+    remote: face::Uri,
+    local: Option<face::LocalUri>,
+) -> io::Result<Box<dyn Transport>> {
+    let remote = remote.to_addr().await?;
+    let local = match local {
+        Some(local) => local.to_addr().await?,
+        None => remote.any(),
+    };
+    match (local, remote) {
+        (face::Addr::Internal(_), face::Addr::Internal(_)) => Ok(Box::new(Internal::loopback())),
+        (face::Addr::Udp(local), face::Addr::Udp(remote)) if remote.addr.ip().is_multicast() => {
+            Ok(Box::new(multicast(local.addr, remote.addr)?))
+        }
+        (face::Addr::Udp(local), face::Addr::Udp(remote)) => {
+            Ok(Box::new(Udp::new(local.addr, remote.addr).await?))
+        }
+        (face::Addr::Unix(_local), face::Addr::Unix(remote)) => {
+            Ok(Box::new(Unix::new(remote.path).await?))
+        }
+        (face::Addr::Ws(_local), face::Addr::Ws(remote)) => {
+            Ok(Box::new(Ws::new(remote.addr, &remote.path).await?))
+        }
+        _ => Err(io::Error::other("Invalid local/remote combination")),
+    }
+}
+
+/// Multicast face joining `group` on the interface of the `local` address
+fn multicast(local: SocketAddr, group: SocketAddr) -> io::Result<UdpMulticast> {
+    let SocketAddr::V4(group) = group else {
+        return Err(io::Error::new(
+            io::ErrorKind::Unsupported,
+            "IPv6 multicast faces are not supported",
+        ));
+    };
+    let SocketAddr::V4(local) = local else {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            "IPv4 multicast group needs an IPv4 local address",
+        ));
+    };
+    UdpMulticast::new(*local.ip(), group)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn custom_scheme() {
+        let registry = Registry::default();
+        let error = registry.connect("mem://a".into(), None).await.unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::Unsupported);
+
+        let registry = registry.with_scheme("mem", |_, _| async {
+            let (one, _) = Internal::pair();
+            let transport: Box<dyn Transport> = Box::new(one);
+            Ok(transport)
+        });
+        assert!(registry.schemes().any(|scheme| scheme == "mem"));
+        let transport = registry.connect("mem://a".into(), None).await.unwrap();
+        assert_eq!(transport.face_scope(), face::FaceScope::Local);
+    }
+
+    #[tokio::test]
+    async fn builtin_schemes() {
+        let registry = Registry::default();
+        let mut transport = registry.connect("internal://".into(), None).await.unwrap();
+        transport.send_item(tlv::Interest::new("/a")).await.unwrap();
+        let item = transport.recv_item().await.unwrap().unwrap();
+        assert_eq!(item.r#type, tlv::Type::Interest);
+    }
+
+    #[tokio::test]
+    async fn multicast_local_address() {
+        let registry = Registry::default();
+        let group = "udp4://224.0.23.170:56365";
+        let local = Some("udp6://[::1]:0".into());
+        let error = registry.connect(group.into(), local).await.unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::InvalidInput);
+    }
+}
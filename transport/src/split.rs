@@ -0,0 +1,104 @@
+use futures::stream::SplitSink;
+use futures::stream::SplitStream;
+use futures::StreamExt;
+
+use super::*;
+
+/// Receiving half of a [`Transport`], see [`TransportExt::split`]
+#[derive(Debug)]
+pub struct ReadHalf<T> {
+    stream: SplitStream<T>,
+}
+
+/// Sending half of a [`Transport`], see [`TransportExt::split`]
+#[derive(Debug)]
+pub struct WriteHalf<T> {
+    sink: SplitSink<T, Bytes>,
+}
+
+pub(crate) fn split<T: Transport>(transport: T) -> (ReadHalf<T>, WriteHalf<T>) {
+    let (sink, stream) = StreamExt::split(transport);
+    (ReadHalf { stream }, WriteHalf { sink })
+}
+
+impl<T: Transport> ReadHalf<T> {
+    /// Next packet, `None` once the other end has gone away
+    pub fn recv_item(&mut self) -> futures::stream::TryNext<'_, Self> {
+        TryStreamExt::try_next(self)
+    }
+
+    /// Put the transport back together, fails unless both halves come from the same split
+    pub fn reunite(self, write: WriteHalf<T>) -> io::Result<T> {
+        self.stream
+            .reunite(write.sink)
+            .map_err(|_| io::Error::other("Halves of different transports"))
+    }
+}
+
+impl<T: Transport> WriteHalf<T> {
+    /// Encode and send a single packet
+    pub fn send_item(&mut self, item: impl tlv::Tlv) -> futures::sink::Send<'_, Self, Bytes> {
+        let bytes = tlv::TlvCodec::bytes(&item);
+        SinkExt::send(self, bytes)
+    }
+}
+
+impl<T: Transport> Stream for ReadHalf<T> {
+    type Item = io::Result<tlv::Generic>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        Pin::new(&mut self.get_mut().stream).poll_next(cx)
+    }
+}
+
+impl<T: Transport> Sink<Bytes> for WriteHalf<T> {
+    type Error = io::Error;
+
+    fn poll_ready(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Pin::new(&mut self.get_mut().sink).poll_ready(cx)
+    }
+
+    fn start_send(self: Pin<&mut Self>, bytes: Bytes) -> io::Result<()> {
+        Pin::new(&mut self.get_mut().sink).start_send(bytes)
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Pin::new(&mut self.get_mut().sink).poll_flush(cx)
+    }
+
+    fn poll_close(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Pin::new(&mut self.get_mut().sink).poll_close(cx)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::*;
+
+    #[tokio::test]
+    async fn send_while_receiving() {
+        let (one, mut other) = Internal::pair();
+        let (mut read, mut write) = TransportExt::split(one);
+
+        // Nothing arrives, yet the write half is not held up by the pending read
+        let reading = tokio::spawn(async move {
+            let item = read.recv_item().await;
+            (read, item)
+        });
+        tokio::time::timeout(
+            Duration::from_secs(1),
+            write.send_item(tlv::Interest::new("/a")),
+        )
+        .await
+        .unwrap()
+        .unwrap();
+        assert!(other.recv_item().await.unwrap().is_some());
+
+        other.send_item(tlv::Interest::new("/b")).await.unwrap();
+        let (read, item) = reading.await.unwrap();
+        assert_eq!(item.unwrap().unwrap().r#type, tlv::Type::Interest);
+        assert!(read.reunite(write).is_ok());
+    }
+}
@@ -1,89 +1,266 @@
+use std::time::Duration;
+
+use tokio::time;
+
 use super::*;
 
+/// Socket options of the outgoing TCP connections
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct TcpOptions {
+    /// Connection attempts not complete by then fail with `TimedOut`
+    pub connect_timeout: Duration,
+    /// Idle time before the first keepalive probe, `None` disables keepalive
+    pub keepalive: Option<Duration>,
+}
+
+impl TcpOptions {
+    pub const DEFAULT_CONNECT_TIMEOUT: Duration = Duration::from_secs(10);
+
+    pub fn with_connect_timeout(self, connect_timeout: Duration) -> Self {
+        Self {
+            connect_timeout,
+            ..self
+        }
+    }
+
+    pub fn with_keepalive(self, keepalive: impl Into<Option<Duration>>) -> Self {
+        let keepalive = keepalive.into();
+        Self { keepalive, ..self }
+    }
+}
+
+impl Default for TcpOptions {
+    fn default() -> Self {
+        Self {
+            connect_timeout: Self::DEFAULT_CONNECT_TIMEOUT,
+            keepalive: None,
+        }
+    }
+}
+
 #[derive(Debug)]
 pub struct Tcp {
-    framed: Framed<net::TcpStream, TlvCodec>,
+    /// `None` once the connection has been dropped to be re-established
+    framed: Option<Framed<net::TcpStream, TlvCodec>>,
+    /// local and remote address the connection has been made with, if made by us
+    dialed: Option<(Option<SocketAddr>, SocketAddr)>,
+    options: TcpOptions,
 }
 
 impl Tcp {
-    pub(super) async fn new(
-        _local: impl net::ToSocketAddrs,
-        remote: impl net::ToSocketAddrs,
+    pub async fn new(local: Option<SocketAddr>, remote: SocketAddr) -> io::Result<Self> {
+        Self::connect(local, remote, TcpOptions::default()).await
+    }
+
+    /// Connect to `remote`, from `local` if given. Binding to a specific address pins the
+    /// connection to the interface owning it on multi-homed hosts.
+    pub async fn connect(
+        local: Option<SocketAddr>,
+        remote: SocketAddr,
+        options: TcpOptions,
     ) -> io::Result<Self> {
-        tracing::info!("Ignoring local for now");
-        let socket = net::TcpStream::connect(remote).await?;
+        let socket = dial(local, remote, options).await?;
+        tracing::debug!(local = ?socket.local_addr(), %remote, "Connected");
+        let dialed = Some((local, remote));
+        Ok(Self {
+            dialed,
+            options,
+            ..Self::from_stream(socket)
+        })
+    }
+
+    /// Transport over an already connected stream, e.g. one accepted by a [`Channel`]
+    pub fn from_stream(socket: net::TcpStream) -> Self {
         let codec = TlvCodec::new();
-        let framed = Framed::new(socket, codec);
-        Ok(Self { framed })
+        let framed = Some(Framed::new(socket, codec));
+        Self {
+            framed,
+            dialed: None,
+            options: TcpOptions::default(),
+        }
     }
 
-    fn local_addr(&self) -> io::Result<SocketAddr> {
-        self.framed.get_ref().local_addr()
+    fn socket(&self) -> io::Result<&net::TcpStream> {
+        self.framed
+            .as_ref()
+            .map(Framed::get_ref)
+            .ok_or_else(|| io::Error::from(io::ErrorKind::NotConnected))
     }
 
-    pub(super) fn face_uri(&self) -> io::Result<String> {
-        let uri = format!(
-            "{}{}{}",
-            face::Tcp::PREFIX,
-            face::URI_DELIMITER,
-            self.local_addr()?,
-        );
-        Ok(uri)
+    fn framed(self: Pin<&mut Self>) -> io::Result<Pin<&mut Framed<net::TcpStream, TlvCodec>>> {
+        self.get_mut()
+            .framed
+            .as_mut()
+            .map(Pin::new)
+            .ok_or_else(|| io::Error::from(io::ErrorKind::NotConnected))
     }
 
-    pub(super) async fn send_item(&mut self, item: impl tlv::Tlv) -> io::Result<()> {
-        self.framed.send(item).await
+    /// Connect again to the same remote from the same local address, dropping whatever is
+    /// left of the previous connection. Accepted connections cannot be re-established.
+    pub async fn redial(&mut self) -> io::Result<()> {
+        let Some((local, remote)) = self.dialed else {
+            return Err(io::Error::new(
+                io::ErrorKind::Unsupported,
+                "Accepted connections cannot be re-established",
+            ));
+        };
+        // The old connection has to go first, it may hold the very same local port
+        self.framed = None;
+        let socket = dial(local, remote, self.options).await?;
+        tracing::debug!(local = ?socket.local_addr(), %remote, "Reconnected");
+        self.framed = Some(Framed::new(socket, TlvCodec::new()));
+        Ok(())
     }
+}
 
-    pub(super) async fn recv_item(&mut self) -> io::Result<Option<tlv::Generic>> {
-        self.framed.try_next().await
+async fn dial(
+    local: Option<SocketAddr>,
+    remote: SocketAddr,
+    options: TcpOptions,
+) -> io::Result<net::TcpStream> {
+    let socket = if remote.is_ipv4() {
+        net::TcpSocket::new_v4()?
+    } else {
+        net::TcpSocket::new_v6()?
+    };
+    if let Some(local) = local {
+        // The port of the previous connection may still linger on reconnect
+        socket.set_reuseaddr(true)?;
+        socket.bind(local)?;
     }
+    if let Some(idle) = options.keepalive {
+        socket.set_keepalive(true)?;
+        keepalive_idle(&socket, idle)?;
+    }
+    time::timeout(options.connect_timeout, socket.connect(remote))
+        .await
+        .map_err(|_| {
+            io::Error::new(
+                io::ErrorKind::TimedOut,
+                format!("Connecting to {remote} timed out"),
+            )
+        })?
+}
 
-    #[tracing::instrument(level = "trace", skip_all, err(level = "error"))]
-    pub(super) async fn send(&self, bytes: Bytes) -> io::Result<()> {
-        let count = loop {
-            self.framed.get_ref().writable().await?;
+/// Idle time before the first probe and between the probes (TCP_KEEPIDLE and TCP_KEEPINTVL)
+#[cfg(target_os = "linux")]
+fn keepalive_idle(socket: &net::TcpSocket, idle: Duration) -> io::Result<()> {
+    use std::os::fd::AsRawFd;
 
-            // Try to write data, this may still fail with `WouldBlock`
-            // if the readiness event is a false positive.
-            match self.framed.get_ref().try_write(&bytes) {
-                Ok(count) => break count,
-                Err(ref e) if e.kind() == io::ErrorKind::WouldBlock => continue,
-                Err(e) => {
-                    return Err(e);
-                }
-            }
+    let secs = libc::c_int::try_from(idle.as_secs().max(1)).unwrap_or(libc::c_int::MAX);
+    for name in [libc::TCP_KEEPIDLE, libc::TCP_KEEPINTVL] {
+        // SAFETY: the option value is a single c_int of the given size
+        let rc = unsafe {
+            libc::setsockopt(
+                socket.as_raw_fd(),
+                libc::IPPROTO_TCP,
+                name,
+                std::ptr::addr_of!(secs).cast(),
+                std::mem::size_of::<libc::c_int>() as libc::socklen_t,
+            )
         };
-
-        if count == bytes.len() {
-            Ok(())
-        } else {
-            Err(io::Error::other("Failed to send TCP data"))
+        if rc != 0 {
+            return Err(io::Error::last_os_error());
         }
     }
+    Ok(())
+}
 
-    #[tracing::instrument(level = "trace", skip_all, err(level = "error"))]
-    pub(super) async fn recv(&self, mut bytes: BytesMut) -> io::Result<Bytes> {
-        loop {
-            self.framed.get_ref().readable().await?;
-
-            let mut buf = [0; 8800];
-            tracing::trace!(buffer = buf.len(), "Got buffer");
-
-            match self.framed.get_ref().try_read(&mut buf) {
-                Ok(0) => break,
-                Ok(count) => {
-                    tracing::trace!(count, "Got bytes");
-                    bytes.extend(&buf[..count]);
-                    if tlv::Generic::from_slice(&buf).is_some() {
-                        break;
-                    }
-                }
-                Err(ref e) if e.kind() == io::ErrorKind::WouldBlock => continue,
-                Err(e) => return Err(e),
-            }
+#[cfg(not(target_os = "linux"))]
+fn keepalive_idle(socket: &net::TcpSocket, idle: Duration) -> io::Result<()> {
+    let _ = (socket, idle);
+    Ok(())
+}
+
+impl Transport for Tcp {
+    fn local_uri(&self) -> io::Result<face::LocalUri> {
+        socket_uri(face::Tcp::PREFIX, self.socket()?.local_addr()).map(Into::into)
+    }
+
+    fn remote_uri(&self) -> io::Result<face::Uri> {
+        socket_uri(face::Tcp::PREFIX, self.socket()?.peer_addr()).map(Into::into)
+    }
+
+    fn face_scope(&self) -> face::FaceScope {
+        scope_of(
+            self.socket().and_then(net::TcpStream::local_addr),
+            self.socket().and_then(net::TcpStream::peer_addr),
+        )
+    }
+
+    fn send_queue_length(&self) -> Option<usize> {
+        let framed = self.framed.as_ref()?;
+        let socket = queue::outq(framed.get_ref())?;
+        Some(framed.write_buffer().len() + socket)
+    }
+
+    fn reconnect(&mut self) -> BoxFuture<'_, io::Result<()>> {
+        self.redial().boxed()
+    }
+}
+
+impl Stream for Tcp {
+    type Item = io::Result<tlv::Generic>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        match self.framed() {
+            Ok(framed) => framed.poll_next(cx),
+            Err(_) => Poll::Ready(None),
         }
+    }
+}
+
+impl Sink<Bytes> for Tcp {
+    type Error = io::Error;
+
+    fn poll_ready(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Sink::<Bytes>::poll_ready(self.framed()?, cx)
+    }
+
+    fn start_send(self: Pin<&mut Self>, bytes: Bytes) -> io::Result<()> {
+        self.framed()?.start_send(bytes)
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Sink::<Bytes>::poll_flush(self.framed()?, cx)
+    }
+
+    fn poll_close(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Sink::<Bytes>::poll_close(self.framed()?, cx)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn bind_and_reconnect() {
+        let listener = net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let remote = listener.local_addr().unwrap();
+        let local = std::net::TcpListener::bind("127.0.0.1:0")
+            .unwrap()
+            .local_addr()
+            .unwrap();
+        let options = TcpOptions::default().with_keepalive(Duration::from_secs(30));
+
+        // The connection comes from the requested local address
+        let mut tcp = Tcp::connect(Some(local), remote, options).await.unwrap();
+        let (peer, from) = listener.accept().await.unwrap();
+        assert_eq!(from, local);
+
+        drop(peer);
+        assert!(tcp.recv_item().await.unwrap().is_none());
+        tcp.reconnect().await.unwrap();
+        let (peer, from) = listener.accept().await.unwrap();
+        assert_eq!(from, local);
+
+        let mut peer = Tcp::from_stream(peer);
+        tcp.send_item(tlv::Interest::new("/a")).await.unwrap();
+        assert!(peer.recv_item().await.unwrap().is_some());
 
-        Ok(bytes.freeze())
+        // Accepted connections are not ours to re-establish
+        let error = peer.reconnect().await.unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::Unsupported);
     }
 }
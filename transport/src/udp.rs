@@ -1,57 +1,302 @@
+use tokio::io::ReadBuf;
+use tokio_util::codec;
+
 use super::*;
 
+/// One packet per datagram
 #[derive(Debug)]
 pub struct Udp {
     socket: net::UdpSocket,
+    mtu: face::Mtu,
+    buffer: Box<[u8]>,
+    /// datagram accepted by the sink, not sent yet
+    pending: Option<Bytes>,
 }
 
 impl Udp {
-    pub(super) async fn new(
+    /// Largest UDP payload
+    pub(crate) const MAX_DATAGRAM_SIZE: usize = 65535;
+    /// IPv4 and UDP headers
+    const IPV4_OVERHEAD: usize = 20 + 8;
+    /// IPv6 and UDP headers
+    const IPV6_OVERHEAD: usize = 40 + 8;
+
+    pub async fn new(
         local: impl net::ToSocketAddrs,
         remote: impl net::ToSocketAddrs,
     ) -> io::Result<Self> {
         let socket = net::UdpSocket::bind(local).await?;
         socket.connect(remote).await?;
-        Ok(Self { socket })
+        let mtu = Self::compute_mtu(&socket);
+        let buffer = vec![0; Self::MAX_DATAGRAM_SIZE].into_boxed_slice();
+        Ok(Self {
+            socket,
+            mtu,
+            buffer,
+            pending: None,
+        })
     }
 
-    fn local_addr(&self) -> io::Result<SocketAddr> {
-        self.socket.local_addr()
+    /// Path MTU less the IP and UDP headers, no more than a single packet may take
+    pub(crate) fn compute_mtu(socket: &net::UdpSocket) -> face::Mtu {
+        let max = face::Mtu::MAX_NDN_PACKET_SIZE.to_usize();
+        let is_ipv6 = socket.peer_addr().is_ok_and(|addr| addr.is_ipv6());
+        let overhead = if is_ipv6 {
+            Self::IPV6_OVERHEAD
+        } else {
+            Self::IPV4_OVERHEAD
+        };
+        let mtu = path_mtu(socket, is_ipv6)
+            .and_then(|mtu| mtu.checked_sub(overhead))
+            .map_or(max, |mtu| mtu.min(max));
+        face::Mtu::from(mtu as u64)
     }
 
-    pub(super) fn face_uri(&self) -> io::Result<String> {
-        let uri = format!(
-            "{}{}{}",
-            face::Udp::PREFIX,
-            face::URI_DELIMITER,
-            self.local_addr()?,
-        );
-        Ok(uri)
+    /// Packet carried by the datagram. A datagram which does not hold exactly one TLV fails
+    /// with `InvalidData`, the socket stays usable for the next one.
+    pub(crate) fn decode(datagram: &[u8]) -> io::Result<tlv::Generic> {
+        let count = datagram.len();
+        let mut datagram = BytesMut::from(datagram);
+        let item = codec::Decoder::decode(&mut TlvCodec::new(), &mut datagram)?
+            .ok_or_else(|| invalid_datagram(format!("Truncated {count} octet datagram")))?;
+        if !datagram.is_empty() {
+            return Err(invalid_datagram(format!(
+                "{} trailing octets after {}",
+                datagram.len(),
+                item.r#type
+            )));
+        }
+        Ok(item)
     }
 
-    pub(super) async fn send_item(&mut self, item: impl tlv::Tlv) -> io::Result<()> {
-        let _ = item;
-        todo!()
+    fn poll_send_pending(&mut self, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        let Some(bytes) = &self.pending else {
+            return Poll::Ready(Ok(()));
+        };
+        let sent = futures::ready!(self.socket.poll_send(cx, bytes));
+        let len = bytes.len();
+        self.pending = None;
+        match sent {
+            Ok(count) if count == len => Poll::Ready(Ok(())),
+            Ok(_) => Poll::Ready(Err(io::Error::other("Failed to send UDP packet"))),
+            Err(error) if is_refused(&error) => {
+                tracing::trace!(%error, "Dropping datagram");
+                Poll::Ready(Ok(()))
+            }
+            Err(error) => Poll::Ready(Err(error)),
+        }
     }
+}
 
-    pub(super) async fn recv_item(&mut self) -> io::Result<Option<tlv::Generic>> {
-        Ok(None)
+impl Transport for Udp {
+    fn local_uri(&self) -> io::Result<face::LocalUri> {
+        socket_uri(face::Udp::PREFIX, self.socket.local_addr()).map(Into::into)
     }
 
-    #[tracing::instrument(level = "trace", skip_all, err(level = "error"))]
-    pub(super) async fn send(&self, bytes: Bytes) -> io::Result<()> {
-        if self.socket.send(&bytes).await? == bytes.len() {
-            Ok(())
-        } else {
-            Err(io::Error::other("Failed to send UDP packet"))
+    fn remote_uri(&self) -> io::Result<face::Uri> {
+        socket_uri(face::Udp::PREFIX, self.socket.peer_addr()).map(Into::into)
+    }
+
+    /// Largest packet sent in a single datagram
+    fn mtu(&self) -> Option<face::Mtu> {
+        Some(self.mtu)
+    }
+
+    fn face_scope(&self) -> face::FaceScope {
+        scope_of(self.socket.local_addr(), self.socket.peer_addr())
+    }
+
+    fn send_queue_length(&self) -> Option<usize> {
+        queue::outq(&self.socket)
+    }
+}
+
+impl Stream for Udp {
+    type Item = io::Result<tlv::Generic>;
+
+    /// Receive the packet carried by the next datagram
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let this = self.get_mut();
+        loop {
+            let mut buf = ReadBuf::new(&mut this.buffer);
+            match futures::ready!(this.socket.poll_recv(cx, &mut buf)) {
+                Ok(()) => {
+                    tracing::trace!(count = buf.filled().len(), "Got datagram");
+                    break Poll::Ready(Some(Self::decode(buf.filled())));
+                }
+                Err(error) if is_refused(&error) => {
+                    tracing::trace!(%error, "Skipping ICMP error of an earlier datagram");
+                }
+                Err(error) => break Poll::Ready(Some(Err(error))),
+            }
         }
     }
+}
+
+impl Sink<Bytes> for Udp {
+    type Error = io::Error;
+
+    fn poll_ready(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        self.get_mut().poll_send_pending(cx)
+    }
 
-    #[tracing::instrument(level = "trace", skip_all, err(level = "error"))]
-    pub(super) async fn recv(&self, mut bytes: BytesMut) -> io::Result<Bytes> {
-        self.socket.recv(&mut bytes).await.map(|count| {
-            tracing::trace!(count, "Got bytes");
-            bytes.freeze()
-        })
+    /// Queue `bytes` as a single datagram, unless it does not fit into the MTU
+    fn start_send(self: Pin<&mut Self>, bytes: Bytes) -> io::Result<()> {
+        let mtu = self.mtu.to_usize();
+        if bytes.len() > mtu {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("{} octet packet exceeds MTU {mtu}", bytes.len()),
+            ));
+        }
+        self.get_mut().pending = Some(bytes);
+        Ok(())
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        self.get_mut().poll_send_pending(cx)
+    }
+
+    fn poll_close(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        self.get_mut().poll_send_pending(cx)
+    }
+}
+
+/// MTU of the path to the connected peer (IP_MTU and IPV6_MTU)
+#[cfg(target_os = "linux")]
+fn path_mtu(socket: &net::UdpSocket, is_ipv6: bool) -> Option<usize> {
+    use std::os::fd::AsRawFd;
+
+    let (level, name) = if is_ipv6 {
+        (libc::IPPROTO_IPV6, libc::IPV6_MTU)
+    } else {
+        (libc::IPPROTO_IP, libc::IP_MTU)
+    };
+    let mut mtu: libc::c_int = 0;
+    let mut len = std::mem::size_of::<libc::c_int>() as libc::socklen_t;
+    // SAFETY: the option value is a single c_int, `len` holds the size of the supplied buffer
+    let rc = unsafe {
+        libc::getsockopt(
+            socket.as_raw_fd(),
+            level,
+            name,
+            std::ptr::addr_of_mut!(mtu).cast(),
+            &mut len,
+        )
+    };
+    if rc == 0 {
+        usize::try_from(mtu).ok()
+    } else {
+        None
+    }
+}
+
+#[cfg(not(target_os = "linux"))]
+fn path_mtu(socket: &net::UdpSocket, is_ipv6: bool) -> Option<usize> {
+    let _ = (socket, is_ipv6);
+    None
+}
+
+/// ICMP port unreachable for an earlier datagram, reported on the next socket operation.
+/// The peer may come back, so this is not fatal to the face.
+fn is_refused(error: &io::Error) -> bool {
+    error.kind() == io::ErrorKind::ConnectionRefused
+}
+
+fn invalid_datagram(reason: String) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, reason)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Our end of the link along with a bare socket at the other end
+    async fn link() -> (Udp, net::UdpSocket) {
+        let peer = net::UdpSocket::bind("127.0.0.1:0").await.unwrap();
+        let udp = Udp::new("127.0.0.1:0", peer.local_addr().unwrap())
+            .await
+            .unwrap();
+        peer.connect(udp.socket.local_addr().unwrap())
+            .await
+            .unwrap();
+        (udp, peer)
+    }
+
+    #[tokio::test]
+    async fn one_packet_per_datagram() {
+        let (mut udp, peer) = link().await;
+        udp.send_item(tlv::Interest::new("/a")).await.unwrap();
+        udp.send_item(tlv::Interest::new("/b")).await.unwrap();
+
+        let mut datagram = [0; 100];
+        for name in ["/a", "/b"] {
+            let count = peer.recv(&mut datagram).await.unwrap();
+            let mut src = BytesMut::from(&datagram[..count]);
+            let interest = <tlv::Interest as tlv::TlvCodec>::decode(&mut src).unwrap();
+            assert_eq!(interest.name.to_string(), name);
+            assert!(src.is_empty());
+        }
+
+        let item = tlv::TlvCodec::bytes(&tlv::Interest::new("/c"));
+        peer.send(&item).await.unwrap();
+        let received = udp.recv_item().await.unwrap().unwrap();
+        assert_eq!(received.r#type, tlv::Type::Interest);
+    }
+
+    #[tokio::test]
+    async fn malformed_datagrams() {
+        let (mut udp, peer) = link().await;
+        let item = tlv::TlvCodec::bytes(&tlv::Interest::new("/a"));
+
+        // Truncated
+        peer.send(&item[..item.len() - 1]).await.unwrap();
+        let error = udp.recv_item().await.unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::InvalidData);
+
+        // Trailing octets
+        let mut trailing = item.to_vec();
+        trailing.push(0);
+        peer.send(&trailing).await.unwrap();
+        let error = udp.recv_item().await.unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::InvalidData);
+
+        // Next datagram is fine
+        peer.send(&item).await.unwrap();
+        assert!(udp.recv_item().await.unwrap().is_some());
+    }
+
+    #[tokio::test]
+    async fn closed_port() {
+        let (mut udp, peer) = link().await;
+        let peer_addr = peer.local_addr().unwrap();
+        drop(peer);
+
+        // Port unreachable from the closed peer is reported on the next send or receive,
+        // it does not break the face
+        for _ in 0..2 {
+            udp.send_item(tlv::Interest::new("/a")).await.unwrap();
+            tokio::time::sleep(std::time::Duration::from_millis(50)).await;
+        }
+        let peer = net::UdpSocket::bind(peer_addr).await.unwrap();
+        peer.connect(udp.socket.local_addr().unwrap())
+            .await
+            .unwrap();
+        let item = tlv::TlvCodec::bytes(&tlv::Interest::new("/b"));
+        peer.send(&item).await.unwrap();
+        let received = udp.recv_item().await.unwrap().unwrap();
+        assert_eq!(received.r#type, tlv::Type::Interest);
+    }
+
+    #[tokio::test]
+    async fn mtu() {
+        let (mut udp, _peer) = link().await;
+        // Loopback MTU is larger than any packet
+        assert_eq!(udp.mtu(), Some(face::Mtu::MAX_NDN_PACKET_SIZE));
+
+        let mut data = tlv::Data::new("/big");
+        data.content = Some(tlv::Content::new(vec![0; udp.mtu.to_usize()]));
+        let error = udp.send_item(data).await.unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::InvalidInput);
     }
 }
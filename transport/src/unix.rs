@@ -7,93 +7,125 @@ use super::*;
 
 #[derive(Debug)]
 pub struct Unix {
-    socket: net::UnixStream,
+    framed: Framed<net::UnixStream, TlvCodec>,
 }
 
 impl Unix {
-    pub(super) async fn new(remote: impl AsRef<Path>) -> io::Result<Self> {
+    pub async fn new(remote: impl AsRef<Path>) -> io::Result<Self> {
         let socket = net::UnixStream::connect(remote).await?;
-        println!("Local: {:?}", socket.local_addr());
-        println!("Peer: {:?}", socket.peer_addr());
-        Ok(Self { socket })
+        tracing::debug!(local = ?socket.local_addr(), peer = ?socket.peer_addr(), "Connected");
+        Ok(Self::from_stream(socket))
     }
 
-    fn local_addr(&self) -> io::Result<unix::SocketAddr> {
-        self.socket.local_addr()
+    /// Transport over an already connected socket
+    pub fn from_stream(socket: net::UnixStream) -> Self {
+        let codec = TlvCodec::new();
+        let framed = Framed::new(socket, codec);
+        Self { framed }
     }
 
-    pub(super) fn face_uri(&self) -> io::Result<String> {
-        let uri = self.unix_face_uri()?.unwrap_or_else(|| self.fd_face_uri());
-        Ok(uri)
+    fn socket(&self) -> &net::UnixStream {
+        self.framed.get_ref()
     }
 
-    fn unix_face_uri(&self) -> io::Result<Option<String>> {
-        let uri = self.local_addr()?.as_pathname().map(|path| {
-            format!(
-                "{}{}{}",
-                face::Unix::PREFIX,
-                face::URI_DELIMITER,
-                path.display()
-            )
-        });
+    fn face_uri(&self, addr: io::Result<unix::SocketAddr>) -> io::Result<String> {
+        let uri = addr?.as_pathname().map_or_else(
+            || self.fd_face_uri(),
+            |path| {
+                format!(
+                    "{}{}{}",
+                    face::Unix::PREFIX,
+                    face::URI_DELIMITER,
+                    path.display()
+                )
+            },
+        );
         Ok(uri)
     }
+
     fn fd_face_uri(&self) -> String {
-        let fd = self.socket.as_raw_fd();
+        let fd = self.socket().as_raw_fd();
         format!("{}{}{}", "fd", face::URI_DELIMITER, fd)
     }
 
-    pub(super) async fn send_item(&mut self, item: impl tlv::Tlv) -> io::Result<()> {
-        let _ = item;
-        todo!()
+    fn framed(self: Pin<&mut Self>) -> Pin<&mut Framed<net::UnixStream, TlvCodec>> {
+        Pin::new(&mut self.get_mut().framed)
     }
+}
 
-    pub(super) async fn recv_item(&mut self) -> io::Result<Option<tlv::Generic>> {
-        Ok(None)
+impl Transport for Unix {
+    fn local_uri(&self) -> io::Result<face::LocalUri> {
+        self.face_uri(self.socket().local_addr()).map(Into::into)
     }
 
-    #[tracing::instrument(level = "trace", skip_all, err(level = "error"))]
-    pub(super) async fn send(&self, bytes: Bytes) -> io::Result<()> {
-        let count = loop {
-            self.socket.writable().await?;
+    fn remote_uri(&self) -> io::Result<face::Uri> {
+        self.face_uri(self.socket().peer_addr()).map(Into::into)
+    }
 
-            // Try to write data, this may still fail with `WouldBlock`
-            // if the readiness event is a false positive.
-            match self.socket.try_write(&bytes) {
-                Ok(count) => break count,
-                Err(ref e) if e.kind() == io::ErrorKind::WouldBlock => continue,
-                Err(e) => {
-                    return Err(e);
-                }
-            }
-        };
+    fn face_scope(&self) -> face::FaceScope {
+        face::FaceScope::Local
+    }
 
-        if count == bytes.len() {
-            Ok(())
-        } else {
-            Err(io::Error::other("Failed to send TCP data"))
-        }
+    fn send_queue_length(&self) -> Option<usize> {
+        let socket = queue::outq(self.socket())?;
+        Some(self.framed.write_buffer().len() + socket)
+    }
+}
+
+impl Stream for Unix {
+    type Item = io::Result<tlv::Generic>;
+
+    /// Ends once the other end has closed the connection
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        self.framed().poll_next(cx)
+    }
+}
+
+impl Sink<Bytes> for Unix {
+    type Error = io::Error;
+
+    fn poll_ready(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Sink::<Bytes>::poll_ready(self.framed(), cx)
+    }
+
+    fn start_send(self: Pin<&mut Self>, bytes: Bytes) -> io::Result<()> {
+        self.framed().start_send(bytes)
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Sink::<Bytes>::poll_flush(self.framed(), cx)
     }
 
-    #[tracing::instrument(level = "trace", skip_all, ret, err(level = "error"))]
-    pub(super) async fn recv(&self, mut bytes: BytesMut) -> io::Result<Bytes> {
-        loop {
-            tracing::trace!("Waiting for socker to become readable");
-            self.socket.readable().await?;
-            tracing::trace!("Socket is readable");
-
-            let mut buf = [0; 8800];
-            match self.socket.try_read(&mut buf) {
-                Ok(0) => break,
-                Ok(count) => {
-                    tracing::trace!(count, "Got bytes");
-                    bytes.extend(&buf[..count]);
-                }
-                Err(ref e) if e.kind() == io::ErrorKind::WouldBlock => continue,
-                Err(e) => return Err(e),
-            }
+    fn poll_close(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Sink::<Bytes>::poll_close(self.framed(), cx)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn framed_packets() {
+        let path = std::env::temp_dir().join(format!("ndn-unix-{}.sock", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+        let listener = net::UnixListener::bind(&path).unwrap();
+
+        let mut unix = Unix::new(&path).await.unwrap();
+        let (socket, _) = listener.accept().await.unwrap();
+        let mut peer = Unix::from_stream(socket);
+        std::fs::remove_file(&path).unwrap();
+
+        // Back to back packets come out one at a time
+        unix.send_item(tlv::Interest::new("/a")).await.unwrap();
+        unix.send_item(tlv::Interest::new("/b")).await.unwrap();
+        for name in ["/a", "/b"] {
+            let item = peer.recv_item().await.unwrap().unwrap();
+            let interest = tlv::Interest::decode_from_generic(item).unwrap();
+            assert_eq!(interest.name.to_string(), name);
         }
 
-        Ok(bytes.freeze())
+        drop(unix);
+        assert!(peer.recv_item().await.unwrap().is_none());
     }
 }
@@ -0,0 +1,218 @@
+use futures::ready;
+use tokio_tungstenite::tungstenite;
+use tokio_tungstenite::WebSocketStream;
+use tokio_util::codec;
+
+use tungstenite::protocol::WebSocketConfig;
+use tungstenite::Message;
+
+use super::*;
+
+/// WebSocket link as used by the browser clients, each binary message carries a single packet
+#[derive(Debug)]
+pub struct Ws {
+    stream: WebSocketStream<net::TcpStream>,
+    persistency: face::FacePersistency,
+}
+
+impl Ws {
+    /// Connect to the WebSocket server at `remote`, requesting `path`
+    pub async fn new(remote: SocketAddr, path: &str) -> io::Result<Self> {
+        let socket = net::TcpStream::connect(remote).await?;
+        let request = format!("{}{}{remote}{path}", face::Ws::PREFIX, face::URI_DELIMITER);
+        let (stream, _response) =
+            tokio_tungstenite::client_async_with_config(request, socket, Some(Self::config()))
+                .await
+                .map_err(ws_error)?;
+        let persistency = face::FacePersistency::Persistent;
+        Ok(Self {
+            stream,
+            persistency,
+        })
+    }
+
+    /// Complete the server side of the handshake on a connection accepted by a [`Channel`].
+    /// The face over it lasts as long as the client stays connected.
+    pub async fn accept(socket: net::TcpStream) -> io::Result<Self> {
+        let stream = tokio_tungstenite::accept_async_with_config(socket, Some(Self::config()))
+            .await
+            .map_err(ws_error)?;
+        let persistency = face::FacePersistency::OnDemand;
+        Ok(Self {
+            stream,
+            persistency,
+        })
+    }
+
+    /// No message or frame may be larger than a single packet
+    fn config() -> WebSocketConfig {
+        let max = face::Mtu::MAX_NDN_PACKET_SIZE.to_usize();
+        WebSocketConfig {
+            max_message_size: Some(max),
+            max_frame_size: Some(max),
+            ..WebSocketConfig::default()
+        }
+    }
+
+    fn socket(&self) -> &net::TcpStream {
+        self.stream.get_ref()
+    }
+
+    fn stream(self: Pin<&mut Self>) -> Pin<&mut WebSocketStream<net::TcpStream>> {
+        Pin::new(&mut self.get_mut().stream)
+    }
+
+    /// Messages are not streams, a message must hold exactly one TLV
+    fn decode(message: &[u8]) -> io::Result<tlv::Generic> {
+        let mut src = BytesMut::from(message);
+        codec::Decoder::decode(&mut TlvCodec::new(), &mut src)?
+            .filter(|_| src.is_empty())
+            .ok_or_else(|| {
+                io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    "WebSocket message must hold exactly one packet",
+                )
+            })
+    }
+}
+
+impl Transport for Ws {
+    fn local_uri(&self) -> io::Result<face::LocalUri> {
+        socket_uri(face::Ws::PREFIX, self.socket().local_addr()).map(Into::into)
+    }
+
+    fn remote_uri(&self) -> io::Result<face::Uri> {
+        socket_uri(face::Ws::PREFIX, self.socket().peer_addr()).map(Into::into)
+    }
+
+    fn face_scope(&self) -> face::FaceScope {
+        scope_of(self.socket().local_addr(), self.socket().peer_addr())
+    }
+
+    fn persistency(&self) -> face::FacePersistency {
+        self.persistency
+    }
+
+    fn send_queue_length(&self) -> Option<usize> {
+        queue::outq(self.socket())
+    }
+}
+
+impl Stream for Ws {
+    type Item = io::Result<tlv::Generic>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        loop {
+            let message = match ready!(self.as_mut().stream().poll_next(cx)) {
+                Some(Ok(message)) => message,
+                None | Some(Err(tungstenite::Error::ConnectionClosed)) => return Poll::Ready(None),
+                Some(Err(error)) => return Poll::Ready(Some(Err(ws_error(error)))),
+            };
+            match message {
+                Message::Binary(message) => return Poll::Ready(Some(Self::decode(&message))),
+                Message::Close(_) => return Poll::Ready(None),
+                // Pings are answered by the WebSocket stream itself
+                message => tracing::trace!(?message, "Ignoring WebSocket message"),
+            }
+        }
+    }
+}
+
+impl Sink<Bytes> for Ws {
+    type Error = io::Error;
+
+    fn poll_ready(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        self.stream().poll_ready(cx).map_err(ws_error)
+    }
+
+    fn start_send(self: Pin<&mut Self>, bytes: Bytes) -> io::Result<()> {
+        let message = Message::Binary(bytes.to_vec());
+        self.stream().start_send(message).map_err(ws_error)
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        self.stream().poll_flush(cx).map_err(ws_error)
+    }
+
+    fn poll_close(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        self.stream().poll_close(cx).map_err(ws_error)
+    }
+}
+
+fn ws_error(error: tungstenite::Error) -> io::Error {
+    match error {
+        tungstenite::Error::Io(error) => error,
+        tungstenite::Error::ConnectionClosed | tungstenite::Error::AlreadyClosed => {
+            io::Error::new(io::ErrorKind::BrokenPipe, error)
+        }
+        error => io::Error::new(io::ErrorKind::InvalidData, error),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// WebSocket channel on loopback along with the address to connect to
+    async fn channel() -> (Channel, SocketAddr) {
+        let local = face::LocalUri::from("ws://127.0.0.1:0");
+        let channel = Channel::new(local.to_addr().await.unwrap()).await.unwrap();
+        let face::Addr::Ws(remote) = channel.local_uri().unwrap().to_addr().await.unwrap() else {
+            panic!("Not a WebSocket channel");
+        };
+        (channel, remote.addr)
+    }
+
+    #[tokio::test]
+    async fn exchange_over_channel() {
+        let (channel, remote) = channel().await;
+        let (client, server) = tokio::join!(Ws::new(remote, "/"), async {
+            channel.accept().await.unwrap().await
+        });
+        let (mut client, mut server) = (client.unwrap(), server.unwrap());
+        assert_eq!(client.persistency(), face::FacePersistency::Persistent);
+        assert_eq!(server.persistency(), face::FacePersistency::OnDemand);
+        assert_eq!(server.face_scope(), face::FaceScope::Local);
+
+        client.send_item(tlv::Interest::new("/a")).await.unwrap();
+        let item = server.recv_item().await.unwrap().unwrap();
+        assert_eq!(item.r#type, tlv::Type::Interest);
+        server.send_item(tlv::Data::new("/a")).await.unwrap();
+        let item = client.recv_item().await.unwrap().unwrap();
+        assert_eq!(item.r#type, tlv::Type::Data);
+
+        client.close().await.unwrap();
+        assert!(server.recv_item().await.unwrap().is_none());
+    }
+
+    #[tokio::test]
+    async fn oversized_messages() {
+        let (channel, remote) = channel().await;
+        let (client, server) = tokio::join!(Ws::new(remote, "/"), async {
+            channel.accept().await.unwrap().await
+        });
+        let (mut client, mut server) = (client.unwrap(), server.unwrap());
+
+        let mut data = tlv::Data::new("/big");
+        let max = face::Mtu::MAX_NDN_PACKET_SIZE.to_usize();
+        data.content = Some(tlv::Content::new(vec![0; max]));
+        client.send_item(data).await.unwrap();
+        // Refused by the WebSocket stream before being buffered whole
+        let error = server.recv_item().await.unwrap_err();
+        let error = error
+            .get_ref()
+            .unwrap()
+            .downcast_ref::<tungstenite::Error>();
+        assert!(matches!(error, Some(tungstenite::Error::Capacity(_))));
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn handshake_timeout() {
+        let (channel, remote) = channel().await;
+        // Connected, but never asking for the upgrade
+        let _socket = net::TcpStream::connect(remote).await.unwrap();
+        let handshake = channel.accept().await.unwrap();
+        let error = handshake.await.unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::TimedOut);
+    }
+}